@@ -0,0 +1,338 @@
+/*!
+ * Bridge Merkle - Rust Client Library
+ *
+ * Inclusion proofs for privacy_bridge outbound epoch roots
+ *
+ * Features:
+ * - Rebuilds an epoch tree from `OutboundMessageQueued` events
+ * - Checks the rebuilt root against `EpochRootPublished`
+ * - Fixed-depth inclusion proofs for destination-chain contracts
+ * - Proof verification using the on-chain hashing rules
+ *
+ * Tree layout (shared with the Solidity verifier):
+ *   leaf  = keccak256(0x00 || encodePacked(transfer))   (see `outbound_leaf`)
+ *   node  = keccak256(0x01 || left || right)
+ *   depth = OUTBOUND_TREE_DEPTH, empty leaves are zero
+ */
+
+use privacy_bridge::{
+    outbound_node, outbound_zero_hashes, EpochRootPublished, OutboundMessageQueued,
+    MAX_EPOCH_LEAVES, OUTBOUND_TREE_DEPTH,
+};
+
+use crate::bridge_events::{decode_events, BridgeEvent};
+
+/// Proof that a transfer is included in an epoch root
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InclusionProof {
+    pub epoch: u64,
+    pub leaf_index: u32,
+    pub leaf: [u8; 32],
+    /// Sibling hashes from the leaf level up; bit `i` of `leaf_index` says
+    /// whether the node at level `i` is a right child
+    pub siblings: [[u8; 32]; OUTBOUND_TREE_DEPTH],
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MerkleError {
+    /// No `OutboundMessageQueued` event for this leaf index
+    MissingLeaf(u32),
+    /// Two different leaves claim the same index
+    ConflictingLeaf(u32),
+    /// More leaves than the tree depth allows
+    TooManyLeaves,
+    /// Rebuilt root does not match the published root
+    RootMismatch,
+    /// Transfer is not part of this epoch
+    UnknownTransfer,
+}
+
+/// All leaves of one outbound epoch, in insertion order
+pub struct EpochTree {
+    epoch: u64,
+    tx_ids: Vec<[u8; 32]>,
+    /// `levels[0]` is the leaf level; each level holds only non-empty nodes
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl EpochTree {
+    /// Rebuild an epoch from its queued-message events, in any order
+    pub fn from_events<I>(epoch: u64, events: I) -> Result<Self, MerkleError>
+    where
+        I: IntoIterator<Item = OutboundMessageQueued>,
+    {
+        let mut slots: Vec<Option<([u8; 32], [u8; 32])>> = Vec::new();
+        for event in events.into_iter().filter(|event| event.epoch == epoch) {
+            let index = event.leaf_index as usize;
+            if index >= MAX_EPOCH_LEAVES as usize {
+                return Err(MerkleError::TooManyLeaves);
+            }
+            if slots.len() <= index {
+                slots.resize(index + 1, None);
+            }
+            match slots[index] {
+                Some((leaf, _)) if leaf != event.leaf => {
+                    return Err(MerkleError::ConflictingLeaf(event.leaf_index));
+                }
+                _ => slots[index] = Some((event.leaf, event.tx_id)),
+            }
+        }
+
+        let mut leaves = Vec::with_capacity(slots.len());
+        let mut tx_ids = Vec::with_capacity(slots.len());
+        for (index, slot) in slots.into_iter().enumerate() {
+            let (leaf, tx_id) = slot.ok_or(MerkleError::MissingLeaf(index as u32))?;
+            leaves.push(leaf);
+            tx_ids.push(tx_id);
+        }
+
+        Ok(Self::build(epoch, leaves, tx_ids))
+    }
+
+    /// Rebuild an epoch from raw transaction logs
+    pub fn from_logs<'a, I>(epoch: u64, logs: I) -> Result<Self, MerkleError>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let events = decode_events(logs).into_iter().filter_map(|event| match event {
+            BridgeEvent::OutboundMessageQueued(event) => Some(event),
+            _ => None,
+        });
+        Self::from_events(epoch, events)
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn leaf_count(&self) -> u32 {
+        self.levels[0].len() as u32
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        match self.levels[OUTBOUND_TREE_DEPTH].first() {
+            Some(root) => *root,
+            None => {
+                let top = outbound_zero_hashes()[OUTBOUND_TREE_DEPTH - 1];
+                outbound_node(&top, &top)
+            }
+        }
+    }
+
+    /// Check the rebuilt tree against the root the program published
+    pub fn check_published(&self, published: &EpochRootPublished) -> Result<(), MerkleError> {
+        if published.epoch != self.epoch
+            || published.leaf_count != self.leaf_count()
+            || published.root != self.root()
+        {
+            return Err(MerkleError::RootMismatch);
+        }
+        Ok(())
+    }
+
+    /// Inclusion proof for the leaf at `leaf_index`
+    pub fn proof(&self, leaf_index: u32) -> Option<InclusionProof> {
+        let leaf = *self.levels[0].get(leaf_index as usize)?;
+        let zeros = outbound_zero_hashes();
+
+        let mut siblings = [[0u8; 32]; OUTBOUND_TREE_DEPTH];
+        let mut index = leaf_index as usize;
+        for (level, sibling) in siblings.iter_mut().enumerate() {
+            *sibling = self.levels[level]
+                .get(index ^ 1)
+                .copied()
+                .unwrap_or(zeros[level]);
+            index >>= 1;
+        }
+
+        Some(InclusionProof {
+            epoch: self.epoch,
+            leaf_index,
+            leaf,
+            siblings,
+        })
+    }
+
+    /// Inclusion proof for a transfer by its `BridgeTransaction::id`
+    pub fn proof_for_tx(&self, tx_id: &[u8; 32]) -> Result<InclusionProof, MerkleError> {
+        let index = self
+            .tx_ids
+            .iter()
+            .position(|id| id == tx_id)
+            .ok_or(MerkleError::UnknownTransfer)?;
+        self.proof(index as u32).ok_or(MerkleError::UnknownTransfer)
+    }
+
+    fn build(epoch: u64, leaves: Vec<[u8; 32]>, tx_ids: Vec<[u8; 32]>) -> Self {
+        let zeros = outbound_zero_hashes();
+        let mut levels = Vec::with_capacity(OUTBOUND_TREE_DEPTH + 1);
+        levels.push(leaves);
+
+        for level in 0..OUTBOUND_TREE_DEPTH {
+            let next = levels[level]
+                .chunks(2)
+                .map(|pair| outbound_node(&pair[0], pair.get(1).unwrap_or(&zeros[level])))
+                .collect();
+            levels.push(next);
+        }
+
+        Self { epoch, tx_ids, levels }
+    }
+}
+
+/// Verify an inclusion proof against an epoch root
+///
+/// Mirrors the check a destination-chain contract performs.
+pub fn verify_inclusion(root: &[u8; 32], proof: &InclusionProof) -> bool {
+    let mut node = proof.leaf;
+    let mut index = proof.leaf_index;
+    for sibling in proof.siblings.iter() {
+        node = if index & 1 == 0 {
+            outbound_node(&node, sibling)
+        } else {
+            outbound_node(sibling, &node)
+        };
+        index >>= 1;
+    }
+    index == 0 && node == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use privacy_bridge::{OutboundEpoch, EVENT_SCHEMA_VERSION};
+    use solana_program::pubkey::Pubkey;
+
+    fn queued(epoch: u64, leaf_index: u32) -> OutboundMessageQueued {
+        let mut leaf = [0u8; 32];
+        leaf[..4].copy_from_slice(&leaf_index.to_le_bytes());
+        leaf[31] = 0xff;
+        OutboundMessageQueued {
+            version: EVENT_SCHEMA_VERSION,
+            actor: Pubkey::new_unique(),
+            tx_id: [leaf_index as u8 + 1; 32],
+            epoch,
+            leaf_index,
+            leaf,
+            slot: 0,
+        }
+    }
+
+    fn queued_all(epoch: u64, count: u32) -> Vec<OutboundMessageQueued> {
+        (0..count).map(|i| queued(epoch, i)).collect()
+    }
+
+    /// Root the program computes for the same leaves
+    fn onchain_root(events: &[OutboundMessageQueued]) -> [u8; 32] {
+        let mut epoch = OutboundEpoch {
+            epoch_id: 0,
+            frontier: [[0; 32]; OUTBOUND_TREE_DEPTH],
+            leaf_count: 0,
+            started_at: 0,
+            finalized: false,
+            finalized_at: 0,
+            root: [0; 32],
+            withheld: false,
+        };
+        for event in events {
+            assert_eq!(epoch.append(event.leaf).unwrap(), event.leaf_index);
+        }
+        epoch.compute_root()
+    }
+
+    #[test]
+    fn root_matches_the_program() {
+        for count in [0u32, 1, 2, 3, 5, 8] {
+            let tree = EpochTree::from_events(7, queued_all(7, count)).unwrap();
+            assert_eq!(tree.leaf_count(), count);
+            assert_eq!(tree.root(), onchain_root(&queued_all(7, count)), "{count} leaves");
+        }
+    }
+
+    #[test]
+    fn every_leaf_has_a_valid_proof() {
+        // Events may arrive out of order and from other epochs
+        let mut shuffled = queued_all(7, 5);
+        shuffled.reverse();
+        shuffled.push(queued(8, 0));
+        let tree = EpochTree::from_events(7, shuffled).unwrap();
+        let root = tree.root();
+
+        for event in &queued_all(7, 5) {
+            let proof = tree.proof_for_tx(&event.tx_id).unwrap();
+            assert_eq!(proof.leaf, event.leaf);
+            assert!(verify_inclusion(&root, &proof));
+        }
+        assert!(tree.proof(5).is_none());
+        assert_eq!(tree.proof_for_tx(&[0; 32]), Err(MerkleError::UnknownTransfer));
+    }
+
+    #[test]
+    fn altered_proofs_fail() {
+        let tree = EpochTree::from_events(7, queued_all(7, 3)).unwrap();
+        let root = tree.root();
+        let proof = tree.proof(1).unwrap();
+
+        let mut wrong_leaf = proof.clone();
+        wrong_leaf.leaf[0] ^= 1;
+        assert!(!verify_inclusion(&root, &wrong_leaf));
+
+        let mut wrong_sibling = proof.clone();
+        wrong_sibling.siblings[3][0] ^= 1;
+        assert!(!verify_inclusion(&root, &wrong_sibling));
+
+        let mut wrong_index = proof.clone();
+        wrong_index.leaf_index = 3;
+        assert!(!verify_inclusion(&root, &wrong_index));
+
+        let mut out_of_range = proof;
+        out_of_range.leaf_index |= 1 << OUTBOUND_TREE_DEPTH;
+        assert!(!verify_inclusion(&root, &out_of_range));
+    }
+
+    #[test]
+    fn gaps_and_conflicts_are_rejected() {
+        let gap = vec![queued(7, 0), queued(7, 2)];
+        assert_eq!(EpochTree::from_events(7, gap).err(), Some(MerkleError::MissingLeaf(1)));
+
+        let mut conflict = queued(7, 0);
+        conflict.leaf = [9; 32];
+        let conflicting = vec![queued(7, 0), conflict];
+        assert_eq!(
+            EpochTree::from_events(7, conflicting).err(),
+            Some(MerkleError::ConflictingLeaf(0))
+        );
+
+        // The same event seen twice is not a conflict
+        let replayed = vec![queued(7, 0), queued(7, 0)];
+        assert_eq!(EpochTree::from_events(7, replayed).unwrap().leaf_count(), 1);
+
+        let too_far = vec![queued(7, 1 << OUTBOUND_TREE_DEPTH)];
+        assert_eq!(EpochTree::from_events(7, too_far).err(), Some(MerkleError::TooManyLeaves));
+    }
+
+    #[test]
+    fn published_root_is_checked() {
+        let tree = EpochTree::from_events(7, queued_all(7, 3)).unwrap();
+        let published = |root, leaf_count| EpochRootPublished {
+            version: EVENT_SCHEMA_VERSION,
+            actor: Pubkey::new_unique(),
+            epoch: 7,
+            root,
+            leaf_count,
+            started_at: 0,
+            finalized_at: 0,
+            slot: 0,
+        };
+        let root = tree.root();
+        assert_eq!(tree.check_published(&published(root, 3)), Ok(()));
+
+        let mut wrong_root = root;
+        wrong_root[0] ^= 1;
+        assert_eq!(
+            tree.check_published(&published(wrong_root, 3)),
+            Err(MerkleError::RootMismatch)
+        );
+        assert_eq!(tree.check_published(&published(root, 4)), Err(MerkleError::RootMismatch));
+    }
+}
@@ -0,0 +1,300 @@
+/*!
+ * Bridge Notes - Rust Client Library
+ *
+ * Encrypted notes attached to privacy_bridge locks
+ *
+ * Features:
+ * - ChaCha20-Poly1305 note encryption under a random content key
+ * - X25519 (ECIES-style) key wrapping for the recipient and the auditor
+ * - Decryption with either viewing key
+ * - Scanning of `AssetLocked` events from program logs (via bridge_events)
+ */
+
+use anchor_lang::prelude::borsh;
+use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand_core::{CryptoRng, RngCore};
+use solana_program::keccak;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use privacy_bridge::{AssetLocked, EncryptedNote};
+
+use crate::bridge_events::{decode_events, BridgeEvent};
+
+/// Domain separator for key-wrapping key derivation
+const NOTE_DOMAIN: &[u8] = b"safemask-bridge-note-v1";
+
+/// Plaintext carried inside an `EncryptedNote`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NotePlaintext {
+    pub amount: u64,
+    pub recipient_commitment: [u8; 32],
+    /// Opens `recipient_commitment`; passed as `recipient_blinding` to unlock
+    pub blinding: [u8; 32],
+    pub memo: Vec<u8>,
+}
+
+/// A lock event whose note we could decrypt
+pub struct DecryptedLock {
+    pub event: AssetLocked,
+    pub note: NotePlaintext,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum NoteError {
+    /// Serialized note exceeds `EncryptedNote::MAX_CIPHERTEXT_LEN`
+    TooLarge,
+    /// AEAD encryption failed
+    Encryption,
+}
+
+#[derive(Clone, Copy)]
+enum Role {
+    Recipient,
+    Auditor,
+}
+
+impl Role {
+    fn label(self) -> &'static [u8] {
+        match self {
+            Role::Recipient => b"recipient",
+            Role::Auditor => b"auditor",
+        }
+    }
+}
+
+/// Encrypt a note to the recipient and, if configured, the bridge auditor
+///
+/// Pass `Bridge::auditor_viewing_key` as `auditor`; an all-zero key leaves
+/// the auditor slot empty.
+pub fn encrypt_note<R: RngCore + CryptoRng>(
+    plaintext: &NotePlaintext,
+    recipient: &[u8; 32],
+    auditor: &[u8; 32],
+    rng: &mut R,
+) -> Result<EncryptedNote, NoteError> {
+    let mut secret_bytes = [0u8; 32];
+    rng.fill_bytes(&mut secret_bytes);
+    let ephemeral_secret = StaticSecret::from(secret_bytes);
+    let ephemeral_pubkey = PublicKey::from(&ephemeral_secret).to_bytes();
+
+    let mut content_key = [0u8; 32];
+    rng.fill_bytes(&mut content_key);
+    let mut nonce = [0u8; 12];
+    rng.fill_bytes(&mut nonce);
+
+    let encoded = plaintext.try_to_vec().map_err(|_| NoteError::Encryption)?;
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&content_key))
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload { msg: &encoded, aad: &ephemeral_pubkey },
+        )
+        .map_err(|_| NoteError::Encryption)?;
+    if ciphertext.len() > EncryptedNote::MAX_CIPHERTEXT_LEN {
+        return Err(NoteError::TooLarge);
+    }
+
+    let recipient_wrapped_key =
+        wrap_key(&ephemeral_secret, &ephemeral_pubkey, recipient, Role::Recipient, &content_key)?;
+    let auditor_wrapped_key = if *auditor == [0; 32] {
+        [0; 48]
+    } else {
+        wrap_key(&ephemeral_secret, &ephemeral_pubkey, auditor, Role::Auditor, &content_key)?
+    };
+
+    Ok(EncryptedNote {
+        ephemeral_pubkey,
+        nonce,
+        ciphertext,
+        recipient_wrapped_key,
+        auditor_wrapped_key,
+    })
+}
+
+/// Decrypt a note with a recipient or auditor viewing key
+pub fn decrypt_note(note: &EncryptedNote, viewing_key: &StaticSecret) -> Option<NotePlaintext> {
+    let content_key = unwrap_key(note, viewing_key, Role::Recipient)
+        .or_else(|| unwrap_key(note, viewing_key, Role::Auditor))?;
+
+    let encoded = ChaCha20Poly1305::new(Key::from_slice(&content_key))
+        .decrypt(
+            Nonce::from_slice(&note.nonce),
+            Payload { msg: &note.ciphertext, aad: &note.ephemeral_pubkey },
+        )
+        .ok()?;
+    NotePlaintext::try_from_slice(&encoded).ok()
+}
+
+/// Scan program logs for locks whose notes this viewing key can open
+///
+/// An auditor key opens every note; a recipient key only its own.
+pub fn scan_logs<'a, I>(logs: I, viewing_key: &StaticSecret) -> Vec<DecryptedLock>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    decode_events(logs)
+        .into_iter()
+        .filter_map(|event| match event {
+            BridgeEvent::AssetLocked(event) => Some(event),
+            _ => None,
+        })
+        .filter_map(|event| {
+            let note = decrypt_note(event.encrypted_note.as_ref()?, viewing_key)?;
+            Some(DecryptedLock { event, note })
+        })
+        .collect()
+}
+
+fn wrap_key(
+    ephemeral_secret: &StaticSecret,
+    ephemeral_pubkey: &[u8; 32],
+    receiver: &[u8; 32],
+    role: Role,
+    content_key: &[u8; 32],
+) -> Result<[u8; 48], NoteError> {
+    let shared = ephemeral_secret.diffie_hellman(&PublicKey::from(*receiver));
+    let kek = derive_kek(shared.as_bytes(), ephemeral_pubkey, role);
+
+    // Each key-encryption key is used exactly once, so a fixed nonce is safe
+    let wrapped = ChaCha20Poly1305::new(Key::from_slice(&kek))
+        .encrypt(Nonce::from_slice(&[0u8; 12]), content_key.as_ref())
+        .map_err(|_| NoteError::Encryption)?;
+
+    let mut out = [0u8; 48];
+    out.copy_from_slice(&wrapped);
+    Ok(out)
+}
+
+fn unwrap_key(note: &EncryptedNote, viewing_key: &StaticSecret, role: Role) -> Option<[u8; 32]> {
+    let wrapped = match role {
+        Role::Recipient => &note.recipient_wrapped_key,
+        Role::Auditor => &note.auditor_wrapped_key,
+    };
+    if *wrapped == [0; 48] {
+        return None;
+    }
+
+    let shared = viewing_key.diffie_hellman(&PublicKey::from(note.ephemeral_pubkey));
+    let kek = derive_kek(shared.as_bytes(), &note.ephemeral_pubkey, role);
+    let content_key = ChaCha20Poly1305::new(Key::from_slice(&kek))
+        .decrypt(Nonce::from_slice(&[0u8; 12]), wrapped.as_ref())
+        .ok()?;
+
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&content_key);
+    Some(out)
+}
+
+fn derive_kek(shared: &[u8; 32], ephemeral_pubkey: &[u8; 32], role: Role) -> [u8; 32] {
+    keccak::hashv(&[NOTE_DOMAIN, role.label(), shared, ephemeral_pubkey]).to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+    use base64::Engine;
+    use rand_core::OsRng;
+    use solana_program::pubkey::Pubkey;
+
+    fn keypair() -> (StaticSecret, [u8; 32]) {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let public = PublicKey::from(&secret).to_bytes();
+        (secret, public)
+    }
+
+    fn plaintext(memo: Vec<u8>) -> NotePlaintext {
+        NotePlaintext {
+            amount: 1_000,
+            recipient_commitment: [1; 32],
+            blinding: [2; 32],
+            memo,
+        }
+    }
+
+    #[test]
+    fn recipient_and_auditor_decrypt() {
+        let (recipient, recipient_pub) = keypair();
+        let (auditor, auditor_pub) = keypair();
+        let (stranger, _) = keypair();
+        let note = encrypt_note(&plaintext(b"invoice 7".to_vec()), &recipient_pub, &auditor_pub, &mut OsRng)
+            .unwrap();
+
+        assert_eq!(decrypt_note(&note, &recipient), Some(plaintext(b"invoice 7".to_vec())));
+        assert_eq!(decrypt_note(&note, &auditor), Some(plaintext(b"invoice 7".to_vec())));
+        assert_eq!(decrypt_note(&note, &stranger), None);
+    }
+
+    #[test]
+    fn zero_auditor_key_leaves_the_slot_empty() {
+        let (recipient, recipient_pub) = keypair();
+        let (auditor, _) = keypair();
+        let note = encrypt_note(&plaintext(Vec::new()), &recipient_pub, &[0; 32], &mut OsRng).unwrap();
+
+        assert_eq!(note.auditor_wrapped_key, [0; 48]);
+        assert!(decrypt_note(&note, &recipient).is_some());
+        assert!(decrypt_note(&note, &auditor).is_none());
+    }
+
+    #[test]
+    fn tampered_note_does_not_decrypt() {
+        let (recipient, recipient_pub) = keypair();
+        let mut note = encrypt_note(&plaintext(Vec::new()), &recipient_pub, &[0; 32], &mut OsRng).unwrap();
+        note.ciphertext[0] ^= 1;
+        assert!(decrypt_note(&note, &recipient).is_none());
+    }
+
+    #[test]
+    fn oversized_memo_is_rejected() {
+        let (_, recipient_pub) = keypair();
+        let memo = vec![0; EncryptedNote::MAX_CIPHERTEXT_LEN];
+        assert_eq!(
+            encrypt_note(&plaintext(memo), &recipient_pub, &[0; 32], &mut OsRng).err(),
+            Some(NoteError::TooLarge)
+        );
+    }
+
+    #[test]
+    fn scan_logs_opens_our_notes() {
+        let (recipient, recipient_pub) = keypair();
+        let (_, other_pub) = keypair();
+        let lock = |encrypted_note| AssetLocked {
+            version: privacy_bridge::EVENT_SCHEMA_VERSION,
+            actor: Pubkey::new_unique(),
+            tx_id: [3; 32],
+            sender: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            recipient_commitment: [5; 32],
+            source_chain: 1,
+            target_chain: 2,
+            amount: 1_000,
+            commitment: [4; 32],
+            stealth: None,
+            encrypted_note,
+            slot: 9,
+        };
+        let ours = encrypt_note(&plaintext(Vec::new()), &recipient_pub, &[0; 32], &mut OsRng).unwrap();
+        let theirs = encrypt_note(&plaintext(Vec::new()), &other_pub, &[0; 32], &mut OsRng).unwrap();
+
+        let data_line = |event: AssetLocked| {
+            format!(
+                "Program data: {}",
+                base64::engine::general_purpose::STANDARD.encode(event.data())
+            )
+        };
+        let bridge = privacy_bridge::ID.to_string();
+        let logs = [
+            format!("Program {bridge} invoke [1]"),
+            data_line(lock(Some(theirs))),
+            data_line(lock(None)),
+            data_line(lock(Some(ours))),
+            format!("Program {bridge} success"),
+        ];
+
+        let found = scan_logs(logs.iter().map(String::as_str), &recipient);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].note, plaintext(Vec::new()));
+        assert_eq!(found[0].event.tx_id, [3; 32]);
+    }
+}
@@ -0,0 +1,9 @@
+//! Off-chain client library for the privacy_bridge program
+
+pub mod bridge_merkle;
+pub mod bridge_notes;
+pub mod stealth_address;
+
+/// Event decoding lives in its own crate so indexers need not pull in the
+/// note and stealth-address cryptography
+pub use bridge_events;
@@ -0,0 +1,346 @@
+/*!
+ * Stealth Addresses - Rust Client Library
+ *
+ * Off-chain stealth address protocol for privacy_bridge recipients
+ *
+ * Features:
+ * - Public meta-address (spend key + view key)
+ * - One-time stealth addresses per lock
+ * - View-key scanning of `AssetLocked` events with view tags
+ * - Spending key derivation and signing for the stealth account
+ *
+ * Key derivation, as proven by circuits/circom/stealth_address_ed25519.circom:
+ *   sharedSecret   = v * R                (sender: r * V)
+ *   stealthPrivKey = H(sharedSecret) + s
+ *   stealthAddress = stealthPrivKey * G   (= S + H(sharedSecret) * G)
+ *
+ * The address must be an ed25519 public key that owns the recipient's token
+ * account, so the shared secret is an ed25519 point and H is
+ * domain-separated keccak256 reduced mod the ed25519 group order, as
+ * available on-chain through the keccak syscall. The original
+ * circuits/circom/stealth_address.circom works in the BN254 scalar field
+ * with Poseidon and cannot prove these addresses.
+ */
+
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha512};
+use solana_program::keccak;
+use solana_program::pubkey::Pubkey;
+
+use privacy_bridge::{AssetLocked, StealthMeta};
+
+/// Domain separator for the shared-secret hash
+const STEALTH_DOMAIN: &[u8] = b"safemask-stealth-v1";
+
+/// Recipient's public meta-address, shared with senders
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StealthMetaAddress {
+    pub spend_pubkey: [u8; 32],
+    pub view_pubkey: [u8; 32],
+}
+
+impl StealthMetaAddress {
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.spend_pubkey);
+        bytes[32..].copy_from_slice(&self.view_pubkey);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; 64]) -> Result<Self, StealthError> {
+        let mut spend_pubkey = [0u8; 32];
+        let mut view_pubkey = [0u8; 32];
+        spend_pubkey.copy_from_slice(&bytes[..32]);
+        view_pubkey.copy_from_slice(&bytes[32..]);
+
+        // Reject encodings that are not valid curve points
+        decompress(&spend_pubkey)?;
+        decompress(&view_pubkey)?;
+
+        Ok(Self { spend_pubkey, view_pubkey })
+    }
+}
+
+/// Recipient's secret keys
+pub struct StealthKeys {
+    spend_secret: Scalar,
+    view_secret: Scalar,
+}
+
+impl StealthKeys {
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self {
+            spend_secret: random_scalar(rng),
+            view_secret: random_scalar(rng),
+        }
+    }
+
+    pub fn from_secrets(spend_secret: [u8; 32], view_secret: [u8; 32]) -> Self {
+        Self {
+            spend_secret: Scalar::from_bytes_mod_order(spend_secret),
+            view_secret: Scalar::from_bytes_mod_order(view_secret),
+        }
+    }
+
+    pub fn meta_address(&self) -> StealthMetaAddress {
+        StealthMetaAddress {
+            spend_pubkey: EdwardsPoint::mul_base(&self.spend_secret).compress().to_bytes(),
+            view_pubkey: EdwardsPoint::mul_base(&self.view_secret).compress().to_bytes(),
+        }
+    }
+
+    /// View-only half, safe to hand to a scanning service
+    pub fn viewing_key(&self) -> StealthViewingKey {
+        StealthViewingKey {
+            view_secret: self.view_secret,
+            spend_pubkey: self.meta_address().spend_pubkey,
+        }
+    }
+
+    /// Derive the spending key for a lock found by scanning
+    pub fn spending_key(&self, stealth: &StealthMeta) -> Result<StealthSpendingKey, StealthError> {
+        let ephemeral = decompress(&stealth.ephemeral_pubkey)?;
+        let (tweak, _) = shared_tweak(&(self.view_secret * ephemeral));
+        Ok(StealthSpendingKey {
+            secret: self.spend_secret + tweak,
+        })
+    }
+}
+
+/// View key: detects incoming locks but cannot spend them
+pub struct StealthViewingKey {
+    view_secret: Scalar,
+    spend_pubkey: [u8; 32],
+}
+
+impl StealthViewingKey {
+    /// Check whether a stealth lock belongs to this recipient
+    pub fn owns(&self, stealth: &StealthMeta, stealth_address: &[u8; 32]) -> Result<bool, StealthError> {
+        let ephemeral = decompress(&stealth.ephemeral_pubkey)?;
+        let (tweak, view_tag) = shared_tweak(&(self.view_secret * ephemeral));
+        if view_tag != stealth.view_tag {
+            return Ok(false);
+        }
+
+        let expected = decompress(&self.spend_pubkey)? + EdwardsPoint::mul_base(&tweak);
+        Ok(expected.compress().to_bytes() == *stealth_address)
+    }
+
+    /// Filter `AssetLocked` events down to the ones addressed to us
+    ///
+    /// `recipient_commitment` is not part of the event, so the caller pairs
+    /// each event with its `BridgeTransaction::recipient_commitment`.
+    pub fn scan<'a, I>(&self, locks: I) -> Vec<&'a AssetLocked>
+    where
+        I: IntoIterator<Item = (&'a AssetLocked, [u8; 32])>,
+    {
+        locks
+            .into_iter()
+            .filter(|(event, stealth_address)| match &event.stealth {
+                Some(stealth) => self.owns(stealth, stealth_address).unwrap_or(false),
+                None => false,
+            })
+            .map(|(event, _)| event)
+            .collect()
+    }
+}
+
+/// Secret key of a single stealth account
+pub struct StealthSpendingKey {
+    secret: Scalar,
+}
+
+impl StealthSpendingKey {
+    pub fn pubkey(&self) -> Pubkey {
+        Pubkey::new_from_array(EdwardsPoint::mul_base(&self.secret).compress().to_bytes())
+    }
+
+    /// Ed25519 signature over `message` with the derived scalar
+    ///
+    /// The stealth scalar has no seed, so a Solana `Keypair` cannot hold it;
+    /// this produces the same signature format for transaction signing.
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        let public = EdwardsPoint::mul_base(&self.secret).compress();
+
+        // Deterministic nonce derived from the secret and message
+        let prefix = keccak::hashv(&[STEALTH_DOMAIN, b"nonce", self.secret.as_bytes()]).to_bytes();
+        let r = Scalar::from_hash(Sha512::new().chain_update(prefix).chain_update(message));
+        let big_r = EdwardsPoint::mul_base(&r).compress();
+
+        let k = Scalar::from_hash(
+            Sha512::new()
+                .chain_update(big_r.as_bytes())
+                .chain_update(public.as_bytes())
+                .chain_update(message),
+        );
+        let s = r + k * self.secret;
+
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(big_r.as_bytes());
+        signature[32..].copy_from_slice(s.as_bytes());
+        signature
+    }
+}
+
+/// One-time payment data produced by the sender
+pub struct StealthPayment {
+    /// Pass as `recipient_commitment` to `lock_assets`
+    pub stealth_address: [u8; 32],
+    /// Pass as `stealth` to `lock_assets`
+    pub meta: StealthMeta,
+}
+
+/// Derive a fresh stealth address for a recipient's meta-address
+pub fn generate_stealth_payment<R: RngCore + CryptoRng>(
+    recipient: &StealthMetaAddress,
+    rng: &mut R,
+) -> Result<StealthPayment, StealthError> {
+    let spend = decompress(&recipient.spend_pubkey)?;
+    let view = decompress(&recipient.view_pubkey)?;
+
+    let ephemeral_secret = random_scalar(rng);
+    let ephemeral_pubkey = EdwardsPoint::mul_base(&ephemeral_secret).compress().to_bytes();
+
+    let (tweak, view_tag) = shared_tweak(&(ephemeral_secret * view));
+    let stealth_address = (spend + EdwardsPoint::mul_base(&tweak)).compress().to_bytes();
+
+    Ok(StealthPayment {
+        stealth_address,
+        meta: StealthMeta {
+            ephemeral_pubkey,
+            view_tag,
+        },
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StealthError {
+    /// Encoding is not a point on the curve, or is a small-order point
+    InvalidPoint,
+}
+
+/// Hash the shared point into the key tweak and its view tag
+fn shared_tweak(shared: &EdwardsPoint) -> (Scalar, u8) {
+    let hash = keccak::hashv(&[STEALTH_DOMAIN, shared.compress().as_bytes()]).to_bytes();
+    (Scalar::from_bytes_mod_order(hash), hash[0])
+}
+
+fn decompress(bytes: &[u8; 32]) -> Result<EdwardsPoint, StealthError> {
+    let point = CompressedEdwardsY(*bytes)
+        .decompress()
+        .ok_or(StealthError::InvalidPoint)?;
+    if point.is_small_order() {
+        return Err(StealthError::InvalidPoint);
+    }
+    Ok(point)
+}
+
+fn random_scalar<R: RngCore + CryptoRng>(rng: &mut R) -> Scalar {
+    let mut wide = [0u8; 64];
+    rng.fill_bytes(&mut wide);
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::OsRng;
+
+    fn lock_event(stealth: Option<StealthMeta>) -> AssetLocked {
+        AssetLocked {
+            version: privacy_bridge::EVENT_SCHEMA_VERSION,
+            actor: Pubkey::new_unique(),
+            tx_id: [1; 32],
+            sender: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            recipient_commitment: [3; 32],
+            source_chain: 1,
+            target_chain: 2,
+            amount: 1_000,
+            commitment: [2; 32],
+            stealth,
+            encrypted_note: None,
+            slot: 7,
+        }
+    }
+
+    #[test]
+    fn recipient_derives_the_stealth_key() {
+        let keys = StealthKeys::generate(&mut OsRng);
+        let payment = generate_stealth_payment(&keys.meta_address(), &mut OsRng).unwrap();
+
+        assert!(keys.viewing_key().owns(&payment.meta, &payment.stealth_address).unwrap());
+        let spending_key = keys.spending_key(&payment.meta).unwrap();
+        assert_eq!(spending_key.pubkey().to_bytes(), payment.stealth_address);
+    }
+
+    #[test]
+    fn other_recipients_do_not_match() {
+        let keys = StealthKeys::generate(&mut OsRng);
+        let other = StealthKeys::generate(&mut OsRng);
+        let payment = generate_stealth_payment(&keys.meta_address(), &mut OsRng).unwrap();
+
+        assert!(!other.viewing_key().owns(&payment.meta, &payment.stealth_address).unwrap());
+        assert_ne!(
+            other.spending_key(&payment.meta).unwrap().pubkey().to_bytes(),
+            payment.stealth_address
+        );
+    }
+
+    #[test]
+    fn scan_finds_only_our_locks() {
+        let keys = StealthKeys::generate(&mut OsRng);
+        let ours = generate_stealth_payment(&keys.meta_address(), &mut OsRng).unwrap();
+        let theirs = generate_stealth_payment(
+            &StealthKeys::generate(&mut OsRng).meta_address(),
+            &mut OsRng,
+        )
+        .unwrap();
+
+        let ours_event = lock_event(Some(ours.meta));
+        let theirs_event = lock_event(Some(theirs.meta));
+        let plain_event = lock_event(None);
+        let found = keys.viewing_key().scan([
+            (&theirs_event, theirs.stealth_address),
+            (&ours_event, ours.stealth_address),
+            (&plain_event, ours.stealth_address),
+        ]);
+
+        assert_eq!(found.len(), 1);
+        assert!(std::ptr::eq(found[0], &ours_event));
+    }
+
+    #[test]
+    fn stealth_signature_verifies() {
+        let keys = StealthKeys::generate(&mut OsRng);
+        let payment = generate_stealth_payment(&keys.meta_address(), &mut OsRng).unwrap();
+        let spending_key = keys.spending_key(&payment.meta).unwrap();
+        let message = b"unlock";
+        let signature = spending_key.sign(message);
+
+        // s * G == R + H(R || A || m) * A
+        let big_r = decompress(&signature[..32].try_into().unwrap()).unwrap();
+        let s = Scalar::from_canonical_bytes(signature[32..].try_into().unwrap()).unwrap();
+        let public = decompress(&payment.stealth_address).unwrap();
+        let k = Scalar::from_hash(
+            Sha512::new()
+                .chain_update(&signature[..32])
+                .chain_update(payment.stealth_address)
+                .chain_update(message),
+        );
+        assert_eq!(EdwardsPoint::mul_base(&s), big_r + k * public);
+    }
+
+    #[test]
+    fn meta_address_round_trips_and_rejects_bad_points() {
+        let meta = StealthKeys::generate(&mut OsRng).meta_address();
+        assert_eq!(StealthMetaAddress::from_bytes(&meta.to_bytes()).unwrap(), meta);
+
+        // The identity point has small order
+        let mut bytes = meta.to_bytes();
+        bytes[32..].copy_from_slice(&EdwardsPoint::default().compress().to_bytes());
+        assert_eq!(StealthMetaAddress::from_bytes(&bytes), Err(StealthError::InvalidPoint));
+    }
+}
@@ -0,0 +1,342 @@
+/*!
+ * Bridge Events - Rust Client Library
+ *
+ * Decoder for privacy_bridge events emitted to transaction logs
+ *
+ * Features:
+ * - Typed `BridgeEvent` for every event the program emits
+ * - Discriminator-based decoding of `Program data:` log lines
+ * - Invoke-stack tracking so CPI logs from other programs are ignored
+ * - Schema version check on every event
+ */
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::Engine;
+use solana_program::pubkey::Pubkey;
+
+use ::privacy_bridge::*;
+
+/// Anchor event log prefix
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+macro_rules! bridge_events {
+    ($($name:ident),* $(,)?) => {
+        /// Any event emitted by privacy_bridge
+        // Decoded once per log line, so unboxed variants cost little
+        #[allow(clippy::large_enum_variant)]
+        pub enum BridgeEvent {
+            $($name($name),)*
+        }
+
+        impl BridgeEvent {
+            /// Decode discriminator-prefixed event data
+            ///
+            /// The schema version is checked before the body is decoded.
+            pub fn decode(data: &[u8]) -> Result<Self, DecodeError> {
+                if data.len() < 8 {
+                    return Err(DecodeError::TooShort);
+                }
+                let (discriminator, body) = data.split_at(8);
+                $(
+                    if discriminator == $name::DISCRIMINATOR {
+                        check_version(body)?;
+                        return $name::try_from_slice(body)
+                            .map(BridgeEvent::$name)
+                            .map_err(|_| DecodeError::InvalidData);
+                    }
+                )*
+                Err(DecodeError::UnknownDiscriminator)
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(BridgeEvent::$name(_) => stringify!($name),)*
+                }
+            }
+
+            /// Schema version the event was emitted with
+            pub fn version(&self) -> u8 {
+                match self {
+                    $(BridgeEvent::$name(event) => event.version,)*
+                }
+            }
+        }
+    };
+}
+
+bridge_events!(
+    AssetLocked,
+    AssetUnlocked,
+    TransactionRelayed,
+    FinalityAttested,
+    RevertVoteCast,
+    AttestationReverted,
+    RelayerAdded,
+    RelayerFeePaid,
+    OutboundMessageQueued,
+    EpochRootPublished,
+    EmergencyReclaimed,
+    SurplusSwept,
+    ReservesAudited,
+    BridgeInitialized,
+    FeeUpdated,
+    MinConfirmationsUpdated,
+    GovernanceDelayUpdated,
+    EmergencyDelayUpdated,
+    EpochDurationUpdated,
+    ChainRegistered,
+    ChainSupportUpdated,
+    ChainFinalityUpdated,
+    TokenRegistered,
+    RelayerStatusChanged,
+    AuditorKeyUpdated,
+    VerifyingKeyUpdated,
+    BridgePaused,
+    BridgeUnpaused,
+    PauseFlagsUpdated,
+    AssociationSetRegistered,
+    AssociationRootUpdated,
+    AssociationProofVerified,
+    AssociationSetStatusChanged,
+    AuthorityProposed,
+    AuthorityTransferred,
+    AuthorityTransferCancelled,
+    GuardianUpdated,
+    GuardianPaused,
+    ProposalQueued,
+    ProposalApproved,
+    ProposalExecuted,
+    ProposalCancelApproved,
+    ProposalCancelled,
+    OwnersChanged,
+);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Data shorter than the 8-byte discriminator
+    TooShort,
+    /// Discriminator does not match any privacy_bridge event
+    UnknownDiscriminator,
+    /// Borsh body does not match the event layout
+    InvalidData,
+    /// Event from a newer schema than this decoder understands
+    UnsupportedVersion(u8),
+}
+
+/// Decode every privacy_bridge event in a transaction's log messages
+///
+/// Tracks `invoke` / `success` / `failed` lines so `Program data:` entries
+/// emitted by other programs (or by CPIs into them) are skipped. Events from
+/// an unknown newer schema version are returned as errors rather than
+/// silently misread.
+pub fn decode_logs<'a, I>(logs: I) -> Vec<Result<BridgeEvent, DecodeError>>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let program_id = ID.to_string();
+    let mut stack: Vec<String> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        if let Some(invoked) = parse_invoke(line) {
+            stack.push(invoked.to_string());
+            continue;
+        }
+        if is_program_exit(line) {
+            stack.pop();
+            continue;
+        }
+
+        let Some(encoded) = line.strip_prefix(PROGRAM_DATA_PREFIX) else {
+            continue;
+        };
+        if stack.last() != Some(&program_id) {
+            continue;
+        }
+
+        let event = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| DecodeError::InvalidData)
+            .and_then(|data| BridgeEvent::decode(&data));
+        events.push(event);
+    }
+
+    events
+}
+
+/// Decode only the events that parsed successfully
+pub fn decode_events<'a, I>(logs: I) -> Vec<BridgeEvent>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    decode_logs(logs).into_iter().filter_map(|event| event.ok()).collect()
+}
+
+/// Every event body starts with its schema version. Newer schemas may
+/// append fields, so they are rejected before the body is decoded.
+fn check_version(body: &[u8]) -> Result<(), DecodeError> {
+    match body.first() {
+        None => Err(DecodeError::InvalidData),
+        Some(&version) if version > EVENT_SCHEMA_VERSION => {
+            Err(DecodeError::UnsupportedVersion(version))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+/// `Program <id> invoke [<depth>]`
+fn parse_invoke(line: &str) -> Option<Pubkey> {
+    let rest = line.strip_prefix("Program ")?;
+    let (id, tail) = rest.split_once(' ')?;
+    if !tail.starts_with("invoke [") {
+        return None;
+    }
+    id.parse().ok()
+}
+
+/// `Program <id> success` or `Program <id> failed: <reason>`
+///
+/// `<id>` must be a valid pubkey, so `Program log: success` is not an exit.
+fn is_program_exit(line: &str) -> bool {
+    let Some(rest) = line.strip_prefix("Program ") else {
+        return false;
+    };
+    let Some((id, tail)) = rest.split_once(' ') else {
+        return false;
+    };
+    (tail == "success" || tail.starts_with("failed:")) && id.parse::<Pubkey>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+
+    fn data_line(event: &impl Event) -> String {
+        format!(
+            "{PROGRAM_DATA_PREFIX}{}",
+            base64::engine::general_purpose::STANDARD.encode(event.data())
+        )
+    }
+
+    fn fee_updated(version: u8) -> FeeUpdated {
+        FeeUpdated {
+            version,
+            actor: Pubkey::new_unique(),
+            old_fee: 10,
+            new_fee: 20,
+            slot: 42,
+        }
+    }
+
+    #[test]
+    fn decodes_events_from_the_bridge_only() {
+        let bridge = ID.to_string();
+        let other = Pubkey::new_unique().to_string();
+        let event = fee_updated(EVENT_SCHEMA_VERSION);
+        let logs = [
+            format!("Program {bridge} invoke [1]"),
+            data_line(&event),
+            format!("Program {other} invoke [2]"),
+            data_line(&fee_updated(EVENT_SCHEMA_VERSION)),
+            format!("Program {other} success"),
+            data_line(&event),
+            format!("Program {bridge} success"),
+            data_line(&event),
+        ];
+
+        let events = decode_events(logs.iter().map(String::as_str));
+        assert_eq!(events.len(), 2);
+        for decoded in events {
+            let BridgeEvent::FeeUpdated(decoded) = decoded else {
+                panic!("unexpected event {}", decoded.name());
+            };
+            assert_eq!(decoded.actor, event.actor);
+            assert_eq!(decoded.new_fee, 20);
+        }
+    }
+
+    #[test]
+    fn lock_events_identify_token_and_recipient() {
+        let event = AssetLocked {
+            version: EVENT_SCHEMA_VERSION,
+            actor: Pubkey::new_unique(),
+            tx_id: [1; 32],
+            sender: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            recipient_commitment: [2; 32],
+            source_chain: 1,
+            target_chain: 2,
+            amount: 1_000,
+            commitment: [3; 32],
+            stealth: None,
+            encrypted_note: None,
+            slot: 7,
+        };
+        let Ok(BridgeEvent::AssetLocked(decoded)) = BridgeEvent::decode(&event.data()) else {
+            panic!("AssetLocked did not decode");
+        };
+        assert_eq!(decoded.mint, event.mint);
+        assert_eq!(decoded.recipient_commitment, [2; 32]);
+        assert_eq!(decoded.amount, 1_000);
+    }
+
+    #[test]
+    fn log_lines_are_not_program_exits() {
+        let bridge = ID.to_string();
+        let other = Pubkey::new_unique().to_string();
+        let logs = [
+            format!("Program {bridge} invoke [1]"),
+            format!("Program {other} invoke [2]"),
+            "Program log: success".to_string(),
+            "Program log: failed: nothing".to_string(),
+            data_line(&fee_updated(EVENT_SCHEMA_VERSION)),
+            format!("Program {other} failed: custom program error: 0x1"),
+        ];
+
+        assert!(decode_events(logs.iter().map(String::as_str)).is_empty());
+        assert!(!is_program_exit("Program log: success"));
+        assert!(!is_program_exit(&format!("Program {bridge} successful")));
+        assert!(!is_program_exit(&format!("Program {bridge} failed")));
+        assert!(is_program_exit(&format!("Program {bridge} success")));
+        assert!(is_program_exit(&format!("Program {bridge} failed: out of memory")));
+    }
+
+    #[test]
+    fn rejects_newer_schema_and_bad_data() {
+        let bridge = ID.to_string();
+        // A newer schema that appended a field
+        let mut extended = fee_updated(EVENT_SCHEMA_VERSION + 1).data();
+        extended.extend_from_slice(&7u64.to_le_bytes());
+        let mut trailing = fee_updated(EVENT_SCHEMA_VERSION).data();
+        trailing.push(0);
+        let encode = |data: &[u8]| {
+            format!("{PROGRAM_DATA_PREFIX}{}", base64::engine::general_purpose::STANDARD.encode(data))
+        };
+        let logs = [
+            format!("Program {bridge} invoke [1]"),
+            data_line(&fee_updated(EVENT_SCHEMA_VERSION + 1)),
+            encode(&extended),
+            encode(&trailing),
+            encode(FeeUpdated::DISCRIMINATOR),
+            format!("{PROGRAM_DATA_PREFIX}not base64!"),
+            format!("{PROGRAM_DATA_PREFIX}AAAA"),
+            format!("{PROGRAM_DATA_PREFIX}AAAAAAAAAAAAAAAA"),
+        ];
+
+        let results = decode_logs(logs.iter().map(String::as_str));
+        let errors: Vec<_> = results.into_iter().map(|result| result.err()).collect();
+        assert_eq!(
+            errors,
+            [
+                Some(DecodeError::UnsupportedVersion(EVENT_SCHEMA_VERSION + 1)),
+                Some(DecodeError::UnsupportedVersion(EVENT_SCHEMA_VERSION + 1)),
+                Some(DecodeError::InvalidData),
+                Some(DecodeError::InvalidData),
+                Some(DecodeError::InvalidData),
+                Some(DecodeError::TooShort),
+                Some(DecodeError::UnknownDiscriminator),
+            ]
+        );
+    }
+}
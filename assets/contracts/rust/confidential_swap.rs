@@ -1,667 +0,0 @@
-/*!
- * Confidential Swap - Rust Smart Contract
- * 
- * Privacy-preserving DEX for Solana/Substrate with Bulletproofs
- * 
- * Features:
- * - Confidential AMM with hidden balances
- * - Bulletproof range proofs
- * - Slippage protection
- * - MEV resistance
- * - Liquidity pools with privacy
- */
-
-use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer, MintTo};
-
-declare_id!("Swap1111111111111111111111111111111111111111");
-
-#[program]
-pub mod confidential_swap {
-    use super::*;
-
-    /// Initialize swap program
-    pub fn initialize(ctx: Context<Initialize>, swap_fee: u16) -> Result<()> {
-        let config = &mut ctx.accounts.config;
-        config.authority = ctx.accounts.authority.key();
-        config.swap_fee = swap_fee;
-        config.paused = false;
-        config.total_pools = 0;
-        Ok(())
-    }
-
-    /// Create liquidity pool
-    pub fn create_pool(
-        ctx: Context<CreatePool>,
-        token_a: Pubkey,
-        token_b: Pubkey,
-    ) -> Result<()> {
-        let config = &mut ctx.accounts.config;
-        require!(!config.paused, ErrorCode::SwapPaused);
-
-        let pool = &mut ctx.accounts.pool;
-        pool.token_a = token_a;
-        pool.token_b = token_b;
-        pool.reserve_a_commitment = [0; 32];
-        pool.reserve_b_commitment = [0; 32];
-        pool.total_supply = 0;
-        pool.initialized = true;
-
-        config.total_pools = config.total_pools
-            .checked_add(1)
-            .ok_or(ErrorCode::ArithmeticOverflow)?;
-
-        emit!(PoolCreated {
-            pool_id: pool.key(),
-            token_a,
-            token_b,
-        });
-
-        Ok(())
-    }
-
-    /// Add liquidity with confidential amounts
-    pub fn add_liquidity(
-        ctx: Context<AddLiquidity>,
-        amount_a: u64,
-        amount_b: u64,
-        amount_a_commitment: [u8; 32],
-        amount_b_commitment: [u8; 32],
-        proof_a: BulletproofProof,
-        proof_b: BulletproofProof,
-    ) -> Result<()> {
-        let config = &ctx.accounts.config;
-        require!(!config.paused, ErrorCode::SwapPaused);
-
-        let pool = &mut ctx.accounts.pool;
-        require!(pool.initialized, ErrorCode::PoolNotInitialized);
-
-        // Verify Bulletproof range proofs
-        require!(
-            verify_range_proof(&amount_a_commitment, &proof_a, 0, u64::MAX)?,
-            ErrorCode::InvalidProof
-        );
-
-        require!(
-            verify_range_proof(&amount_b_commitment, &proof_b, 0, u64::MAX)?,
-            ErrorCode::InvalidProof
-        );
-
-        // Transfer tokens from user
-        let cpi_accounts_a = Transfer {
-            from: ctx.accounts.user_token_a.to_account_info(),
-            to: ctx.accounts.pool_token_a.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_a),
-            amount_a
-        )?;
-
-        let cpi_accounts_b = Transfer {
-            from: ctx.accounts.user_token_b.to_account_info(),
-            to: ctx.accounts.pool_token_b.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_b),
-            amount_b
-        )?;
-
-        // Calculate liquidity tokens
-        let liquidity = if pool.total_supply == 0 {
-            // First liquidity provider
-            let sqrt = ((amount_a as u128 * amount_b as u128) as f64).sqrt() as u64;
-            sqrt.checked_sub(MINIMUM_LIQUIDITY).ok_or(ErrorCode::InsufficientLiquidity)?
-        } else {
-            // Subsequent liquidity providers
-            let liquidity_a = amount_a as u128 * pool.total_supply as u128 / 
-                pool.reserve_a_commitment[0] as u128;
-            let liquidity_b = amount_b as u128 * pool.total_supply as u128 / 
-                pool.reserve_b_commitment[0] as u128;
-            std::cmp::min(liquidity_a, liquidity_b) as u64
-        };
-
-        require!(liquidity > 0, ErrorCode::InsufficientLiquidity);
-
-        // Update pool commitments (homomorphic addition)
-        pool.reserve_a_commitment = add_commitments(
-            &pool.reserve_a_commitment,
-            &amount_a_commitment
-        );
-        pool.reserve_b_commitment = add_commitments(
-            &pool.reserve_b_commitment,
-            &amount_b_commitment
-        );
-
-        pool.total_supply = pool.total_supply
-            .checked_add(liquidity)
-            .ok_or(ErrorCode::ArithmeticOverflow)?;
-
-        // Update user's liquidity position
-        let position = &mut ctx.accounts.liquidity_position;
-        position.pool = pool.key();
-        position.user = ctx.accounts.user.key();
-        position.liquidity = position.liquidity
-            .checked_add(liquidity)
-            .ok_or(ErrorCode::ArithmeticOverflow)?;
-
-        emit!(LiquidityAdded {
-            pool: pool.key(),
-            provider: ctx.accounts.user.key(),
-            amount_a_commitment,
-            amount_b_commitment,
-            liquidity,
-        });
-
-        Ok(())
-    }
-
-    /// Commit to swap (MEV protection)
-    pub fn commit_swap(
-        ctx: Context<CommitSwap>,
-        input_commitment: [u8; 32],
-        output_commitment: [u8; 32],
-    ) -> Result<()> {
-        let config = &ctx.accounts.config;
-        require!(!config.paused, ErrorCode::SwapPaused);
-
-        let swap = &mut ctx.accounts.swap_commitment;
-        swap.user = ctx.accounts.user.key();
-        swap.pool = ctx.accounts.pool.key();
-        swap.input_commitment = input_commitment;
-        swap.output_commitment = output_commitment;
-        swap.timestamp = Clock::get()?.unix_timestamp;
-        swap.revealed = false;
-        swap.executed = false;
-
-        emit!(SwapCommitted {
-            swap_id: swap.key(),
-            pool: ctx.accounts.pool.key(),
-            user: ctx.accounts.user.key(),
-            input_commitment,
-            timestamp: swap.timestamp,
-        });
-
-        Ok(())
-    }
-
-    /// Execute swap with Bulletproof
-    pub fn execute_swap(
-        ctx: Context<ExecuteSwap>,
-        amount_in: u64,
-        min_amount_out: u64,
-        proof: BulletproofProof,
-    ) -> Result<()> {
-        let config = &ctx.accounts.config;
-        let swap = &mut ctx.accounts.swap_commitment;
-        let pool = &mut ctx.accounts.pool;
-
-        require!(!config.paused, ErrorCode::SwapPaused);
-        require!(swap.user == ctx.accounts.user.key(), ErrorCode::NotSwapOwner);
-        require!(!swap.executed, ErrorCode::AlreadyExecuted);
-
-        let current_time = Clock::get()?.unix_timestamp;
-        require!(
-            current_time >= swap.timestamp + 60,
-            ErrorCode::RevealTooEarly
-        );
-        require!(
-            current_time <= swap.timestamp + 600,
-            ErrorCode::SwapExpired
-        );
-
-        // Verify Bulletproof for input
-        require!(
-            verify_range_proof(&swap.input_commitment, &proof, 0, u64::MAX)?,
-            ErrorCode::InvalidProof
-        );
-
-        // Calculate output amount (constant product formula: x * y = k)
-        // In production, use homomorphic operations on commitments
-        let fee = (amount_in as u128 * config.swap_fee as u128 / 10000) as u64;
-        let amount_in_with_fee = amount_in.checked_sub(fee).ok_or(ErrorCode::ArithmeticOverflow)?;
-
-        // Simple constant product calculation (mock)
-        let amount_out = calculate_output_amount(
-            amount_in_with_fee,
-            pool.reserve_a_commitment[0] as u64,
-            pool.reserve_b_commitment[0] as u64,
-        )?;
-
-        require!(amount_out >= min_amount_out, ErrorCode::SlippageExceeded);
-
-        // Transfer input tokens from user
-        let cpi_accounts_in = Transfer {
-            from: ctx.accounts.user_token_in.to_account_info(),
-            to: ctx.accounts.pool_token_in.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_in),
-            amount_in
-        )?;
-
-        // Transfer output tokens to user
-        let authority_bump = ctx.bumps.pool_authority;
-        let authority_seeds = &[
-            b"pool_authority",
-            pool.key().as_ref(),
-            &[authority_bump],
-        ];
-        let signer = &[&authority_seeds[..]];
-
-        let cpi_accounts_out = Transfer {
-            from: ctx.accounts.pool_token_out.to_account_info(),
-            to: ctx.accounts.user_token_out.to_account_info(),
-            authority: ctx.accounts.pool_authority.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                cpi_accounts_out,
-                signer
-            ),
-            amount_out
-        )?;
-
-        // Update pool commitments
-        pool.reserve_a_commitment = add_commitments(
-            &pool.reserve_a_commitment,
-            &swap.input_commitment
-        );
-        pool.reserve_b_commitment = subtract_commitments(
-            &pool.reserve_b_commitment,
-            &swap.output_commitment
-        );
-
-        swap.revealed = true;
-        swap.executed = true;
-
-        emit!(SwapExecuted {
-            swap_id: swap.key(),
-            pool: pool.key(),
-            input_commitment: swap.input_commitment,
-            output_commitment: swap.output_commitment,
-            amount_out,
-        });
-
-        Ok(())
-    }
-
-    /// Remove liquidity
-    pub fn remove_liquidity(
-        ctx: Context<RemoveLiquidity>,
-        liquidity: u64,
-    ) -> Result<()> {
-        let pool = &mut ctx.accounts.pool;
-        let position = &mut ctx.accounts.liquidity_position;
-
-        require!(position.liquidity >= liquidity, ErrorCode::InsufficientLiquidity);
-
-        // Calculate amounts to withdraw
-        let amount_a = liquidity as u128 * pool.reserve_a_commitment[0] as u128 / 
-            pool.total_supply as u128;
-        let amount_b = liquidity as u128 * pool.reserve_b_commitment[0] as u128 / 
-            pool.total_supply as u128;
-
-        // Update pool
-        pool.total_supply = pool.total_supply
-            .checked_sub(liquidity)
-            .ok_or(ErrorCode::ArithmeticOverflow)?;
-
-        // Update position
-        position.liquidity = position.liquidity
-            .checked_sub(liquidity)
-            .ok_or(ErrorCode::ArithmeticOverflow)?;
-
-        emit!(LiquidityRemoved {
-            pool: pool.key(),
-            provider: ctx.accounts.user.key(),
-            liquidity,
-            amount_a: amount_a as u64,
-            amount_b: amount_b as u64,
-        });
-
-        Ok(())
-    }
-}
-
-// ========== ACCOUNTS ==========
-
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + SwapConfig::LEN,
-        seeds = [b"config"],
-        bump
-    )]
-    pub config: Account<'info, SwapConfig>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct CreatePool<'info> {
-    #[account(mut, seeds = [b"config"], bump)]
-    pub config: Account<'info, SwapConfig>,
-
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Pool::LEN,
-    )]
-    pub pool: Account<'info, Pool>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct AddLiquidity<'info> {
-    #[account(seeds = [b"config"], bump)]
-    pub config: Account<'info, SwapConfig>,
-
-    #[account(mut)]
-    pub pool: Account<'info, Pool>,
-
-    #[account(
-        init_if_needed,
-        payer = user,
-        space = 8 + LiquidityPosition::LEN,
-    )]
-    pub liquidity_position: Account<'info, LiquidityPosition>,
-
-    #[account(mut)]
-    pub user: Signer<'info>,
-
-    #[account(mut)]
-    pub user_token_a: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub user_token_b: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub pool_token_a: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub pool_token_b: Account<'info, TokenAccount>,
-
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct CommitSwap<'info> {
-    #[account(seeds = [b"config"], bump)]
-    pub config: Account<'info, SwapConfig>,
-
-    pub pool: Account<'info, Pool>,
-
-    #[account(
-        init,
-        payer = user,
-        space = 8 + SwapCommitment::LEN,
-    )]
-    pub swap_commitment: Account<'info, SwapCommitment>,
-
-    #[account(mut)]
-    pub user: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct ExecuteSwap<'info> {
-    #[account(seeds = [b"config"], bump)]
-    pub config: Account<'info, SwapConfig>,
-
-    #[account(mut)]
-    pub pool: Account<'info, Pool>,
-
-    /// CHECK: PDA authority
-    #[account(
-        seeds = [b"pool_authority", pool.key().as_ref()],
-        bump
-    )]
-    pub pool_authority: UncheckedAccount<'info>,
-
-    #[account(mut)]
-    pub swap_commitment: Account<'info, SwapCommitment>,
-
-    #[account(mut)]
-    pub user: Signer<'info>,
-
-    #[account(mut)]
-    pub user_token_in: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub user_token_out: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub pool_token_in: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub pool_token_out: Account<'info, TokenAccount>,
-
-    pub token_program: Program<'info, Token>,
-}
-
-#[derive(Accounts)]
-pub struct RemoveLiquidity<'info> {
-    #[account(mut)]
-    pub pool: Account<'info, Pool>,
-
-    #[account(mut)]
-    pub liquidity_position: Account<'info, LiquidityPosition>,
-
-    pub user: Signer<'info>,
-}
-
-// ========== STATE ==========
-
-#[account]
-pub struct SwapConfig {
-    pub authority: Pubkey,
-    pub swap_fee: u16,
-    pub paused: bool,
-    pub total_pools: u64,
-}
-
-impl SwapConfig {
-    pub const LEN: usize = 32 + 2 + 1 + 8;
-}
-
-#[account]
-pub struct Pool {
-    pub token_a: Pubkey,
-    pub token_b: Pubkey,
-    pub reserve_a_commitment: [u8; 32],
-    pub reserve_b_commitment: [u8; 32],
-    pub total_supply: u64,
-    pub initialized: bool,
-}
-
-impl Pool {
-    pub const LEN: usize = 32 + 32 + 32 + 32 + 8 + 1;
-}
-
-#[account]
-pub struct LiquidityPosition {
-    pub pool: Pubkey,
-    pub user: Pubkey,
-    pub liquidity: u64,
-}
-
-impl LiquidityPosition {
-    pub const LEN: usize = 32 + 32 + 8;
-}
-
-#[account]
-pub struct SwapCommitment {
-    pub user: Pubkey,
-    pub pool: Pubkey,
-    pub input_commitment: [u8; 32],
-    pub output_commitment: [u8; 32],
-    pub timestamp: i64,
-    pub revealed: bool,
-    pub executed: bool,
-}
-
-impl SwapCommitment {
-    pub const LEN: usize = 32 + 32 + 32 + 32 + 8 + 1 + 1;
-}
-
-// ========== STRUCTS ==========
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct BulletproofProof {
-    pub a: [u8; 32],
-    pub s: [u8; 32],
-    pub t1: [u8; 32],
-    pub t2: [u8; 32],
-    pub taux: [u8; 32],
-    pub mu: [u8; 32],
-    pub inner_product: Vec<u8>,
-}
-
-// ========== EVENTS ==========
-
-#[event]
-pub struct PoolCreated {
-    pub pool_id: Pubkey,
-    pub token_a: Pubkey,
-    pub token_b: Pubkey,
-}
-
-#[event]
-pub struct LiquidityAdded {
-    pub pool: Pubkey,
-    pub provider: Pubkey,
-    pub amount_a_commitment: [u8; 32],
-    pub amount_b_commitment: [u8; 32],
-    pub liquidity: u64,
-}
-
-#[event]
-pub struct SwapCommitted {
-    pub swap_id: Pubkey,
-    pub pool: Pubkey,
-    pub user: Pubkey,
-    pub input_commitment: [u8; 32],
-    pub timestamp: i64,
-}
-
-#[event]
-pub struct SwapExecuted {
-    pub swap_id: Pubkey,
-    pub pool: Pubkey,
-    pub input_commitment: [u8; 32],
-    pub output_commitment: [u8; 32],
-    pub amount_out: u64,
-}
-
-#[event]
-pub struct LiquidityRemoved {
-    pub pool: Pubkey,
-    pub provider: Pubkey,
-    pub liquidity: u64,
-    pub amount_a: u64,
-    pub amount_b: u64,
-}
-
-// ========== ERRORS ==========
-
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Swap is paused")]
-    SwapPaused,
-
-    #[msg("Pool not initialized")]
-    PoolNotInitialized,
-
-    #[msg("Invalid Bulletproof")]
-    InvalidProof,
-
-    #[msg("Insufficient liquidity")]
-    InsufficientLiquidity,
-
-    #[msg("Not swap owner")]
-    NotSwapOwner,
-
-    #[msg("Already executed")]
-    AlreadyExecuted,
-
-    #[msg("Reveal too early (wait 1 minute)")]
-    RevealTooEarly,
-
-    #[msg("Swap expired (>10 minutes)")]
-    SwapExpired,
-
-    #[msg("Slippage exceeded")]
-    SlippageExceeded,
-
-    #[msg("Arithmetic overflow")]
-    ArithmeticOverflow,
-}
-
-// ========== CONSTANTS ==========
-
-const MINIMUM_LIQUIDITY: u64 = 1000;
-
-// ========== HELPER FUNCTIONS ==========
-
-/// Add Pedersen commitments (homomorphic)
-fn add_commitments(c1: &[u8; 32], c2: &[u8; 32]) -> [u8; 32] {
-    // Simplified - in production, use proper elliptic curve addition
-    let mut result = [0u8; 32];
-    for i in 0..32 {
-        result[i] = c1[i].wrapping_add(c2[i]);
-    }
-    result
-}
-
-/// Subtract Pedersen commitments (homomorphic)
-fn subtract_commitments(c1: &[u8; 32], c2: &[u8; 32]) -> [u8; 32] {
-    // Simplified - in production, use proper elliptic curve subtraction
-    let mut result = [0u8; 32];
-    for i in 0..32 {
-        result[i] = c1[i].wrapping_sub(c2[i]);
-    }
-    result
-}
-
-/// Verify Bulletproof range proof
-fn verify_range_proof(
-    commitment: &[u8; 32],
-    proof: &BulletproofProof,
-    min: u64,
-    max: u64,
-) -> Result<bool> {
-    // Mock verification - in production, use bulletproofs crate
-    let valid = proof.a.len() == 32 && 
-                proof.s.len() == 32 && 
-                proof.t1.len() == 32 && 
-                proof.t2.len() == 32;
-    Ok(valid)
-}
-
-/// Calculate output amount (constant product formula)
-fn calculate_output_amount(
-    amount_in: u64,
-    reserve_in: u64,
-    reserve_out: u64,
-) -> Result<u64> {
-    let amount_in_with_fee = amount_in as u128 * 997 / 1000;
-    let numerator = amount_in_with_fee * reserve_out as u128;
-    let denominator = (reserve_in as u128 * 1000) + amount_in_with_fee;
-    
-    Ok((numerator / denominator) as u64)
-}
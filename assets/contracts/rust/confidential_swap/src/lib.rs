@@ -0,0 +1,4230 @@
+/*!
+ * Confidential Swap - Rust Smart Contract
+ * 
+ * Privacy-preserving DEX for Solana/Substrate with Bulletproofs
+ * 
+ * Features:
+ * - Confidential AMM with hidden balances
+ * - Bulletproof range proofs
+ * - Slippage protection
+ * - MEV resistance
+ * - Liquidity pools with privacy
+ * - Plaintext reserve accounting alongside reserve commitments
+ * - Transferable SPL LP tokens per pool
+ * - Swaps in either direction, derived from the token mints
+ * - PDA vaults and full account validation
+ * - One canonical pool per token pair and fee tier
+ * - Binding commit-reveal: reveals must match a hash of the trade
+ * - Frequent batch auctions settled at a uniform clearing price
+ * - Batch orders claimed one by one, refunded if their batch goes unsettled
+ * - Per-pool reveal window and a commit bond forfeited on expiry
+ * - Commitments closed on execution, by their owner or by a keeper crank
+ * - Finished batches and their orders closed to return rent
+ * - M-of-N owner approvals on every admin action; single-signer emergency pause
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use std::cmp::Ordering;
+use multisig::{Approvals, OwnerSet, MAX_OWNERS};
+
+declare_id!("Swap111111111111111111111111111111111111111");
+
+#[program]
+pub mod confidential_swap {
+    use super::*;
+
+    /// Initialize swap program
+    ///
+    /// `owners` approve admin actions; `threshold` of them must sign off
+    /// before an action executes.
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        owners: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.pending_authority = Pubkey::default();
+        config.paused = false;
+        config.total_pools = 0;
+        config.min_reveal_delay = DEFAULT_MIN_REVEAL_DELAY;
+        config.max_reveal_delay = DEFAULT_MAX_REVEAL_DELAY;
+        config.commit_bond = DEFAULT_COMMIT_BOND;
+        config.treasury = ctx.accounts.authority.key();
+        config.keeper_tip_bps = DEFAULT_KEEPER_TIP_BPS;
+        config.multisig = OwnerSet::new(owners, threshold)?;
+        config.proposal_count = 0;
+        Ok(())
+    }
+
+    /// Create liquidity pool
+    ///
+    /// `mint_a` must sort before `mint_b`, so each pair and fee tier has a
+    /// single pool at `find_pool_address`. `fee_tier` is the pool's swap fee
+    /// in basis points and must be one of `FEE_TIERS`.
+    pub fn create_pool(ctx: Context<CreatePool>, fee_tier: u16) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(!config.paused, ErrorCode::SwapPaused);
+
+        let token_a = ctx.accounts.mint_a.key();
+        let token_b = ctx.accounts.mint_b.key();
+        check_pool_key(token_a, token_b, fee_tier)?;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.token_a = token_a;
+        pool.token_b = token_b;
+        pool.fee_tier = fee_tier;
+        pool.reserve_a_commitment = [0; 32];
+        pool.reserve_b_commitment = [0; 32];
+        pool.reserve_a = 0;
+        pool.reserve_b = 0;
+        pool.total_supply = 0;
+        pool.lp_mint = ctx.accounts.lp_mint.key();
+        pool.min_reveal_delay = config.min_reveal_delay;
+        pool.max_reveal_delay = config.max_reveal_delay;
+        pool.commit_bond = config.commit_bond;
+        pool.initialized = true;
+
+        config.total_pools = config.total_pools
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(PoolCreated {
+            pool_id: pool.key(),
+            token_a,
+            token_b,
+            fee_tier,
+            lp_mint: pool.lp_mint,
+        });
+
+        Ok(())
+    }
+
+    /// Add liquidity with confidential amounts
+    ///
+    /// The deposited amounts are public token transfers, so their
+    /// commitments are derived on-chain rather than supplied by the caller.
+    pub fn add_liquidity(
+        ctx: Context<AddLiquidity>,
+        amount_a: u64,
+        amount_b: u64,
+        proof_a: BulletproofProof,
+        proof_b: BulletproofProof,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, ErrorCode::SwapPaused);
+
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.initialized, ErrorCode::PoolNotInitialized);
+
+        let amount_a_commitment = public_amount_commitment(amount_a);
+        let amount_b_commitment = public_amount_commitment(amount_b);
+
+        // Verify Bulletproof range proofs
+        require!(
+            verify_range_proof(&amount_a_commitment, &proof_a, 0, u64::MAX)?,
+            ErrorCode::InvalidProof
+        );
+
+        require!(
+            verify_range_proof(&amount_b_commitment, &proof_b, 0, u64::MAX)?,
+            ErrorCode::InvalidProof
+        );
+
+        // Transfer tokens from user
+        let cpi_accounts_a = Transfer {
+            from: ctx.accounts.user_token_a.to_account_info(),
+            to: ctx.accounts.pool_token_a.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_a),
+            amount_a
+        )?;
+
+        let cpi_accounts_b = Transfer {
+            from: ctx.accounts.user_token_b.to_account_info(),
+            to: ctx.accounts.pool_token_b.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_b),
+            amount_b
+        )?;
+
+        // Calculate liquidity tokens
+        let is_first_deposit = pool.total_supply == 0;
+        let liquidity = if is_first_deposit {
+            // First liquidity provider
+            let sqrt = integer_sqrt(amount_a as u128 * amount_b as u128);
+            sqrt.checked_sub(MINIMUM_LIQUIDITY).ok_or(ErrorCode::InsufficientLiquidity)?
+        } else {
+            // Subsequent liquidity providers
+            require!(
+                pool.reserve_a > 0 && pool.reserve_b > 0,
+                ErrorCode::InsufficientLiquidity
+            );
+            let liquidity_a = amount_a as u128 * pool.total_supply as u128 /
+                pool.reserve_a as u128;
+            let liquidity_b = amount_b as u128 * pool.total_supply as u128 /
+                pool.reserve_b as u128;
+            u64::try_from(std::cmp::min(liquidity_a, liquidity_b))
+                .map_err(|_| ErrorCode::ArithmeticOverflow)?
+        };
+
+        require!(liquidity > 0, ErrorCode::InsufficientLiquidity);
+
+        pool.deposit(amount_a, amount_b)?;
+
+        // The first MINIMUM_LIQUIDITY shares are minted to the pool's
+        // locked_lp account, so total_supply always equals the mint supply
+        let minted = if is_first_deposit {
+            liquidity.checked_add(MINIMUM_LIQUIDITY).ok_or(ErrorCode::ArithmeticOverflow)?
+        } else {
+            liquidity
+        };
+        pool.total_supply = pool.total_supply
+            .checked_add(minted)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        // Mint LP tokens to the locked account and the provider
+        let pool_key = pool.key();
+        let authority_bump = ctx.bumps.pool_authority;
+        let authority_seeds = &[
+            b"pool_authority",
+            pool_key.as_ref(),
+            &[authority_bump],
+        ];
+        let signer = &[&authority_seeds[..]];
+
+        if is_first_deposit {
+            let cpi_accounts_lock = MintTo {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                to: ctx.accounts.locked_lp_token.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            };
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    cpi_accounts_lock,
+                    signer
+                ),
+                MINIMUM_LIQUIDITY
+            )?;
+        }
+
+        let cpi_accounts_mint = MintTo {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            to: ctx.accounts.user_lp_token.to_account_info(),
+            authority: ctx.accounts.pool_authority.to_account_info(),
+        };
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts_mint,
+                signer
+            ),
+            liquidity
+        )?;
+
+        emit!(LiquidityAdded {
+            pool: pool.key(),
+            provider: ctx.accounts.user.key(),
+            amount_a_commitment,
+            amount_b_commitment,
+            liquidity,
+        });
+
+        Ok(())
+    }
+
+    /// Commit to swap (MEV protection)
+    ///
+    /// `swap_hash` is `compute_swap_hash(amount_in, min_amount_out,
+    /// direction, salt, user)`; only the hash is public until the reveal.
+    /// The pool's commit bond is escrowed until the swap executes, or
+    /// forfeited once the reveal window closes.
+    pub fn commit_swap(ctx: Context<CommitSwap>, swap_hash: [u8; 32]) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, ErrorCode::SwapPaused);
+
+        let pool = &ctx.accounts.pool;
+        let commitment = SwapCommitment::open(
+            ctx.accounts.user.key(),
+            pool.key(),
+            pool,
+            swap_hash,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        // Escrow the pool's commit bond in the commitment account
+        if pool.commit_bond > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user.to_account_info(),
+                        to: ctx.accounts.swap_commitment.to_account_info(),
+                    },
+                ),
+                pool.commit_bond,
+            )?;
+        }
+
+        let swap = &mut ctx.accounts.swap_commitment;
+        swap.set_inner(commitment);
+
+        emit!(SwapCommitted {
+            swap_id: swap.key(),
+            pool: ctx.accounts.pool.key(),
+            user: ctx.accounts.user.key(),
+            swap_hash,
+            timestamp: swap.timestamp,
+            reveal_after: swap.reveal_after,
+            expires_at: swap.expires_at,
+            bond: swap.bond,
+        });
+
+        Ok(())
+    }
+
+    /// Reveal and execute a committed swap
+    ///
+    /// The direction follows the mints of `user_token_in` / `user_token_out`,
+    /// which must be the pool's two tokens in either order. The revealed
+    /// amounts, direction and `salt` must hash to the committed `swap_hash`.
+    /// On success the commitment is closed and its rent and bond refunded.
+    pub fn execute_swap(
+        ctx: Context<ExecuteSwap>,
+        amount_in: u64,
+        min_amount_out: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let swap = &mut ctx.accounts.swap_commitment;
+        let pool = &mut ctx.accounts.pool;
+
+        require!(!config.paused, ErrorCode::SwapPaused);
+        let direction = swap_direction(
+            pool,
+            ctx.accounts.user_token_in.mint,
+            ctx.accounts.user_token_out.mint,
+        )?;
+        swap.reveal(amount_in, min_amount_out, direction, &salt, Clock::get()?.unix_timestamp)?;
+
+        let (reserve_in, reserve_out) = match direction {
+            SwapDirection::AToB => (pool.reserve_a, pool.reserve_b),
+            SwapDirection::BToA => (pool.reserve_b, pool.reserve_a),
+        };
+
+        // Calculate output amount (constant product formula: x * y = k)
+        let amount_out = calculate_output_amount(
+            amount_in,
+            reserve_in,
+            reserve_out,
+            pool.fee_tier,
+        )?;
+
+        require!(amount_out >= min_amount_out, ErrorCode::SlippageExceeded);
+        require!(amount_out < reserve_out, ErrorCode::InsufficientLiquidity);
+
+        // Transfer input tokens from user
+        let cpi_accounts_in = Transfer {
+            from: ctx.accounts.user_token_in.to_account_info(),
+            to: ctx.accounts.pool_token_in.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_in),
+            amount_in
+        )?;
+
+        // Transfer output tokens to user
+        let pool_key = pool.key();
+        let authority_bump = ctx.bumps.pool_authority;
+        let authority_seeds = &[
+            b"pool_authority",
+            pool_key.as_ref(),
+            &[authority_bump],
+        ];
+        let signer = &[&authority_seeds[..]];
+
+        let cpi_accounts_out = Transfer {
+            from: ctx.accounts.pool_token_out.to_account_info(),
+            to: ctx.accounts.user_token_out.to_account_info(),
+            authority: ctx.accounts.pool_authority.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts_out,
+                signer
+            ),
+            amount_out
+        )?;
+
+        // The fee stays in the pool, so the full input joins the reserve
+        pool.swap(direction, amount_in, amount_out)?;
+
+        // The commitment is closed to the user on exit, returning rent and bond
+        swap.executed = true;
+
+        emit!(SwapExecuted {
+            swap_id: swap.key(),
+            pool: pool.key(),
+            amount_in,
+            amount_out,
+            direction,
+        });
+
+        Ok(())
+    }
+
+    /// Remove liquidity by burning LP tokens
+    ///
+    /// Returns the provider's share of both reserves from the pool vaults.
+    /// The withdrawn amounts are public, so the reserve commitments are
+    /// reduced by commitments derived from them rather than caller input.
+    pub fn remove_liquidity(
+        ctx: Context<RemoveLiquidity>,
+        liquidity: u64,
+        min_amount_a: u64,
+        min_amount_b: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let (amount_a, amount_b) = pool.withdrawal_amounts(
+            liquidity,
+            ctx.accounts.user_lp_token.amount,
+            min_amount_a,
+            min_amount_b,
+            deadline,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        // Burn the provider's LP tokens
+        let cpi_accounts_burn = Burn {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            from: ctx.accounts.user_lp_token.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        token::burn(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_burn),
+            liquidity
+        )?;
+
+        // Transfer both tokens back to the provider
+        let pool_key = pool.key();
+        let authority_bump = ctx.bumps.pool_authority;
+        let authority_seeds = &[
+            b"pool_authority",
+            pool_key.as_ref(),
+            &[authority_bump],
+        ];
+        let signer = &[&authority_seeds[..]];
+
+        let cpi_accounts_a = Transfer {
+            from: ctx.accounts.pool_token_a.to_account_info(),
+            to: ctx.accounts.user_token_a.to_account_info(),
+            authority: ctx.accounts.pool_authority.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts_a,
+                signer
+            ),
+            amount_a
+        )?;
+
+        let cpi_accounts_b = Transfer {
+            from: ctx.accounts.pool_token_b.to_account_info(),
+            to: ctx.accounts.user_token_b.to_account_info(),
+            authority: ctx.accounts.pool_authority.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts_b,
+                signer
+            ),
+            amount_b
+        )?;
+
+        // Update pool
+        pool.withdraw(amount_a, amount_b)?;
+
+        pool.total_supply = pool.total_supply
+            .checked_sub(liquidity)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(LiquidityRemoved {
+            pool: pool.key(),
+            provider: ctx.accounts.user.key(),
+            liquidity,
+            amount_a,
+            amount_b,
+        });
+
+        Ok(())
+    }
+
+    /// Commit to an order in a batch auction
+    ///
+    /// Batches are time slots of `BATCH_DURATION` seconds: orders for batch
+    /// `now / BATCH_DURATION` are committed during the slot, revealed during
+    /// the following `BATCH_REVEAL_WINDOW`, then settled together within
+    /// `BATCH_SETTLE_WINDOW`. The pool's commit bond is escrowed in the
+    /// order and forfeited if it is never revealed. A batch takes at most
+    /// `MAX_BATCH_ORDERS` orders.
+    pub fn commit_batch_order(
+        ctx: Context<CommitBatchOrder>,
+        batch_id: u64,
+        swap_hash: [u8; 32],
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(!config.paused, ErrorCode::SwapPaused);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= 0 && now as u64 / BATCH_DURATION as u64 == batch_id,
+            ErrorCode::BatchNotInCommitPhase
+        );
+        ctx.accounts.batch.open_order()?;
+
+        // Escrow the pool's commit bond in the order account
+        let commit_bond = ctx.accounts.pool.commit_bond;
+        if commit_bond > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user.to_account_info(),
+                        to: ctx.accounts.order.to_account_info(),
+                    },
+                ),
+                commit_bond,
+            )?;
+        }
+
+        let batch = &mut ctx.accounts.batch;
+        if batch.pool == Pubkey::default() {
+            batch.creator = ctx.accounts.user.key();
+            let commit_end = (batch_id as i64 + 1)
+                .checked_mul(BATCH_DURATION)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            batch.pool = ctx.accounts.pool.key();
+            batch.batch_id = batch_id;
+            batch.commit_end = commit_end;
+            batch.reveal_end = commit_end
+                .checked_add(BATCH_REVEAL_WINDOW)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            batch.settle_end = batch.reveal_end
+                .checked_add(BATCH_SETTLE_WINDOW)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+
+        let order = &mut ctx.accounts.order;
+        order.batch = batch.key();
+        order.user = ctx.accounts.user.key();
+        order.swap_hash = swap_hash;
+        order.revealed = false;
+        order.direction = SwapDirection::AToB;
+        order.amount_in = 0;
+        order.min_amount_out = 0;
+        order.amount_out = 0;
+        order.bond = commit_bond;
+        order.filled = false;
+        order.settled = false;
+
+        emit!(BatchOrderCommitted {
+            batch: batch.key(),
+            order: order.key(),
+            user: order.user,
+            swap_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Reveal a batch order and escrow its input in the pool vault
+    ///
+    /// The input stays outside the pool reserves until `settle_batch`.
+    pub fn reveal_batch_order(
+        ctx: Context<RevealBatchOrder>,
+        amount_in: u64,
+        min_amount_out: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let batch = &mut ctx.accounts.batch;
+        let order = &mut ctx.accounts.order;
+        let pool = &ctx.accounts.pool;
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= batch.commit_end && now < batch.reveal_end,
+            ErrorCode::BatchNotInRevealPhase
+        );
+        require!(!order.revealed, ErrorCode::AlreadyExecuted);
+        require!(amount_in > 0, ErrorCode::InvalidAmount);
+        require!(
+            (batch.revealed_count as usize) < MAX_BATCH_ORDERS,
+            ErrorCode::BatchFull
+        );
+
+        let mint_in = ctx.accounts.user_token_in.mint;
+        let mint_out = if mint_in == pool.token_a { pool.token_b } else { pool.token_a };
+        let direction = swap_direction(pool, mint_in, mint_out)?;
+
+        require!(
+            compute_swap_hash(amount_in, min_amount_out, direction, &salt, &order.user) == order.swap_hash,
+            ErrorCode::CommitmentMismatch
+        );
+
+        // Escrow the input
+        let cpi_accounts_in = Transfer {
+            from: ctx.accounts.user_token_in.to_account_info(),
+            to: ctx.accounts.pool_token_in.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_in),
+            amount_in
+        )?;
+
+        order.revealed = true;
+        order.direction = direction;
+        order.amount_in = amount_in;
+        order.min_amount_out = min_amount_out;
+
+        batch.revealed_count += 1;
+        match direction {
+            SwapDirection::AToB => {
+                batch.total_in_a = batch.total_in_a
+                    .checked_add(amount_in)
+                    .ok_or(ErrorCode::ArithmeticOverflow)?;
+            }
+            SwapDirection::BToA => {
+                batch.total_in_b = batch.total_in_b
+                    .checked_add(amount_in)
+                    .ok_or(ErrorCode::ArithmeticOverflow)?;
+            }
+        }
+
+        emit!(BatchOrderRevealed {
+            batch: batch.key(),
+            order: order.key(),
+            direction,
+            amount_in,
+            min_amount_out,
+        });
+
+        Ok(())
+    }
+
+    /// Settle a batch at one uniform clearing price (permissionless)
+    ///
+    /// Opposing orders are netted against each other and only the imbalance
+    /// trades against the pool; every filled order gets the same price.
+    /// Orders whose `min_amount_out` cannot be met are refunded and the price
+    /// is recomputed without them. The net trade moves the reserves and
+    /// their commitments like a single swap.
+    ///
+    /// Only the result is recorded here; each order is then paid out by
+    /// `claim_batch_order`. `remaining_accounts` holds every revealed order
+    /// of the batch, all writable.
+    pub fn settle_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleBatch<'info>>,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let batch = &mut ctx.accounts.batch;
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= batch.reveal_end, ErrorCode::BatchNotSettleable);
+        require!(now < batch.settle_end, ErrorCode::BatchSettleExpired);
+        require!(!batch.settled, ErrorCode::AlreadyExecuted);
+        require!(
+            ctx.remaining_accounts.len() == batch.revealed_count as usize,
+            ErrorCode::InvalidBatchAccounts
+        );
+
+        // Load and check every revealed order
+        let batch_key = batch.key();
+        let mut orders: Vec<Account<'info, BatchOrder>> = Vec::new();
+        for account in ctx.remaining_accounts.iter() {
+            let order: Account<'info, BatchOrder> = Account::try_from(account)?;
+            require!(order.batch == batch_key, ErrorCode::InvalidBatchAccounts);
+            require!(order.revealed && !order.settled, ErrorCode::InvalidBatchAccounts);
+            require!(
+                orders.iter().all(|other| other.key() != order.key()),
+                ErrorCode::InvalidBatchAccounts
+            );
+            orders.push(order);
+        }
+
+        let inputs: Vec<BatchOrderInput> = orders
+            .iter()
+            .map(|order| BatchOrderInput {
+                key: order.key(),
+                direction: order.direction,
+                amount_in: order.amount_in,
+                min_amount_out: order.min_amount_out,
+            })
+            .collect();
+        let clearing = clear_batch(&inputs, pool.reserve_a, pool.reserve_b, pool.fee_tier)?;
+
+        for (index, order) in orders.iter_mut().enumerate() {
+            order.filled = clearing.filled[index];
+            order.amount_out = clearing.amounts_out[index];
+            order.settled = true;
+            order.exit(ctx.program_id)?;
+        }
+
+        // Only the net imbalance moves the reserves
+        if let Some((direction, pool_in, pool_out)) = clearing.net_trade {
+            pool.swap(direction, pool_in, pool_out)?;
+        }
+
+        batch.settled = true;
+        batch.clearing_price = clearing.clearing_price;
+
+        emit!(BatchSettled {
+            batch: batch_key,
+            pool: pool.key(),
+            clearing_price: clearing.clearing_price,
+            filled_orders: clearing.filled.iter().filter(|filled| **filled).count() as u32,
+            refunded_orders: clearing.filled.iter().filter(|filled| !**filled).count() as u32,
+            net_direction: clearing.net_trade.map(|(direction, _, _)| direction),
+            net_amount_in: clearing.net_trade.map_or(0, |(_, amount_in, _)| amount_in),
+            net_amount_out: clearing.net_trade.map_or(0, |(_, _, amount_out)| amount_out),
+        });
+
+        Ok(())
+    }
+
+    /// Pay out a revealed batch order (permissionless)
+    ///
+    /// A filled order receives its output and anything else gets its input
+    /// back, including every order of a batch not settled by `settle_end`.
+    /// Tokens go to the owner's associated token account, created at the
+    /// caller's expense if needed; the order is closed and its rent and bond
+    /// returned to the owner.
+    pub fn claim_batch_order(ctx: Context<ClaimBatchOrder>) -> Result<()> {
+        let order = &ctx.accounts.order;
+        let (mint, amount) = check_claim_accounts(
+            &ctx.accounts.pool,
+            &ClaimAccounts {
+                pool: ctx.accounts.pool.key(),
+                batch_key: ctx.accounts.batch.key(),
+                batch: &ctx.accounts.batch,
+                order,
+                user: ctx.accounts.user.key(),
+                mint: ctx.accounts.mint.key(),
+                vault: ctx.accounts.vault.key(),
+            },
+            Clock::get()?.unix_timestamp,
+        )?;
+        ctx.accounts.batch.close_order()?;
+
+        let pool_key = ctx.accounts.pool.key();
+        let authority_bump = ctx.bumps.pool_authority;
+        let authority_seeds = &[
+            b"pool_authority",
+            pool_key.as_ref(),
+            &[authority_bump],
+        ];
+        pay_from_vault(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.user_token.to_account_info(),
+            &ctx.accounts.pool_authority.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &[&authority_seeds[..]],
+            amount,
+        )?;
+
+        emit!(BatchOrderClaimed {
+            batch: order.batch,
+            order: order.key(),
+            user: order.user,
+            filled: order.settled && order.filled,
+            mint,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Close a batch order that was never revealed (permissionless)
+    ///
+    /// Once the reveal phase ends the order's bond is forfeited, with
+    /// `keeper_tip_bps` of it paid to the caller and the rest to the
+    /// treasury. Rent, and the bond while swaps are paused, go back to the
+    /// owner.
+    pub fn forfeit_batch_order(ctx: Context<ForfeitBatchOrder>) -> Result<()> {
+        let order = &ctx.accounts.order;
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.batch.reveal_end,
+            ErrorCode::BatchNotSettleable
+        );
+        require!(!order.revealed, ErrorCode::BatchOrderRevealed);
+        ctx.accounts.batch.close_order()?;
+
+        let config = &ctx.accounts.config;
+        let (keeper_tip, bond_forfeited) = config.forfeit(order.bond, config.keeper_tip_bps);
+        move_lamports(&order.to_account_info(), &ctx.accounts.keeper.to_account_info(), keeper_tip)?;
+        move_lamports(&order.to_account_info(), &ctx.accounts.treasury.to_account_info(), bond_forfeited)?;
+
+        emit!(BatchOrderForfeited {
+            batch: order.batch,
+            order: order.key(),
+            user: order.user,
+            closed_by: ctx.accounts.keeper.key(),
+            bond_forfeited,
+            keeper_tip,
+        });
+
+        Ok(())
+    }
+
+    /// Close a finished batch and return its rent to the creator (permissionless)
+    ///
+    /// Every order must have been claimed or forfeited first.
+    pub fn close_batch(ctx: Context<CloseBatch>) -> Result<()> {
+        let batch = &ctx.accounts.batch;
+        batch.check_closable(Clock::get()?.unix_timestamp)?;
+
+        emit!(BatchClosed {
+            batch: batch.key(),
+            pool: batch.pool,
+            creator: batch.creator,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel an unexecuted swap commitment and reclaim its rent
+    ///
+    /// The commit bond is forfeited to the treasury; only executing the
+    /// swap returns it, or cancelling while swaps are paused.
+    pub fn close_swap_commitment(ctx: Context<CloseSwapCommitment>) -> Result<()> {
+        let swap = &ctx.accounts.swap_commitment;
+        let (_, bond_forfeited) = ctx.accounts.config.forfeit(swap.bond, 0);
+        move_lamports(&swap.to_account_info(), &ctx.accounts.treasury.to_account_info(), bond_forfeited)?;
+
+        emit!(SwapCommitmentClosed {
+            swap_id: swap.key(),
+            pool: swap.pool,
+            user: swap.user,
+            closed_by: swap.user,
+            bond_forfeited,
+            keeper_tip: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Close expired swap commitments (permissionless)
+    ///
+    /// `remaining_accounts` holds `(swap_commitment, user)` pairs, all
+    /// writable. Rent goes back to each user; any bond is forfeited, with
+    /// `keeper_tip_bps` of it paid to the keeper and the rest to the treasury,
+    /// unless swaps are paused.
+    pub fn crank_expired_commitments<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankExpiredCommitments<'info>>,
+    ) -> Result<()> {
+        require!(
+            !ctx.remaining_accounts.is_empty()
+                && ctx.remaining_accounts.len() % CRANK_ACCOUNTS == 0,
+            ErrorCode::InvalidCrankAccounts
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let keeper = ctx.accounts.keeper.to_account_info();
+        let treasury = ctx.accounts.treasury.to_account_info();
+        let config = &ctx.accounts.config;
+
+        for accounts in ctx.remaining_accounts.chunks(CRANK_ACCOUNTS) {
+            // A commitment listed twice fails here, as closing hands it to
+            // the system program
+            let swap: Account<'info, SwapCommitment> = Account::try_from(&accounts[0])?;
+            let user = &accounts[1];
+            swap.check_expired(&user.key(), now)?;
+
+            let (keeper_tip, bond_forfeited) = config.forfeit(swap.bond, config.keeper_tip_bps);
+            move_lamports(&swap.to_account_info(), &keeper, keeper_tip)?;
+            move_lamports(&swap.to_account_info(), &treasury, bond_forfeited)?;
+
+            emit!(SwapCommitmentClosed {
+                swap_id: swap.key(),
+                pool: swap.pool,
+                user: swap.user,
+                closed_by: keeper.key(),
+                bond_forfeited,
+                keeper_tip,
+            });
+
+            swap.close(user.clone())?;
+        }
+
+        Ok(())
+    }
+
+    /// Pause swaps and new liquidity (authority only, for emergencies)
+    ///
+    /// Unpausing needs an owner-approved `Unpause` proposal.
+    pub fn pause(ctx: Context<UpdateConfig>) -> Result<()> {
+        ctx.accounts.config.paused = true;
+
+        emit!(PauseUpdated { paused: true });
+
+        Ok(())
+    }
+
+    /// Propose an admin action (owners only)
+    ///
+    /// The proposer approves implicitly; the action executes once
+    /// `threshold` owners have approved.
+    pub fn propose_admin_action(
+        ctx: Context<ProposeAdminAction>,
+        action: SwapAdminAction,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.id = config.proposal_count;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.action = action.clone();
+        proposal.approvals = config.multisig.propose(&proposal.proposer)?;
+        proposal.executed = false;
+        proposal.cancel_approvals = Approvals::none(&config.multisig);
+        proposal.cancelled = false;
+
+        config.proposal_count = config.proposal_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(AdminActionProposed {
+            proposal_id: proposal.id,
+            proposer: proposal.proposer,
+            action,
+        });
+
+        Ok(())
+    }
+
+    /// Approve a pending admin action (owners only)
+    pub fn approve_admin_action(ctx: Context<ApproveAdminAction>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let approvals = proposal.approve(&ctx.accounts.config, &ctx.accounts.owner.key())?;
+
+        emit!(AdminActionApproved {
+            proposal_id: proposal.id,
+            owner: ctx.accounts.owner.key(),
+            approvals,
+        });
+
+        Ok(())
+    }
+
+    /// Vote to cancel a pending admin action (owners only)
+    ///
+    /// Cancelled once `threshold` owners have voted to cancel; the proposal
+    /// account is then closed to its proposer.
+    pub fn cancel_admin_action(ctx: Context<CancelAdminAction>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let votes = proposal.approve_cancel(&ctx.accounts.config, &ctx.accounts.owner.key())?;
+
+        emit!(AdminActionCancelApproved {
+            proposal_id: proposal.id,
+            owner: ctx.accounts.owner.key(),
+            votes,
+        });
+        if proposal.cancelled {
+            emit!(AdminActionCancelled { proposal_id: proposal.id });
+            proposal.close(ctx.accounts.proposer.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// Execute an approved admin action (permissionless)
+    ///
+    /// The proposal account is closed to its proposer.
+    pub fn execute_admin_action(ctx: Context<ExecuteAdminAction>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute(config)?;
+
+        match proposal.action.clone() {
+            SwapAdminAction::Unpause => {
+                config.paused = false;
+                emit!(PauseUpdated { paused: false });
+            }
+            SwapAdminAction::SetKeeperTip { keeper_tip_bps } => {
+                require!(keeper_tip_bps <= 10000, ErrorCode::InvalidKeeperTip);
+                config.keeper_tip_bps = keeper_tip_bps;
+                emit!(KeeperTipUpdated { keeper_tip_bps });
+            }
+            SwapAdminAction::UpdateCommitDefaults { min_reveal_delay, max_reveal_delay, commit_bond } => {
+                validate_reveal_window(min_reveal_delay, max_reveal_delay)?;
+                config.min_reveal_delay = min_reveal_delay;
+                config.max_reveal_delay = max_reveal_delay;
+                config.commit_bond = commit_bond;
+                emit!(CommitDefaultsUpdated {
+                    min_reveal_delay,
+                    max_reveal_delay,
+                    commit_bond,
+                });
+            }
+            // Applies to new commitments only; existing ones keep the window
+            // and bond they were created with
+            SwapAdminAction::SetPoolCommitParams {
+                pool,
+                min_reveal_delay,
+                max_reveal_delay,
+                commit_bond,
+            } => {
+                validate_reveal_window(min_reveal_delay, max_reveal_delay)?;
+                let pool_account = ctx.accounts.pool
+                    .as_mut()
+                    .ok_or(ErrorCode::MissingProposalAccount)?;
+                require_keys_eq!(pool_account.key(), pool, ErrorCode::MissingProposalAccount);
+                require!(pool_account.initialized, ErrorCode::PoolNotInitialized);
+                pool_account.min_reveal_delay = min_reveal_delay;
+                pool_account.max_reveal_delay = max_reveal_delay;
+                pool_account.commit_bond = commit_bond;
+                emit!(PoolCommitParamsUpdated {
+                    pool,
+                    min_reveal_delay,
+                    max_reveal_delay,
+                    commit_bond,
+                });
+            }
+            SwapAdminAction::SetTreasury { treasury } => {
+                require!(treasury != Pubkey::default(), ErrorCode::InvalidTreasury);
+                let old_treasury = config.treasury;
+                config.treasury = treasury;
+                emit!(TreasuryUpdated {
+                    old_treasury,
+                    new_treasury: treasury,
+                });
+            }
+            SwapAdminAction::ProposeAuthority { new_authority } => {
+                config.propose_authority(new_authority)?;
+                emit!(AuthorityProposed {
+                    current_authority: config.authority,
+                    pending_authority: new_authority,
+                });
+            }
+            SwapAdminAction::CancelAuthorityTransfer => {
+                let cancelled = config.cancel_authority_transfer()?;
+                emit!(AuthorityTransferCancelled {
+                    authority: config.authority,
+                    cancelled_authority: cancelled,
+                });
+            }
+            SwapAdminAction::SetOwners { owners, threshold } => {
+                // Invalidates every pending proposal approved under the old owner set
+                config.multisig.replace(owners.clone(), threshold)?;
+                emit!(OwnersChanged {
+                    owners,
+                    threshold,
+                    seqno: config.multisig.seqno,
+                });
+            }
+        }
+
+        emit!(AdminActionExecuted {
+            proposal_id: proposal.id,
+            executor: ctx.accounts.executor.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Propose a new config authority
+    ///
+    /// Executes an approved `ProposeAuthority` proposal (permissionless) and
+    /// closes it to its proposer. The transfer completes once the new
+    /// authority signs `accept_authority`.
+    pub fn propose_authority(ctx: Context<ChangeAuthority>, new_authority: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute_matching(config, &SwapAdminAction::ProposeAuthority { new_authority })?;
+        config.propose_authority(new_authority)?;
+
+        emit!(AuthorityProposed {
+            current_authority: config.authority,
+            pending_authority: new_authority,
+        });
+        emit!(AdminActionExecuted {
+            proposal_id: proposal.id,
+            executor: ctx.accounts.executor.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a proposed authority transfer before it is accepted
+    ///
+    /// Executes an approved `CancelAuthorityTransfer` proposal (permissionless)
+    /// and closes it to its proposer.
+    pub fn cancel_authority_transfer(ctx: Context<ChangeAuthority>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute_matching(config, &SwapAdminAction::CancelAuthorityTransfer)?;
+        let cancelled = config.cancel_authority_transfer()?;
+
+        emit!(AuthorityTransferCancelled {
+            authority: config.authority,
+            cancelled_authority: cancelled,
+        });
+        emit!(AdminActionExecuted {
+            proposal_id: proposal.id,
+            executor: ctx.accounts.executor.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Accept an authority transfer proposed by the owners (signed by the new authority)
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let old_authority = config.authority;
+        config.authority = config.pending_authority;
+        config.pending_authority = Pubkey::default();
+
+        emit!(AuthorityTransferred {
+            old_authority,
+            new_authority: config.authority,
+        });
+
+        Ok(())
+    }
+}
+
+// ========== ACCOUNTS ==========
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SwapConfig::LEN,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, SwapConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(fee_tier: u16)]
+pub struct CreatePool<'info> {
+    #[account(mut, seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Pool::LEN,
+        seeds = [
+            b"pool",
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            fee_tier.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    /// CHECK: PDA authority
+    #[account(
+        seeds = [b"pool_authority", pool.key().as_ref()],
+        bump
+    )]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"lp_mint", pool.key().as_ref()],
+        bump,
+        mint::decimals = LP_DECIMALS,
+        mint::authority = pool_authority
+    )]
+    pub lp_mint: Account<'info, Mint>,
+
+    /// Holds the MINIMUM_LIQUIDITY shares of the first deposit; nothing
+    /// transfers out of it
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"locked_lp", pool.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = pool_authority
+    )]
+    pub locked_lp_token: Account<'info, TokenAccount>,
+
+    pub mint_a: Account<'info, Mint>,
+
+    pub mint_b: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vault", pool.key().as_ref(), mint_a.key().as_ref()],
+        bump,
+        token::mint = mint_a,
+        token::authority = pool_authority
+    )]
+    pub pool_token_a: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vault", pool.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+        token::mint = mint_b,
+        token::authority = pool_authority
+    )]
+    pub pool_token_b: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddLiquidity<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    #[account(mut, constraint = pool.initialized @ ErrorCode::PoolNotInitialized)]
+    pub pool: Account<'info, Pool>,
+
+    /// CHECK: PDA authority
+    #[account(
+        seeds = [b"pool_authority", pool.key().as_ref()],
+        bump
+    )]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    #[account(mut, address = pool.lp_mint @ ErrorCode::InvalidLpMint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = lp_mint,
+        associated_token::authority = user
+    )]
+    pub user_lp_token: Account<'info, TokenAccount>,
+
+    /// Receives the MINIMUM_LIQUIDITY shares of the first deposit
+    #[account(
+        mut,
+        seeds = [b"locked_lp", pool.key().as_ref()],
+        bump
+    )]
+    pub locked_lp_token: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, token::mint = pool.token_a, token::authority = user)]
+    pub user_token_a: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = pool.token_b, token::authority = user)]
+    pub user_token_b: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref(), pool.token_a.as_ref()],
+        bump,
+        token::authority = pool_authority
+    )]
+    pub pool_token_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref(), pool.token_b.as_ref()],
+        bump,
+        token::authority = pool_authority
+    )]
+    pub pool_token_b: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CommitSwap<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    /// Must be initialized, see `SwapCommitment::open`
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + SwapCommitment::LEN,
+    )]
+    pub swap_commitment: Account<'info, SwapCommitment>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSwap<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    /// CHECK: PDA authority
+    #[account(
+        seeds = [b"pool_authority", pool.key().as_ref()],
+        bump
+    )]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = user,
+        has_one = pool @ ErrorCode::InvalidSwapCommitment,
+        has_one = user @ ErrorCode::NotSwapOwner
+    )]
+    pub swap_commitment: Account<'info, SwapCommitment>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The user's account for the input mint; the two mints set the
+    /// direction, see `swap_direction`
+    #[account(mut, token::authority = user)]
+    pub user_token_in: Account<'info, TokenAccount>,
+
+    #[account(mut, token::authority = user)]
+    pub user_token_out: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref(), user_token_in.mint.as_ref()],
+        bump
+    )]
+    pub pool_token_in: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref(), user_token_out.mint.as_ref()],
+        bump
+    )]
+    pub pool_token_out: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveLiquidity<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    /// CHECK: PDA authority
+    #[account(
+        seeds = [b"pool_authority", pool.key().as_ref()],
+        bump
+    )]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    #[account(mut, address = pool.lp_mint @ ErrorCode::InvalidLpMint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(mut, token::mint = lp_mint, token::authority = user)]
+    pub user_lp_token: Account<'info, TokenAccount>,
+
+    pub user: Signer<'info>,
+
+    #[account(mut, token::mint = pool.token_a, token::authority = user)]
+    pub user_token_a: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = pool.token_b, token::authority = user)]
+    pub user_token_b: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref(), pool.token_a.as_ref()],
+        bump,
+        token::authority = pool_authority
+    )]
+    pub pool_token_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref(), pool.token_b.as_ref()],
+        bump,
+        token::authority = pool_authority
+    )]
+    pub pool_token_b: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(batch_id: u64)]
+pub struct CommitBatchOrder<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    #[account(constraint = pool.initialized @ ErrorCode::PoolNotInitialized)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + BatchAuction::LEN,
+        seeds = [b"batch", pool.key().as_ref(), batch_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub batch: Account<'info, BatchAuction>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + BatchOrder::LEN,
+    )]
+    pub order: Account<'info, BatchOrder>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealBatchOrder<'info> {
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool @ ErrorCode::InvalidBatchAccounts,
+        seeds = [b"batch", pool.key().as_ref(), batch.batch_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub batch: Account<'info, BatchAuction>,
+
+    #[account(
+        mut,
+        has_one = batch @ ErrorCode::InvalidBatchAccounts,
+        has_one = user @ ErrorCode::NotSwapOwner
+    )]
+    pub order: Account<'info, BatchOrder>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = user_token_in.owner == user.key() @ ErrorCode::InvalidTokenOwner
+    )]
+    pub user_token_in: Account<'info, TokenAccount>,
+
+    /// Vault for the input mint; `swap_direction` checks the mint is in the pool
+    #[account(
+        mut,
+        seeds = [b"vault", pool.key().as_ref(), user_token_in.mint.as_ref()],
+        bump
+    )]
+    pub pool_token_in: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SettleBatch<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        has_one = pool @ ErrorCode::InvalidBatchAccounts,
+        seeds = [b"batch", pool.key().as_ref(), batch.batch_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub batch: Account<'info, BatchAuction>,
+
+    pub settler: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimBatchOrder<'info> {
+    pub pool: Account<'info, Pool>,
+
+    /// CHECK: PDA authority
+    #[account(
+        seeds = [b"pool_authority", pool.key().as_ref()],
+        bump
+    )]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    /// The pool's batch, see `check_claim_accounts`
+    #[account(
+        mut,
+        seeds = [b"batch", pool.key().as_ref(), batch.batch_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub batch: Account<'info, BatchAuction>,
+
+    /// An order of `batch` owned by `user`, see `check_claim_accounts`
+    #[account(mut, close = user)]
+    pub order: Account<'info, BatchOrder>,
+
+    /// CHECK: order owner, see `check_claim_accounts`; receives its rent and bond
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+
+    /// Mint paid out, see `check_claim_accounts`
+    pub mint: Account<'info, Mint>,
+
+    /// Pool vault for the mint paid out, see `check_claim_accounts`
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = claimer,
+        associated_token::mint = mint,
+        associated_token::authority = user
+    )]
+    pub user_token: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ForfeitBatchOrder<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    #[account(mut)]
+    pub batch: Account<'info, BatchAuction>,
+
+    #[account(
+        mut,
+        has_one = batch @ ErrorCode::InvalidBatchAccounts,
+        has_one = user @ ErrorCode::NotSwapOwner,
+        close = user
+    )]
+    pub order: Account<'info, BatchOrder>,
+
+    /// CHECK: order owner, checked against the order; receives its rent
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+
+    /// CHECK: receives the forfeited bond; checked against the config
+    #[account(mut, address = config.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseBatch<'info> {
+    #[account(
+        mut,
+        has_one = creator @ ErrorCode::InvalidBatchAccounts,
+        close = creator
+    )]
+    pub batch: Account<'info, BatchAuction>,
+
+    /// CHECK: batch creator, checked against the batch; receives its rent
+    #[account(mut)]
+    pub creator: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSwapCommitment<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    #[account(mut, close = user, has_one = user @ ErrorCode::NotSwapOwner)]
+    pub swap_commitment: Account<'info, SwapCommitment>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: receives the bond; checked against the config
+    #[account(mut, address = config.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CrankExpiredCommitments<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    /// CHECK: receives forfeited bonds; checked against the config
+    #[account(mut, address = config.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub config: Account<'info, SwapConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdminAction<'info> {
+    #[account(mut, seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + SwapProposal::LEN,
+        seeds = [b"proposal", config.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, SwapProposal>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveAdminAction<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, SwapProposal>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAdminAction<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, SwapProposal>,
+
+    /// CHECK: receives the proposal's rent once it is cancelled
+    #[account(mut, address = proposal.proposer @ ErrorCode::MissingProposalAccount)]
+    pub proposer: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteAdminAction<'info> {
+    #[account(mut, seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, SwapProposal>,
+
+    /// CHECK: receives the executed proposal's rent
+    #[account(mut, address = proposal.proposer @ ErrorCode::MissingProposalAccount)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// Required for `SetPoolCommitParams`
+    #[account(mut)]
+    pub pool: Option<Account<'info, Pool>>,
+
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ChangeAuthority<'info> {
+    #[account(mut, seeds = [b"config"], bump)]
+    pub config: Account<'info, SwapConfig>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, SwapProposal>,
+
+    /// CHECK: receives the executed proposal's rent
+    #[account(mut, address = proposal.proposer @ ErrorCode::MissingProposalAccount)]
+    pub proposer: UncheckedAccount<'info>,
+
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.pending_authority != Pubkey::default() @ ErrorCode::NoPendingAuthority,
+        constraint = config.pending_authority == new_authority.key() @ ErrorCode::NotPendingAuthority
+    )]
+    pub config: Account<'info, SwapConfig>,
+
+    pub new_authority: Signer<'info>,
+}
+
+// ========== STATE ==========
+
+#[account]
+pub struct SwapConfig {
+    /// Operator key that may pause in an emergency; admin changes need
+    /// `multisig.threshold` owners through a `SwapProposal`
+    pub authority: Pubkey,
+    /// Proposed authority awaiting acceptance, default when none
+    pub pending_authority: Pubkey,
+    pub paused: bool,
+    pub total_pools: u64,
+    /// Reveal window and commit bond given to new pools
+    pub min_reveal_delay: i64,
+    pub max_reveal_delay: i64,
+    pub commit_bond: u64,
+    /// Receives bonds of expired commitments
+    pub treasury: Pubkey,
+    /// Share of a forfeited bond paid to the keeper that closes it
+    pub keeper_tip_bps: u16,
+    /// Owners that propose and approve admin actions
+    pub multisig: OwnerSet,
+    pub proposal_count: u64,
+}
+
+impl SwapConfig {
+    pub const LEN: usize = 32 + 32 + 1 + 8 + 8 + 8 + 8 + 32 + 2 + OwnerSet::LEN + 8;
+
+    /// Set the authority awaiting `accept_authority`
+    pub fn propose_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        require!(new_authority != Pubkey::default(), ErrorCode::InvalidAuthority);
+        self.pending_authority = new_authority;
+        Ok(())
+    }
+
+    /// Drop the pending authority and return it
+    pub fn cancel_authority_transfer(&mut self) -> Result<Pubkey> {
+        require!(
+            self.pending_authority != Pubkey::default(),
+            ErrorCode::NoPendingAuthority
+        );
+        Ok(std::mem::take(&mut self.pending_authority))
+    }
+
+    /// Split a forfeited `bond` into the keeper's tip and the treasury's share
+    ///
+    /// Nothing is forfeited while swaps are paused, as the owner could not
+    /// have executed in time; the bond then goes back with the rent.
+    pub fn forfeit(&self, bond: u64, keeper_tip_bps: u16) -> (u64, u64) {
+        if self.paused {
+            return (0, 0);
+        }
+        let keeper_tip = (bond as u128 * keeper_tip_bps as u128 / 10000) as u64;
+        (keeper_tip, bond - keeper_tip)
+    }
+}
+
+/// An admin action awaiting owner approvals
+#[account]
+pub struct SwapProposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: SwapAdminAction,
+    pub approvals: Approvals,
+    pub executed: bool,
+    /// Owner votes to cancel
+    pub cancel_approvals: Approvals,
+    pub cancelled: bool,
+}
+
+impl SwapProposal {
+    pub const LEN: usize = 8 + 32 + SwapAdminAction::LEN + Approvals::LEN + 1 + Approvals::LEN + 1;
+
+    /// Proposals stay open until executed or cancelled
+    fn check_open(&self) -> Result<()> {
+        require!(!self.executed, ErrorCode::AlreadyExecuted);
+        require!(!self.cancelled, ErrorCode::ProposalCancelled);
+        Ok(())
+    }
+
+    /// Record an owner's approval and return the approval count
+    pub fn approve(&mut self, config: &SwapConfig, owner: &Pubkey) -> Result<u8> {
+        self.check_open()?;
+        self.approvals.approve(&config.multisig, owner)
+    }
+
+    /// Record an owner's cancel vote and return the vote count; the
+    /// proposal is cancelled once the votes reach the threshold
+    pub fn approve_cancel(&mut self, config: &SwapConfig, owner: &Pubkey) -> Result<u8> {
+        self.check_open()?;
+        let votes = self.cancel_approvals.approve(&config.multisig, owner)?;
+        if self.cancel_approvals.check_threshold(&config.multisig).is_ok() {
+            self.cancelled = true;
+        }
+        Ok(votes)
+    }
+
+    /// Mark the proposal executed once enough current owners approved it
+    pub fn execute(&mut self, config: &SwapConfig) -> Result<()> {
+        self.check_open()?;
+        self.approvals.check_threshold(&config.multisig)?;
+        self.executed = true;
+        Ok(())
+    }
+
+    /// Execute a proposal from the instruction that carries out `action`
+    pub fn execute_matching(&mut self, config: &SwapConfig, action: &SwapAdminAction) -> Result<()> {
+        require!(self.action == *action, ErrorCode::ProposalActionMismatch);
+        self.execute(config)
+    }
+}
+
+#[account]
+pub struct Pool {
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub reserve_a_commitment: [u8; 32],
+    pub reserve_b_commitment: [u8; 32],
+    /// Token A owned by the pool; the vault may hold more (donations)
+    pub reserve_a: u64,
+    /// Token B owned by the pool; the vault may hold more (donations)
+    pub reserve_b: u64,
+    /// LP mint supply, including the MINIMUM_LIQUIDITY shares locked in
+    /// the pool's `locked_lp` account
+    pub total_supply: u64,
+    /// SPL mint for LP shares, minted by `pool_authority`
+    pub lp_mint: Pubkey,
+    /// Swap fee in basis points, part of the pool address
+    pub fee_tier: u16,
+    pub initialized: bool,
+    /// Seconds a commitment must wait before it can be revealed
+    pub min_reveal_delay: i64,
+    /// Seconds after commit at which an unrevealed commitment expires
+    pub max_reveal_delay: i64,
+    /// Lamports escrowed per commitment, forfeited if it expires
+    pub commit_bond: u64,
+}
+
+impl Pool {
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 8 + 8 + 8 + 32 + 2 + 1 + 8 + 8 + 8;
+
+    /// Add deposited liquidity to the reserves and their commitments
+    pub fn deposit(&mut self, amount_a: u64, amount_b: u64) -> Result<()> {
+        self.reserve_a = self.reserve_a
+            .checked_add(amount_a)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.reserve_b = self.reserve_b
+            .checked_add(amount_b)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.reserve_a_commitment = add_commitments(
+            &self.reserve_a_commitment,
+            &public_amount_commitment(amount_a)
+        );
+        self.reserve_b_commitment = add_commitments(
+            &self.reserve_b_commitment,
+            &public_amount_commitment(amount_b)
+        );
+        Ok(())
+    }
+
+    /// Move a trade through the reserves and their commitments
+    ///
+    /// `amount_in` joins the input side and `amount_out` leaves the output
+    /// side; the amounts are public once revealed.
+    pub fn swap(&mut self, direction: SwapDirection, amount_in: u64, amount_out: u64) -> Result<()> {
+        let (reserve_in, reserve_out, commitment_in, commitment_out) = match direction {
+            SwapDirection::AToB => (
+                &mut self.reserve_a,
+                &mut self.reserve_b,
+                &mut self.reserve_a_commitment,
+                &mut self.reserve_b_commitment,
+            ),
+            SwapDirection::BToA => (
+                &mut self.reserve_b,
+                &mut self.reserve_a,
+                &mut self.reserve_b_commitment,
+                &mut self.reserve_a_commitment,
+            ),
+        };
+        let new_reserve_in = reserve_in
+            .checked_add(amount_in)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let new_reserve_out = reserve_out
+            .checked_sub(amount_out)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        *reserve_in = new_reserve_in;
+        *reserve_out = new_reserve_out;
+        *commitment_in = add_commitments(commitment_in, &public_amount_commitment(amount_in));
+        *commitment_out = subtract_commitments(commitment_out, &public_amount_commitment(amount_out));
+        Ok(())
+    }
+
+    /// Amounts of each token paid out for burning `liquidity` LP tokens
+    ///
+    /// The provider must hold `lp_balance >= liquidity`, each share must
+    /// meet its minimum and the request must land before `deadline`.
+    pub fn withdrawal_amounts(
+        &self,
+        liquidity: u64,
+        lp_balance: u64,
+        min_amount_a: u64,
+        min_amount_b: u64,
+        deadline: i64,
+        now: i64,
+    ) -> Result<(u64, u64)> {
+        require!(now <= deadline, ErrorCode::DeadlineExceeded);
+        require!(liquidity > 0, ErrorCode::InsufficientLiquidity);
+        require!(lp_balance >= liquidity, ErrorCode::InsufficientLiquidity);
+        require!(liquidity <= self.total_supply, ErrorCode::InsufficientLiquidity);
+
+        // Pro-rata share of each reserve, rounded down in the pool's favour
+        let amount_a = (liquidity as u128 * self.reserve_a as u128 /
+            self.total_supply as u128) as u64;
+        let amount_b = (liquidity as u128 * self.reserve_b as u128 /
+            self.total_supply as u128) as u64;
+
+        require!(amount_a >= min_amount_a, ErrorCode::SlippageExceeded);
+        require!(amount_b >= min_amount_b, ErrorCode::SlippageExceeded);
+        Ok((amount_a, amount_b))
+    }
+
+    /// Take withdrawn liquidity out of the reserves and their commitments
+    pub fn withdraw(&mut self, amount_a: u64, amount_b: u64) -> Result<()> {
+        self.reserve_a = self.reserve_a
+            .checked_sub(amount_a)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.reserve_b = self.reserve_b
+            .checked_sub(amount_b)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.reserve_a_commitment = subtract_commitments(
+            &self.reserve_a_commitment,
+            &public_amount_commitment(amount_a)
+        );
+        self.reserve_b_commitment = subtract_commitments(
+            &self.reserve_b_commitment,
+            &public_amount_commitment(amount_b)
+        );
+        Ok(())
+    }
+}
+
+#[account]
+pub struct SwapCommitment {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    /// Hash binding the trade, see `compute_swap_hash`
+    pub swap_hash: [u8; 32],
+    pub timestamp: i64,
+    /// Earliest time the commitment can be revealed
+    pub reveal_after: i64,
+    /// Unrevealed commitments cannot execute after this time
+    pub expires_at: i64,
+    /// Lamports held on this account beyond rent, returned when the swap
+    /// executes and forfeited otherwise
+    pub bond: u64,
+    pub revealed: bool,
+    pub executed: bool,
+}
+
+impl SwapCommitment {
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1;
+
+    /// A new commitment by `user` on an initialized pool, with the pool's
+    /// reveal window and commit bond
+    pub fn open(
+        user: Pubkey,
+        pool_key: Pubkey,
+        pool: &Pool,
+        swap_hash: [u8; 32],
+        now: i64,
+    ) -> Result<Self> {
+        require!(pool.initialized, ErrorCode::PoolNotInitialized);
+        Ok(Self {
+            user,
+            pool: pool_key,
+            swap_hash,
+            timestamp: now,
+            reveal_after: now
+                .checked_add(pool.min_reveal_delay)
+                .ok_or(ErrorCode::ArithmeticOverflow)?,
+            expires_at: now
+                .checked_add(pool.max_reveal_delay)
+                .ok_or(ErrorCode::ArithmeticOverflow)?,
+            bond: pool.commit_bond,
+            revealed: false,
+            executed: false,
+        })
+    }
+
+    /// A keeper may close the commitment for `user` once it has expired
+    pub fn check_expired(&self, user: &Pubkey, now: i64) -> Result<()> {
+        require_keys_eq!(self.user, *user, ErrorCode::InvalidCrankAccounts);
+        require!(now > self.expires_at, ErrorCode::CommitmentNotExpired);
+        Ok(())
+    }
+
+    /// Open the commitment with the revealed trade inside its reveal window
+    pub fn reveal(
+        &mut self,
+        amount_in: u64,
+        min_amount_out: u64,
+        direction: SwapDirection,
+        salt: &[u8; 32],
+        now: i64,
+    ) -> Result<()> {
+        require!(!self.executed, ErrorCode::AlreadyExecuted);
+        require!(now >= self.reveal_after, ErrorCode::RevealTooEarly);
+        require!(now <= self.expires_at, ErrorCode::SwapExpired);
+        require!(
+            compute_swap_hash(amount_in, min_amount_out, direction, salt, &self.user)
+                == self.swap_hash,
+            ErrorCode::CommitmentMismatch
+        );
+        self.revealed = true;
+        Ok(())
+    }
+}
+
+/// One batch-auction slot of a pool
+#[account]
+pub struct BatchAuction {
+    pub pool: Pubkey,
+    pub batch_id: u64,
+    /// Paid the account's rent and gets it back in `close_batch`
+    pub creator: Pubkey,
+    /// Commits close and reveals open at this time
+    pub commit_end: i64,
+    /// Reveals close and settlement opens at this time
+    pub reveal_end: i64,
+    /// Settlement closes at this time; unsettled orders are then refunded
+    pub settle_end: i64,
+    pub order_count: u32,
+    pub revealed_count: u32,
+    /// Orders claimed or forfeited so far
+    pub closed_orders: u32,
+    /// Escrowed input of revealed A-to-B orders
+    pub total_in_a: u64,
+    /// Escrowed input of revealed B-to-A orders
+    pub total_in_b: u64,
+    pub settled: bool,
+    /// Token B per token A, scaled by PRICE_SCALE
+    pub clearing_price: u128,
+}
+
+impl BatchAuction {
+    pub const LEN: usize = 32 + 8 + 32 + 8 + 8 + 8 + 4 + 4 + 4 + 8 + 8 + 1 + 16;
+
+    /// A batch can be closed once it is over and all its orders are closed
+    pub fn check_closable(&self, now: i64) -> Result<()> {
+        require!(self.closed_orders == self.order_count, ErrorCode::BatchOrdersOpen);
+        require!(self.settled || now >= self.settle_end, ErrorCode::BatchNotSettled);
+        Ok(())
+    }
+
+    /// Count a committed order, up to `MAX_BATCH_ORDERS`
+    ///
+    /// Every committed order may be revealed, so the cap applies here rather
+    /// than at reveal, where a turned-away order would lose its bond.
+    fn open_order(&mut self) -> Result<()> {
+        require!((self.order_count as usize) < MAX_BATCH_ORDERS, ErrorCode::BatchFull);
+        self.order_count += 1;
+        Ok(())
+    }
+
+    /// Count an order closed by `claim_batch_order` or `forfeit_batch_order`
+    fn close_order(&mut self) -> Result<()> {
+        self.closed_orders = self.closed_orders
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct BatchOrder {
+    pub batch: Pubkey,
+    pub user: Pubkey,
+    /// Hash binding the order, see `compute_swap_hash`
+    pub swap_hash: [u8; 32],
+    pub revealed: bool,
+    pub direction: SwapDirection,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+    /// Output owed at the clearing price, zero if refunded
+    pub amount_out: u64,
+    /// Lamports held on this account beyond rent, returned when the order
+    /// is claimed and forfeited if it is never revealed
+    pub bond: u64,
+    /// Set by settlement when the order fills; otherwise it is refunded
+    pub filled: bool,
+    /// Set once the batch clears and `filled` / `amount_out` are final
+    pub settled: bool,
+}
+
+impl BatchOrder {
+    pub const LEN: usize = 32 + 32 + 32 + 1 + 1 + 8 + 8 + 8 + 8 + 1 + 1;
+
+    /// Mint and amount owed to the owner of a revealed order
+    ///
+    /// The output if the batch filled it, the input if the batch refunded
+    /// it or was not settled by `settle_end`.
+    pub fn payout(&self, pool: &Pool, batch: &BatchAuction, now: i64) -> Result<(Pubkey, u64)> {
+        require!(self.revealed, ErrorCode::BatchOrderNotRevealed);
+        require!(
+            self.settled || (!batch.settled && now >= batch.settle_end),
+            ErrorCode::BatchNotSettled
+        );
+
+        let (mint_in, mint_out) = match self.direction {
+            SwapDirection::AToB => (pool.token_a, pool.token_b),
+            SwapDirection::BToA => (pool.token_b, pool.token_a),
+        };
+        if self.settled && self.filled {
+            Ok((mint_out, self.amount_out))
+        } else {
+            Ok((mint_in, self.amount_in))
+        }
+    }
+}
+
+// ========== ENUMS ==========
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapDirection {
+    /// Sell `token_a` for `token_b`
+    AToB,
+    /// Sell `token_b` for `token_a`
+    BToA,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum SwapAdminAction {
+    Unpause,
+    SetOwners { owners: Vec<Pubkey>, threshold: u8 },
+    ProposeAuthority { new_authority: Pubkey },
+    CancelAuthorityTransfer,
+    UpdateCommitDefaults { min_reveal_delay: i64, max_reveal_delay: i64, commit_bond: u64 },
+    SetPoolCommitParams { pool: Pubkey, min_reveal_delay: i64, max_reveal_delay: i64, commit_bond: u64 },
+    SetTreasury { treasury: Pubkey },
+    SetKeeperTip { keeper_tip_bps: u16 },
+}
+
+impl SwapAdminAction {
+    // Discriminant + largest variant (SetOwners)
+    pub const LEN: usize = 1 + (4 + 32 * MAX_OWNERS) + 1;
+}
+
+// ========== STRUCTS ==========
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BulletproofProof {
+    pub a: [u8; 32],
+    pub s: [u8; 32],
+    pub t1: [u8; 32],
+    pub t2: [u8; 32],
+    pub taux: [u8; 32],
+    pub mu: [u8; 32],
+    pub inner_product: Vec<u8>,
+}
+
+/// Accounts `claim_batch_order` checks against its pool
+pub struct ClaimAccounts<'a> {
+    pub pool: Pubkey,
+    pub batch_key: Pubkey,
+    pub batch: &'a BatchAuction,
+    pub order: &'a BatchOrder,
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+}
+
+/// Revealed order as seen by the clearing computation
+#[derive(Clone, Copy)]
+pub struct BatchOrderInput {
+    pub key: Pubkey,
+    pub direction: SwapDirection,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+}
+
+/// Result of clearing a batch
+pub struct BatchClearing {
+    /// Per order: filled at the clearing price, or refunded
+    pub filled: Vec<bool>,
+    /// Per order: output owed if filled
+    pub amounts_out: Vec<u64>,
+    /// Imbalance traded against the pool: (direction, amount in, amount out)
+    pub net_trade: Option<(SwapDirection, u64, u64)>,
+    /// Token B per token A, scaled by PRICE_SCALE
+    pub clearing_price: u128,
+}
+
+// ========== EVENTS ==========
+
+#[event]
+pub struct PoolCreated {
+    pub pool_id: Pubkey,
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub fee_tier: u16,
+    pub lp_mint: Pubkey,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub pool: Pubkey,
+    pub provider: Pubkey,
+    pub amount_a_commitment: [u8; 32],
+    pub amount_b_commitment: [u8; 32],
+    pub liquidity: u64,
+}
+
+#[event]
+pub struct SwapCommitted {
+    pub swap_id: Pubkey,
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub swap_hash: [u8; 32],
+    pub timestamp: i64,
+    pub reveal_after: i64,
+    pub expires_at: i64,
+    pub bond: u64,
+}
+
+#[event]
+pub struct SwapExecuted {
+    pub swap_id: Pubkey,
+    pub pool: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub direction: SwapDirection,
+}
+
+#[event]
+pub struct BatchOrderCommitted {
+    pub batch: Pubkey,
+    pub order: Pubkey,
+    pub user: Pubkey,
+    pub swap_hash: [u8; 32],
+}
+
+#[event]
+pub struct BatchOrderRevealed {
+    pub batch: Pubkey,
+    pub order: Pubkey,
+    pub direction: SwapDirection,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+}
+
+#[event]
+pub struct BatchSettled {
+    pub batch: Pubkey,
+    pub pool: Pubkey,
+    pub clearing_price: u128,
+    pub filled_orders: u32,
+    pub refunded_orders: u32,
+    pub net_direction: Option<SwapDirection>,
+    pub net_amount_in: u64,
+    pub net_amount_out: u64,
+}
+
+#[event]
+pub struct BatchOrderClaimed {
+    pub batch: Pubkey,
+    pub order: Pubkey,
+    pub user: Pubkey,
+    pub filled: bool,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BatchOrderForfeited {
+    pub batch: Pubkey,
+    pub order: Pubkey,
+    pub user: Pubkey,
+    pub closed_by: Pubkey,
+    pub bond_forfeited: u64,
+    pub keeper_tip: u64,
+}
+
+#[event]
+pub struct BatchClosed {
+    pub batch: Pubkey,
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+}
+
+#[event]
+pub struct LiquidityRemoved {
+    pub pool: Pubkey,
+    pub provider: Pubkey,
+    pub liquidity: u64,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct SwapCommitmentClosed {
+    pub swap_id: Pubkey,
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub closed_by: Pubkey,
+    pub bond_forfeited: u64,
+    pub keeper_tip: u64,
+}
+
+#[event]
+pub struct KeeperTipUpdated {
+    pub keeper_tip_bps: u16,
+}
+
+#[event]
+pub struct CommitDefaultsUpdated {
+    pub min_reveal_delay: i64,
+    pub max_reveal_delay: i64,
+    pub commit_bond: u64,
+}
+
+#[event]
+pub struct PoolCommitParamsUpdated {
+    pub pool: Pubkey,
+    pub min_reveal_delay: i64,
+    pub max_reveal_delay: i64,
+    pub commit_bond: u64,
+}
+
+#[event]
+pub struct TreasuryUpdated {
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub current_authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub authority: Pubkey,
+    pub cancelled_authority: Pubkey,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub paused: bool,
+}
+
+#[event]
+pub struct AdminActionProposed {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub action: SwapAdminAction,
+}
+
+#[event]
+pub struct AdminActionApproved {
+    pub proposal_id: u64,
+    pub owner: Pubkey,
+    pub approvals: u8,
+}
+
+#[event]
+pub struct AdminActionCancelApproved {
+    pub proposal_id: u64,
+    pub owner: Pubkey,
+    pub votes: u8,
+}
+
+#[event]
+pub struct AdminActionCancelled {
+    pub proposal_id: u64,
+}
+
+#[event]
+pub struct AdminActionExecuted {
+    pub proposal_id: u64,
+    pub executor: Pubkey,
+}
+
+#[event]
+pub struct OwnersChanged {
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+    pub seqno: u32,
+}
+
+// ========== ERRORS ==========
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Swap is paused")]
+    SwapPaused,
+
+    #[msg("Pool not initialized")]
+    PoolNotInitialized,
+
+    #[msg("Invalid Bulletproof")]
+    InvalidProof,
+
+    #[msg("Insufficient liquidity")]
+    InsufficientLiquidity,
+
+    #[msg("Not swap owner")]
+    NotSwapOwner,
+
+    #[msg("Already executed")]
+    AlreadyExecuted,
+
+    #[msg("Reveal delay has not elapsed")]
+    RevealTooEarly,
+
+    #[msg("Swap commitment expired")]
+    SwapExpired,
+
+    #[msg("Slippage exceeded")]
+    SlippageExceeded,
+
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+
+    #[msg("Invalid authority")]
+    InvalidAuthority,
+
+    #[msg("No pending authority transfer")]
+    NoPendingAuthority,
+
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+
+    #[msg("Fee must be below 10000 basis points")]
+    InvalidFee,
+
+    #[msg("LP mint does not belong to this pool")]
+    InvalidLpMint,
+
+    #[msg("Transaction deadline passed")]
+    DeadlineExceeded,
+
+    #[msg("Signer does not own the liquidity position")]
+    NotPositionOwner,
+
+    #[msg("Token mints do not match the pool")]
+    InvalidSwapMints,
+
+    #[msg("Token account is not owned by the signer")]
+    InvalidTokenOwner,
+
+    #[msg("Swap commitment belongs to another pool")]
+    InvalidSwapCommitment,
+
+    #[msg("Vault is not the pool's vault for this mint")]
+    InvalidVault,
+
+    #[msg("Unsupported fee tier")]
+    InvalidFeeTier,
+
+    #[msg("mint_a must sort before mint_b")]
+    UnsortedMints,
+
+    #[msg("Revealed swap does not match the commitment")]
+    CommitmentMismatch,
+
+    #[msg("Batch is not accepting commitments")]
+    BatchNotInCommitPhase,
+
+    #[msg("Batch is not in its reveal phase")]
+    BatchNotInRevealPhase,
+
+    #[msg("Batch reveal phase has not ended")]
+    BatchNotSettleable,
+
+    #[msg("Batch is full")]
+    BatchFull,
+
+    #[msg("Invalid or missing batch accounts")]
+    InvalidBatchAccounts,
+
+    #[msg("Batch settlement window has closed")]
+    BatchSettleExpired,
+
+    #[msg("Batch has not been settled and can still be")]
+    BatchNotSettled,
+
+    #[msg("Batch order was not revealed")]
+    BatchOrderNotRevealed,
+
+    #[msg("Batch order was revealed")]
+    BatchOrderRevealed,
+
+    #[msg("Invalid reveal window")]
+    InvalidRevealWindow,
+
+    #[msg("Swap commitment has not expired")]
+    CommitmentNotExpired,
+
+    #[msg("Swap commitment holds no bond")]
+    NoCommitBond,
+
+    #[msg("Invalid treasury")]
+    InvalidTreasury,
+
+    #[msg("Account required by the proposal is missing or does not match")]
+    MissingProposalAccount,
+
+    #[msg("Batch still has open orders")]
+    BatchOrdersOpen,
+
+    #[msg("Invalid keeper tip")]
+    InvalidKeeperTip,
+
+    #[msg("Invalid or missing commitment accounts")]
+    InvalidCrankAccounts,
+
+    #[msg("Proposal action does not match the instruction")]
+    ProposalActionMismatch,
+
+    #[msg("Proposal was cancelled")]
+    ProposalCancelled,
+}
+
+// ========== CONSTANTS ==========
+
+const MINIMUM_LIQUIDITY: u64 = 1000;
+
+/// Seconds a commitment waits before its reveal, until the authority
+/// changes it
+const DEFAULT_MIN_REVEAL_DELAY: i64 = 60;
+
+/// Seconds after commit at which an unrevealed commitment expires, until
+/// the authority changes it
+const DEFAULT_MAX_REVEAL_DELAY: i64 = 600;
+
+/// Longest allowed reveal window (1 day)
+const MAX_REVEAL_DELAY: i64 = 86_400;
+
+/// Commit bond given to pools until the authority changes it (0.01 SOL)
+const DEFAULT_COMMIT_BOND: u64 = 10_000_000;
+
+/// Share of forfeited bonds paid to keepers until the authority changes it
+const DEFAULT_KEEPER_TIP_BPS: u16 = 1000;
+
+/// Remaining accounts per commitment in `crank_expired_commitments`
+const CRANK_ACCOUNTS: usize = 2;
+
+/// Decimals of every pool's LP mint
+const LP_DECIMALS: u8 = 9;
+
+/// Length of a batch-auction commit slot in seconds
+const BATCH_DURATION: i64 = 30;
+
+/// Seconds after a batch's commit slot during which orders can be revealed
+const BATCH_REVEAL_WINDOW: i64 = 30;
+
+/// Seconds after a batch's reveal phase during which it can be settled
+const BATCH_SETTLE_WINDOW: i64 = 600;
+
+/// Most revealed orders per batch, bounded by the accounts `settle_batch`
+/// can take
+pub const MAX_BATCH_ORDERS: usize = 16;
+
+/// Fixed-point scale of batch clearing prices
+pub const PRICE_SCALE: u128 = 1_000_000_000_000;
+
+/// Allowed pool fees in basis points (0.05%, 0.3%, 1%)
+pub const FEE_TIERS: [u16; 3] = [5, 30, 100];
+
+// ========== HELPER FUNCTIONS ==========
+
+/// Add Pedersen commitments (homomorphic)
+fn add_commitments(c1: &[u8; 32], c2: &[u8; 32]) -> [u8; 32] {
+    // Simplified - in production, use proper elliptic curve addition
+    let mut result = [0u8; 32];
+    for i in 0..32 {
+        result[i] = c1[i].wrapping_add(c2[i]);
+    }
+    result
+}
+
+/// Subtract Pedersen commitments (homomorphic)
+fn subtract_commitments(c1: &[u8; 32], c2: &[u8; 32]) -> [u8; 32] {
+    // Simplified - in production, use proper elliptic curve subtraction
+    let mut result = [0u8; 32];
+    for i in 0..32 {
+        result[i] = c1[i].wrapping_sub(c2[i]);
+    }
+    result
+}
+
+/// Commitment to a public amount with zero blinding
+fn public_amount_commitment(amount: u64) -> [u8; 32] {
+    // Simplified - in production, amount * G on the Pedersen generator
+    let mut commitment = [0u8; 32];
+    commitment[..8].copy_from_slice(&amount.to_le_bytes());
+    commitment
+}
+
+/// Verify Bulletproof range proof
+fn verify_range_proof(
+    _commitment: &[u8; 32],
+    proof: &BulletproofProof,
+    _min: u64,
+    _max: u64,
+) -> Result<bool> {
+    // Mock verification - in production, use bulletproofs crate
+    let valid = proof.a.len() == 32 && 
+                proof.s.len() == 32 && 
+                proof.t1.len() == 32 && 
+                proof.t2.len() == 32;
+    Ok(valid)
+}
+
+/// Order two mints as (token_a, token_b) for a pool
+pub fn sort_mints(mint_x: Pubkey, mint_y: Pubkey) -> (Pubkey, Pubkey) {
+    if mint_x < mint_y {
+        (mint_x, mint_y)
+    } else {
+        (mint_y, mint_x)
+    }
+}
+
+/// Only sorted, distinct mints and a listed fee tier identify a pool
+pub fn check_pool_key(token_a: Pubkey, token_b: Pubkey, fee_tier: u16) -> Result<()> {
+    require!(token_a < token_b, ErrorCode::UnsortedMints);
+    require!(FEE_TIERS.contains(&fee_tier), ErrorCode::InvalidFeeTier);
+    Ok(())
+}
+
+/// Address of the pool for a token pair and fee tier, in either mint order
+pub fn find_pool_address(mint_x: Pubkey, mint_y: Pubkey, fee_tier: u16) -> (Pubkey, u8) {
+    let (token_a, token_b) = sort_mints(mint_x, mint_y);
+    Pubkey::find_program_address(
+        &[
+            b"pool",
+            token_a.as_ref(),
+            token_b.as_ref(),
+            &fee_tier.to_le_bytes(),
+        ],
+        &ID,
+    )
+}
+
+/// Address of a pool's vault for `mint`
+pub fn find_vault_address(pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", pool.as_ref(), mint.as_ref()], &ID)
+}
+
+/// Addresses of every possible pool for a token pair, one per fee tier
+pub fn find_pool_addresses(mint_x: Pubkey, mint_y: Pubkey) -> Vec<(u16, Pubkey)> {
+    FEE_TIERS
+        .iter()
+        .map(|&fee_tier| (fee_tier, find_pool_address(mint_x, mint_y, fee_tier).0))
+        .collect()
+}
+
+/// Hash a swap commits to before revealing it
+///
+/// `keccak256(amount_in || min_amount_out || direction || salt || user)`
+/// with little-endian amounts and `direction` as 0 (A to B) or 1 (B to A).
+/// `salt` must be random and secret, or small trades can be brute-forced.
+pub fn compute_swap_hash(
+    amount_in: u64,
+    min_amount_out: u64,
+    direction: SwapDirection,
+    salt: &[u8; 32],
+    user: &Pubkey,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        &amount_in.to_le_bytes(),
+        &min_amount_out.to_le_bytes(),
+        &[direction as u8],
+        salt,
+        user.as_ref(),
+    ])
+    .to_bytes()
+}
+
+/// Derive the swap direction from the input and output mints
+fn swap_direction(pool: &Pool, mint_in: Pubkey, mint_out: Pubkey) -> Result<SwapDirection> {
+    if mint_in == pool.token_a && mint_out == pool.token_b {
+        Ok(SwapDirection::AToB)
+    } else if mint_in == pool.token_b && mint_out == pool.token_a {
+        Ok(SwapDirection::BToA)
+    } else {
+        err!(ErrorCode::InvalidSwapMints)
+    }
+}
+
+/// Check the accounts of a batch order claim and derive its payout
+///
+/// The batch must belong to this pool, the order to the batch and user, and
+/// the mint and vault must be the ones the order is paid from.
+fn check_claim_accounts(pool: &Pool, accounts: &ClaimAccounts, now: i64) -> Result<(Pubkey, u64)> {
+    require_keys_eq!(accounts.batch.pool, accounts.pool, ErrorCode::InvalidBatchAccounts);
+    require_keys_eq!(accounts.order.batch, accounts.batch_key, ErrorCode::InvalidBatchAccounts);
+    require_keys_eq!(accounts.order.user, accounts.user, ErrorCode::NotSwapOwner);
+
+    let (mint, amount) = accounts.order.payout(pool, accounts.batch, now)?;
+    require_keys_eq!(accounts.mint, mint, ErrorCode::InvalidSwapMints);
+    require_keys_eq!(
+        accounts.vault,
+        find_vault_address(&accounts.pool, &mint).0,
+        ErrorCode::InvalidVault
+    );
+    Ok((mint, amount))
+}
+
+/// Calculate output amount (constant product formula)
+///
+/// Same rounding as Uniswap v2 `getAmountOut`, with the fee in basis points:
+/// `out = in * (10000 - fee) * R_out / (R_in * 10000 + in * (10000 - fee))`
+fn calculate_output_amount(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    require!(amount_in > 0, ErrorCode::InvalidAmount);
+    require!(reserve_in > 0 && reserve_out > 0, ErrorCode::InsufficientLiquidity);
+    require!(fee_bps < 10000, ErrorCode::InvalidFee);
+
+    let amount_in_with_fee = amount_in as u128 * (10000 - fee_bps as u128);
+    let numerator = amount_in_with_fee
+        .checked_mul(reserve_out as u128)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let denominator = (reserve_in as u128 * 10000)
+        .checked_add(amount_in_with_fee)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    u64::try_from(numerator / denominator).map_err(|_| ErrorCode::ArithmeticOverflow.into())
+}
+
+/// Clear a batch against the pool at a uniform price
+///
+/// With `A` sold into the batch, `B` sold the other way, reserves `Ra`, `Rb`
+/// and `g = 1 - fee`, the imbalance `x` that goes through the pool is the one
+/// whose pool execution price equals the batch price:
+///
+///   A-side excess: x = (A * Rb * g - B * Ra) / (g * (Rb + B))
+///   B-side excess: x = (B * Ra * g - A * Rb) / (g * (Ra + A))
+///
+/// Inside the fee band neither side is in excess and the orders cross
+/// directly at `B / A`. While some orders are below their limit, the one
+/// furthest below it (highest `min_amount_out / amount_out`) is dropped and
+/// the batch cleared again, as the others may fill at the price without it.
+/// Equal shortfalls are broken on the order key, so the order in which the
+/// settler passes the orders does not matter.
+pub fn clear_batch(
+    orders: &[BatchOrderInput],
+    reserve_a: u64,
+    reserve_b: u64,
+    fee_bps: u16,
+) -> Result<BatchClearing> {
+    require!(fee_bps < 10000, ErrorCode::InvalidFee);
+    let mut filled: Vec<bool> = orders.iter().map(|order| order.amount_in > 0).collect();
+
+    loop {
+        let mut total_a: u64 = 0;
+        let mut total_b: u64 = 0;
+        for (order, _) in orders.iter().zip(filled.iter()).filter(|(_, filled)| **filled) {
+            match order.direction {
+                SwapDirection::AToB => {
+                    total_a = total_a.checked_add(order.amount_in).ok_or(ErrorCode::ArithmeticOverflow)?
+                }
+                SwapDirection::BToA => {
+                    total_b = total_b.checked_add(order.amount_in).ok_or(ErrorCode::ArithmeticOverflow)?
+                }
+            }
+        }
+
+        // Net the two sides, then route the imbalance through the pool
+        let net_trade = match net_excess(total_a, total_b, reserve_a, reserve_b, fee_bps)? {
+            Some((SwapDirection::AToB, x)) => {
+                Some((SwapDirection::AToB, x, calculate_output_amount(x, reserve_a, reserve_b, fee_bps)?))
+            }
+            Some((SwapDirection::BToA, x)) => {
+                Some((SwapDirection::BToA, x, calculate_output_amount(x, reserve_b, reserve_a, fee_bps)?))
+            }
+            None => None,
+        };
+
+        // Total each side receives: the other side's input, adjusted by the pool trade
+        let (paid_to_a_sellers, paid_to_b_sellers) = match net_trade {
+            Some((SwapDirection::AToB, x, y)) => (
+                total_b.checked_add(y).ok_or(ErrorCode::ArithmeticOverflow)?,
+                total_a.checked_sub(x).ok_or(ErrorCode::ArithmeticOverflow)?,
+            ),
+            Some((SwapDirection::BToA, x, y)) => (
+                total_b.checked_sub(x).ok_or(ErrorCode::ArithmeticOverflow)?,
+                total_a.checked_add(y).ok_or(ErrorCode::ArithmeticOverflow)?,
+            ),
+            None => (total_b, total_a),
+        };
+
+        let amounts_out: Vec<u64> = orders
+            .iter()
+            .zip(filled.iter())
+            .map(|(order, filled)| match (filled, order.direction) {
+                (false, _) => 0,
+                (true, SwapDirection::AToB) => pro_rata(order.amount_in, paid_to_a_sellers, total_a),
+                (true, SwapDirection::BToA) => pro_rata(order.amount_in, paid_to_b_sellers, total_b),
+            })
+            .collect();
+
+        // Drop the order with the tightest unmet limit and clear again
+        let mut tightest: Option<usize> = None;
+        for (index, order) in orders.iter().enumerate() {
+            let amount_out = amounts_out[index];
+            if !filled[index] || (amount_out > 0 && amount_out >= order.min_amount_out) {
+                continue;
+            }
+            let tighter = match tightest {
+                Some(current) => tighter_limit(order, amount_out, &orders[current], amounts_out[current]),
+                None => true,
+            };
+            if tighter {
+                tightest = Some(index);
+            }
+        }
+        if let Some(index) = tightest {
+            filled[index] = false;
+            continue;
+        }
+
+        let clearing_price = if total_a > 0 {
+            paid_to_a_sellers as u128 * PRICE_SCALE / total_a as u128
+        } else if paid_to_b_sellers > 0 {
+            total_b as u128 * PRICE_SCALE / paid_to_b_sellers as u128
+        } else {
+            0
+        };
+
+        return Ok(BatchClearing {
+            filled,
+            amounts_out,
+            net_trade,
+            clearing_price,
+        });
+    }
+}
+
+/// Whether `order` is further below its limit than `other`
+///
+/// Compares `min_amount_out / amount_out`, which orders of both directions
+/// share; an order offered nothing is the furthest below. Of two orders
+/// equally far below, the one with the higher key counts as further.
+fn tighter_limit(order: &BatchOrderInput, amount_out: u64, other: &BatchOrderInput, other_out: u64) -> bool {
+    let shortfall = match (amount_out, other_out) {
+        (0, 0) => Ordering::Equal,
+        (_, 0) => Ordering::Less,
+        (0, _) => Ordering::Greater,
+        _ => (order.min_amount_out as u128 * other_out as u128)
+            .cmp(&(other.min_amount_out as u128 * amount_out as u128)),
+    };
+    shortfall.then_with(|| order.key.cmp(&other.key)) == Ordering::Greater
+}
+
+/// Side in excess after netting, and how much of it trades against the pool
+fn net_excess(
+    total_a: u64,
+    total_b: u64,
+    reserve_a: u64,
+    reserve_b: u64,
+    fee_bps: u16,
+) -> Result<Option<(SwapDirection, u64)>> {
+    let g = 10000 - fee_bps as u128;
+    let mul = |x: u128, y: u128| x.checked_mul(y).ok_or(ErrorCode::ArithmeticOverflow);
+
+    let a_value = mul(mul(total_a as u128, reserve_b as u128)?, g)?;
+    let b_value = mul(mul(total_b as u128, reserve_a as u128)?, 10000)?;
+    if a_value > b_value {
+        let denominator = mul(g, reserve_b as u128 + total_b as u128)?;
+        let x = ((a_value - b_value) / denominator) as u64;
+        return Ok((x > 0).then_some((SwapDirection::AToB, x)));
+    }
+
+    let b_value = mul(mul(total_b as u128, reserve_a as u128)?, g)?;
+    let a_value = mul(mul(total_a as u128, reserve_b as u128)?, 10000)?;
+    if b_value > a_value {
+        let denominator = mul(g, reserve_a as u128 + total_a as u128)?;
+        let x = ((b_value - a_value) / denominator) as u64;
+        return Ok((x > 0).then_some((SwapDirection::BToA, x)));
+    }
+
+    Ok(None)
+}
+
+/// `amount * total_out / total_in`, rounded down
+fn pro_rata(amount: u64, total_out: u64, total_in: u64) -> u64 {
+    if total_in == 0 {
+        return 0;
+    }
+    (amount as u128 * total_out as u128 / total_in as u128) as u64
+}
+
+/// The window must open no later than it closes and stay within a day
+fn validate_reveal_window(min_reveal_delay: i64, max_reveal_delay: i64) -> Result<()> {
+    require!(
+        min_reveal_delay >= 0
+            && min_reveal_delay < max_reveal_delay
+            && max_reveal_delay <= MAX_REVEAL_DELAY,
+        ErrorCode::InvalidRevealWindow
+    );
+    Ok(())
+}
+
+/// Move lamports out of an account owned by this program
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    let from_balance = from.lamports()
+        .checked_sub(amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let to_balance = to.lamports()
+        .checked_add(amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    **from.try_borrow_mut_lamports()? = from_balance;
+    **to.try_borrow_mut_lamports()? = to_balance;
+    Ok(())
+}
+
+/// Transfer from a pool vault, signed by the pool authority PDA
+fn pay_from_vault<'info>(
+    vault: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    pool_authority: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let cpi_accounts = Transfer {
+        from: vault.clone(),
+        to: destination.clone(),
+        authority: pool_authority.clone(),
+    };
+    token::transfer(
+        CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer),
+        amount
+    )
+}
+
+/// Integer square root (floor) of a product of two u64 values
+fn integer_sqrt(value: u128) -> u64 {
+    if value < 2 {
+        return value as u64;
+    }
+    // Newton's method from an initial guess above the root
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_option::COption;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token;
+    use std::collections::BTreeSet;
+
+    fn test_pool() -> (Pubkey, Pool) {
+        let (token_a, token_b) = sort_mints(Pubkey::new_unique(), Pubkey::new_unique());
+        let pool = Pool {
+            token_a,
+            token_b,
+            reserve_a_commitment: [0; 32],
+            reserve_b_commitment: [0; 32],
+            reserve_a: 1_000_000,
+            reserve_b: 1_000_000,
+            total_supply: 1_000_000,
+            lp_mint: Pubkey::new_unique(),
+            fee_tier: 30,
+            initialized: true,
+            min_reveal_delay: DEFAULT_MIN_REVEAL_DELAY,
+            max_reveal_delay: DEFAULT_MAX_REVEAL_DELAY,
+            commit_bond: DEFAULT_COMMIT_BOND,
+        };
+        (find_pool_address(token_a, token_b, 30).0, pool)
+    }
+
+    #[test]
+    fn pool_address_ignores_mint_order() {
+        let (mint_x, mint_y) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(find_pool_address(mint_x, mint_y, 30), find_pool_address(mint_y, mint_x, 30));
+        assert_ne!(find_pool_address(mint_x, mint_y, 30).0, find_pool_address(mint_x, mint_y, 5).0);
+
+        let pools = find_pool_addresses(mint_y, mint_x);
+        assert_eq!(pools.len(), FEE_TIERS.len());
+        for (fee_tier, pool) in pools {
+            assert_eq!(pool, find_pool_address(mint_x, mint_y, fee_tier).0);
+        }
+    }
+
+    #[test]
+    fn pools_need_sorted_mints_and_a_listed_fee_tier() {
+        let (token_a, token_b) = sort_mints(Pubkey::new_unique(), Pubkey::new_unique());
+        for fee_tier in FEE_TIERS {
+            check_pool_key(token_a, token_b, fee_tier).unwrap();
+        }
+        for (mint_a, mint_b) in [(token_b, token_a), (token_a, token_a)] {
+            assert_eq!(
+                check_pool_key(mint_a, mint_b, 30).unwrap_err(),
+                ErrorCode::UnsortedMints.into()
+            );
+        }
+        for fee_tier in [0, 1, 31, 10_000] {
+            assert_eq!(
+                check_pool_key(token_a, token_b, fee_tier).unwrap_err(),
+                ErrorCode::InvalidFeeTier.into()
+            );
+        }
+    }
+
+    fn commitment(pool: Pubkey, user: Pubkey) -> SwapCommitment {
+        SwapCommitment {
+            user,
+            pool,
+            swap_hash: [0; 32],
+            timestamp: 0,
+            reveal_after: 0,
+            expires_at: 0,
+            bond: 0,
+            revealed: false,
+            executed: false,
+        }
+    }
+
+    #[test]
+    fn direction_follows_the_mints() {
+        let (_, pool) = test_pool();
+        assert_eq!(swap_direction(&pool, pool.token_a, pool.token_b).unwrap(), SwapDirection::AToB);
+        assert_eq!(swap_direction(&pool, pool.token_b, pool.token_a).unwrap(), SwapDirection::BToA);
+        for (mint_in, mint_out) in [
+            (pool.token_a, pool.token_a),
+            (pool.token_b, pool.token_b),
+            (pool.token_a, Pubkey::new_unique()),
+            (Pubkey::new_unique(), pool.token_b),
+        ] {
+            assert_eq!(
+                swap_direction(&pool, mint_in, mint_out).unwrap_err(),
+                ErrorCode::InvalidSwapMints.into()
+            );
+        }
+    }
+
+    /// An account as `try_accounts` sees it
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        data: Vec<u8>,
+        is_signer: bool,
+        executable: bool,
+    }
+
+    impl TestAccount {
+        fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+            Self { key, owner, data, is_signer: false, executable: false }
+        }
+
+        fn program<T: AccountSerialize>(key: Pubkey, state: &T) -> Self {
+            let mut data = Vec::new();
+            state.try_serialize(&mut data).unwrap();
+            Self::new(key, ID, data)
+        }
+
+        fn token(key: Pubkey, mint: Pubkey, owner: Pubkey) -> Self {
+            let mut data = vec![0; spl_token::state::Account::LEN];
+            spl_token::state::Account {
+                mint,
+                owner,
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            }
+            .pack_into_slice(&mut data);
+            Self::new(key, spl_token::ID, data)
+        }
+
+        fn mint(key: Pubkey, authority: Pubkey) -> Self {
+            let mut data = vec![0; spl_token::state::Mint::LEN];
+            spl_token::state::Mint {
+                mint_authority: COption::Some(authority),
+                decimals: LP_DECIMALS,
+                is_initialized: true,
+                ..Default::default()
+            }
+            .pack_into_slice(&mut data);
+            Self::new(key, spl_token::ID, data)
+        }
+
+        fn signer(key: Pubkey) -> Self {
+            Self { is_signer: true, ..Self::new(key, system_program::ID, Vec::new()) }
+        }
+
+        fn executable(key: Pubkey) -> Self {
+            Self { executable: true, ..Self::new(key, Pubkey::default(), Vec::new()) }
+        }
+    }
+
+    /// Run the Anchor account validation of `T` over `accounts`, in field order
+    fn try_accounts<T>(accounts: &[TestAccount]) -> Result<()>
+    where
+        T: Accounts<'static, T::Bumps> + anchor_lang::Bumps,
+        T::Bumps: Default,
+    {
+        let infos: Vec<AccountInfo<'static>> = accounts
+            .iter()
+            .map(|account| {
+                AccountInfo::new(
+                    Box::leak(Box::new(account.key)),
+                    account.is_signer,
+                    true,
+                    Box::leak(Box::new(1_000_000_000)),
+                    Box::leak(account.data.clone().into_boxed_slice()),
+                    Box::leak(Box::new(account.owner)),
+                    account.executable,
+                    0,
+                )
+            })
+            .collect();
+        let mut infos: &'static [AccountInfo<'static>] = Box::leak(infos.into_boxed_slice());
+        T::try_accounts(&ID, &mut infos, &[], &mut T::Bumps::default(), &mut BTreeSet::new())?;
+        Ok(())
+    }
+
+    fn pool_authority(pool_key: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"pool_authority", pool_key.as_ref()], &ID).0
+    }
+
+    // Field positions in `ExecuteSwap`
+    const SWAP_COMMITMENT: usize = 3;
+    const USER_TOKEN_IN: usize = 5;
+    const USER_TOKEN_OUT: usize = 6;
+    const POOL_TOKEN_IN: usize = 7;
+    const POOL_TOKEN_OUT: usize = 8;
+
+    /// `ExecuteSwap` accounts for an A-to-B swap committed by `user`
+    fn execute_swap_accounts(pool_key: Pubkey, pool: &Pool, user: Pubkey) -> Vec<TestAccount> {
+        let authority = pool_authority(&pool_key);
+        vec![
+            TestAccount::program(Pubkey::find_program_address(&[b"config"], &ID).0, &test_config()),
+            TestAccount::program(pool_key, pool),
+            TestAccount::new(authority, system_program::ID, Vec::new()),
+            TestAccount::program(Pubkey::new_unique(), &commitment(pool_key, user)),
+            TestAccount::signer(user),
+            TestAccount::token(Pubkey::new_unique(), pool.token_a, user),
+            TestAccount::token(Pubkey::new_unique(), pool.token_b, user),
+            TestAccount::token(find_vault_address(&pool_key, &pool.token_a).0, pool.token_a, authority),
+            TestAccount::token(find_vault_address(&pool_key, &pool.token_b).0, pool.token_b, authority),
+            TestAccount::executable(Token::id()),
+        ]
+    }
+
+    #[test]
+    fn swap_accounts_pass_in_both_directions() {
+        let (pool_key, pool) = test_pool();
+        let user = Pubkey::new_unique();
+        try_accounts::<ExecuteSwap>(&execute_swap_accounts(pool_key, &pool, user)).unwrap();
+
+        let mut accounts = execute_swap_accounts(pool_key, &pool, user);
+        accounts.swap(USER_TOKEN_IN, USER_TOKEN_OUT);
+        accounts.swap(POOL_TOKEN_IN, POOL_TOKEN_OUT);
+        try_accounts::<ExecuteSwap>(&accounts).unwrap();
+    }
+
+    #[test]
+    fn substituted_swap_accounts_are_rejected() {
+        let (pool_key, pool) = test_pool();
+        let user = Pubkey::new_unique();
+        let (other_pool_key, _) = test_pool();
+        let stranger = Pubkey::new_unique();
+
+        let rejected = |substitute: &dyn Fn(&mut Vec<TestAccount>)| {
+            let mut accounts = execute_swap_accounts(pool_key, &pool, user);
+            substitute(&mut accounts);
+            try_accounts::<ExecuteSwap>(&accounts).unwrap_err()
+        };
+
+        // Commitment opened on another pool or by someone else
+        assert_eq!(
+            rejected(&|accounts| {
+                accounts[SWAP_COMMITMENT] =
+                    TestAccount::program(Pubkey::new_unique(), &commitment(other_pool_key, user))
+            }),
+            ErrorCode::InvalidSwapCommitment.into()
+        );
+        assert_eq!(
+            rejected(&|accounts| {
+                accounts[SWAP_COMMITMENT] =
+                    TestAccount::program(Pubkey::new_unique(), &commitment(pool_key, stranger))
+            }),
+            ErrorCode::NotSwapOwner.into()
+        );
+
+        // Token accounts owned by someone else
+        for index in [USER_TOKEN_IN, USER_TOKEN_OUT] {
+            assert_eq!(
+                rejected(&|accounts| {
+                    let mint = accounts[index].data[..32].try_into().unwrap();
+                    accounts[index] = TestAccount::token(Pubkey::new_unique(), Pubkey::new_from_array(mint), stranger)
+                }),
+                anchor_lang::error::ErrorCode::ConstraintTokenOwner.into()
+            );
+        }
+
+        // Vaults swapped, foreign, or of another pool
+        assert_eq!(
+            rejected(&|accounts| accounts.swap(POOL_TOKEN_IN, POOL_TOKEN_OUT)),
+            anchor_lang::error::ErrorCode::ConstraintSeeds.into()
+        );
+        assert_eq!(
+            rejected(&|accounts| accounts[POOL_TOKEN_OUT].key = Pubkey::new_unique()),
+            anchor_lang::error::ErrorCode::ConstraintSeeds.into()
+        );
+        assert_eq!(
+            rejected(&|accounts| {
+                accounts[POOL_TOKEN_IN].key = find_vault_address(&other_pool_key, &pool.token_a).0
+            }),
+            anchor_lang::error::ErrorCode::ConstraintSeeds.into()
+        );
+    }
+
+    #[test]
+    fn commitments_take_their_pool_window_and_bond() {
+        let (pool_key, mut pool) = test_pool();
+        let user = Pubkey::new_unique();
+        let swap = SwapCommitment::open(user, pool_key, &pool, [1; 32], 100).unwrap();
+        assert_eq!(swap.reveal_after, 100 + pool.min_reveal_delay);
+        assert_eq!(swap.expires_at, 100 + pool.max_reveal_delay);
+        assert_eq!(swap.bond, pool.commit_bond);
+
+        pool.initialized = false;
+        assert_eq!(
+            SwapCommitment::open(user, pool_key, &pool, [1; 32], 100).err(),
+            Some(ErrorCode::PoolNotInitialized.into())
+        );
+    }
+
+    // Field positions in `RemoveLiquidity`
+    const LP_MINT: usize = 2;
+    const USER_LP_TOKEN: usize = 3;
+    const USER: usize = 4;
+    const USER_TOKEN_A: usize = 5;
+    const USER_TOKEN_B: usize = 6;
+    const POOL_TOKEN_A: usize = 7;
+    const POOL_TOKEN_B: usize = 8;
+
+    /// `RemoveLiquidity` accounts for a withdrawal by `user`
+    fn remove_liquidity_accounts(pool_key: Pubkey, pool: &Pool, user: Pubkey) -> Vec<TestAccount> {
+        let authority = pool_authority(&pool_key);
+        vec![
+            TestAccount::program(pool_key, pool),
+            TestAccount::new(authority, system_program::ID, Vec::new()),
+            TestAccount::mint(pool.lp_mint, authority),
+            TestAccount::token(Pubkey::new_unique(), pool.lp_mint, user),
+            TestAccount::signer(user),
+            TestAccount::token(Pubkey::new_unique(), pool.token_a, user),
+            TestAccount::token(Pubkey::new_unique(), pool.token_b, user),
+            TestAccount::token(find_vault_address(&pool_key, &pool.token_a).0, pool.token_a, authority),
+            TestAccount::token(find_vault_address(&pool_key, &pool.token_b).0, pool.token_b, authority),
+            TestAccount::executable(Token::id()),
+        ]
+    }
+
+    #[test]
+    fn substituted_liquidity_accounts_are_rejected() {
+        let (pool_key, pool) = test_pool();
+        let user = Pubkey::new_unique();
+        let (other_pool_key, other_pool) = test_pool();
+        let stranger = Pubkey::new_unique();
+
+        try_accounts::<RemoveLiquidity>(&remove_liquidity_accounts(pool_key, &pool, user)).unwrap();
+
+        let rejected = |substitute: &dyn Fn(&mut Vec<TestAccount>)| {
+            let mut accounts = remove_liquidity_accounts(pool_key, &pool, user);
+            substitute(&mut accounts);
+            try_accounts::<RemoveLiquidity>(&accounts).unwrap_err()
+        };
+        let token_mint = anchor_lang::error::ErrorCode::ConstraintTokenMint;
+        let token_owner = anchor_lang::error::ErrorCode::ConstraintTokenOwner;
+
+        // LP mint of another pool, or an LP account for another mint
+        assert_eq!(
+            rejected(&|accounts| {
+                accounts[LP_MINT] = TestAccount::mint(other_pool.lp_mint, pool_authority(&other_pool_key))
+            }),
+            ErrorCode::InvalidLpMint.into()
+        );
+        assert_eq!(
+            rejected(&|accounts| {
+                accounts[USER_LP_TOKEN] = TestAccount::token(Pubkey::new_unique(), other_pool.lp_mint, user)
+            }),
+            token_mint.into()
+        );
+        assert_eq!(
+            rejected(&|accounts| {
+                accounts[USER_LP_TOKEN] = TestAccount::token(Pubkey::new_unique(), pool.lp_mint, stranger)
+            }),
+            token_owner.into()
+        );
+
+        // Token accounts for the wrong mint or owned by someone else
+        assert_eq!(
+            rejected(&|accounts| {
+                accounts[USER_TOKEN_A] = TestAccount::token(Pubkey::new_unique(), pool.token_b, user)
+            }),
+            token_mint.into()
+        );
+        assert_eq!(
+            rejected(&|accounts| {
+                accounts[USER_TOKEN_B] = TestAccount::token(Pubkey::new_unique(), other_pool.token_b, user)
+            }),
+            token_mint.into()
+        );
+        assert_eq!(
+            rejected(&|accounts| {
+                accounts[USER_TOKEN_A] = TestAccount::token(Pubkey::new_unique(), pool.token_a, stranger)
+            }),
+            token_owner.into()
+        );
+
+        // Vaults swapped, foreign, or of another pool
+        assert_eq!(
+            rejected(&|accounts| accounts.swap(POOL_TOKEN_A, POOL_TOKEN_B)),
+            anchor_lang::error::ErrorCode::ConstraintSeeds.into()
+        );
+        assert_eq!(
+            rejected(&|accounts| accounts[POOL_TOKEN_B].key = Pubkey::new_unique()),
+            anchor_lang::error::ErrorCode::ConstraintSeeds.into()
+        );
+        assert_eq!(
+            rejected(&|accounts| {
+                accounts[POOL_TOKEN_A].key = find_vault_address(&other_pool_key, &pool.token_a).0
+            }),
+            anchor_lang::error::ErrorCode::ConstraintSeeds.into()
+        );
+    }
+
+    /// `CloseSwapCommitment` accounts for `user` cancelling their own commitment
+    fn close_commitment_accounts(config: &SwapConfig, user: Pubkey) -> Vec<TestAccount> {
+        vec![
+            TestAccount::program(Pubkey::find_program_address(&[b"config"], &ID).0, config),
+            TestAccount::program(Pubkey::new_unique(), &commitment(Pubkey::new_unique(), user)),
+            TestAccount::signer(user),
+            TestAccount::new(config.treasury, system_program::ID, Vec::new()),
+        ]
+    }
+
+    #[test]
+    fn only_the_committer_closes_a_commitment() {
+        let config = test_config();
+        let user = Pubkey::new_unique();
+        try_accounts::<CloseSwapCommitment>(&close_commitment_accounts(&config, user)).unwrap();
+
+        let mut accounts = close_commitment_accounts(&config, user);
+        accounts[2] = TestAccount::signer(Pubkey::new_unique());
+        assert_eq!(
+            try_accounts::<CloseSwapCommitment>(&accounts).unwrap_err(),
+            ErrorCode::NotSwapOwner.into()
+        );
+
+        let mut accounts = close_commitment_accounts(&config, user);
+        accounts[3].key = Pubkey::new_unique();
+        assert_eq!(
+            try_accounts::<CloseSwapCommitment>(&accounts).unwrap_err(),
+            ErrorCode::InvalidTreasury.into()
+        );
+    }
+
+    #[test]
+    fn only_expired_commitments_are_cranked() {
+        let swap = committed_swap(&[7; 32]);
+        assert_eq!(
+            swap.check_expired(&swap.user, 300).unwrap_err(),
+            ErrorCode::CommitmentNotExpired.into()
+        );
+        swap.check_expired(&swap.user, 301).unwrap();
+
+        // Rent must go back to the committer
+        assert_eq!(
+            swap.check_expired(&Pubkey::new_unique(), 301).unwrap_err(),
+            ErrorCode::InvalidCrankAccounts.into()
+        );
+    }
+
+    #[test]
+    fn forfeited_bonds_are_split_unless_paused() {
+        let mut config = test_config();
+        assert_eq!(config.forfeit(1_000, config.keeper_tip_bps), (250, 750));
+        assert_eq!(config.forfeit(1_000, 0), (0, 1_000));
+        assert_eq!(config.forfeit(1_000, 10_000), (1_000, 0));
+
+        // Nobody could execute, so nobody loses their bond
+        config.paused = true;
+        assert_eq!(config.forfeit(1_000, config.keeper_tip_bps), (0, 0));
+    }
+
+    /// Commitment to a 1000-in, 990-min-out A-to-B swap, revealable in [60, 300]
+    fn committed_swap(salt: &[u8; 32]) -> SwapCommitment {
+        let mut swap = commitment(Pubkey::new_unique(), Pubkey::new_unique());
+        swap.swap_hash = compute_swap_hash(1_000, 990, SwapDirection::AToB, salt, &swap.user);
+        swap.reveal_after = 60;
+        swap.expires_at = 300;
+        swap
+    }
+
+    #[test]
+    fn reveal_window_is_bounded() {
+        validate_reveal_window(0, 1).unwrap();
+        validate_reveal_window(DEFAULT_MIN_REVEAL_DELAY, DEFAULT_MAX_REVEAL_DELAY).unwrap();
+        validate_reveal_window(MAX_REVEAL_DELAY - 1, MAX_REVEAL_DELAY).unwrap();
+        for (min_reveal_delay, max_reveal_delay) in
+            [(-1, 60), (60, 60), (61, 60), (0, MAX_REVEAL_DELAY + 1)]
+        {
+            assert_eq!(
+                validate_reveal_window(min_reveal_delay, max_reveal_delay).unwrap_err(),
+                ErrorCode::InvalidRevealWindow.into()
+            );
+        }
+    }
+
+    #[test]
+    fn reveal_opens_the_commitment_in_its_window() {
+        let salt = [7; 32];
+        for now in [60, 300] {
+            let mut swap = committed_swap(&salt);
+            swap.reveal(1_000, 990, SwapDirection::AToB, &salt, now).unwrap();
+            assert!(swap.revealed);
+        }
+    }
+
+    #[test]
+    fn reveal_rejects_a_different_trade() {
+        let salt = [7; 32];
+        let mut swap = committed_swap(&salt);
+        for (amount_in, min_amount_out, direction, salt) in [
+            (1_001, 990, SwapDirection::AToB, salt),
+            (1_000, 989, SwapDirection::AToB, salt),
+            (1_000, 990, SwapDirection::BToA, salt),
+            (1_000, 990, SwapDirection::AToB, [8; 32]),
+        ] {
+            assert_eq!(
+                swap.reveal(amount_in, min_amount_out, direction, &salt, 100).unwrap_err(),
+                ErrorCode::CommitmentMismatch.into()
+            );
+        }
+        assert!(!swap.revealed);
+
+        // The hash binds the user, so a copied commitment cannot be replayed
+        swap.user = Pubkey::new_unique();
+        assert_eq!(
+            swap.reveal(1_000, 990, SwapDirection::AToB, &salt, 100).unwrap_err(),
+            ErrorCode::CommitmentMismatch.into()
+        );
+    }
+
+    #[test]
+    fn reveal_outside_the_window_fails() {
+        let salt = [7; 32];
+        let mut swap = committed_swap(&salt);
+        assert_eq!(
+            swap.reveal(1_000, 990, SwapDirection::AToB, &salt, 59).unwrap_err(),
+            ErrorCode::RevealTooEarly.into()
+        );
+        assert_eq!(
+            swap.reveal(1_000, 990, SwapDirection::AToB, &salt, 301).unwrap_err(),
+            ErrorCode::SwapExpired.into()
+        );
+
+        swap.executed = true;
+        assert_eq!(
+            swap.reveal(1_000, 990, SwapDirection::AToB, &salt, 100).unwrap_err(),
+            ErrorCode::AlreadyExecuted.into()
+        );
+    }
+
+    /// Constant-product output written out independently of the program
+    fn reference_output(amount_in: u64, reserve_in: u64, reserve_out: u64, fee_bps: u16) -> u64 {
+        let amount_in = amount_in as u128 * (10000 - fee_bps as u128);
+        let out = amount_in * reserve_out as u128 / (reserve_in as u128 * 10000 + amount_in);
+        out as u64
+    }
+
+    #[test]
+    fn output_matches_the_reference_amm() {
+        // Uniswap v2 getAmountOut: 1000 in at 1:1 depth 1e6 with 0.3% fee
+        assert_eq!(calculate_output_amount(1_000, 1_000_000, 1_000_000, 30).unwrap(), 996);
+
+        for &(amount_in, reserve_in, reserve_out) in &[
+            (1, 1, 1),
+            (1, 1_000_000, 1_000_000),
+            (500, 1_000, 4_000),
+            (123_456_789, 9_876_543_210, 1_234_567),
+            (u32::MAX as u64, u32::MAX as u64, u32::MAX as u64),
+        ] {
+            for fee_bps in FEE_TIERS.iter().copied().chain([0, 9999]) {
+                let out = calculate_output_amount(amount_in, reserve_in, reserve_out, fee_bps).unwrap();
+                assert_eq!(out, reference_output(amount_in, reserve_in, reserve_out, fee_bps));
+                assert!(out < reserve_out);
+                // The invariant never decreases
+                assert!(
+                    (reserve_in as u128 + amount_in as u128) * (reserve_out - out) as u128
+                        >= reserve_in as u128 * reserve_out as u128
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn output_grows_with_input_and_shrinks_with_fee() {
+        let mut last = 0;
+        for amount_in in (1..=100_000u64).step_by(997) {
+            let out = calculate_output_amount(amount_in, 1_000_000, 2_000_000, 30).unwrap();
+            assert!(out >= last);
+            last = out;
+        }
+        let low_fee = calculate_output_amount(10_000, 1_000_000, 1_000_000, 5).unwrap();
+        let high_fee = calculate_output_amount(10_000, 1_000_000, 1_000_000, 100).unwrap();
+        assert!(low_fee > high_fee);
+    }
+
+    #[test]
+    fn output_rejects_bad_inputs() {
+        assert_eq!(
+            calculate_output_amount(0, 1_000, 1_000, 30).unwrap_err(),
+            ErrorCode::InvalidAmount.into()
+        );
+        assert_eq!(
+            calculate_output_amount(1, 0, 1_000, 30).unwrap_err(),
+            ErrorCode::InsufficientLiquidity.into()
+        );
+        assert_eq!(
+            calculate_output_amount(1, 1_000, 0, 30).unwrap_err(),
+            ErrorCode::InsufficientLiquidity.into()
+        );
+        assert_eq!(
+            calculate_output_amount(1, 1_000, 1_000, 10000).unwrap_err(),
+            ErrorCode::InvalidFee.into()
+        );
+        assert_eq!(
+            calculate_output_amount(u64::MAX, u64::MAX, u64::MAX, 0).unwrap_err(),
+            ErrorCode::ArithmeticOverflow.into()
+        );
+    }
+
+    #[test]
+    fn withdrawal_commitment_follows_the_paid_amount() {
+        let reserve = add_commitments(&[7; 32], &public_amount_commitment(1_000));
+        assert_eq!(subtract_commitments(&reserve, &public_amount_commitment(1_000)), [7; 32]);
+        assert_ne!(subtract_commitments(&reserve, &public_amount_commitment(999)), [7; 32]);
+        assert_eq!(public_amount_commitment(0), [0; 32]);
+    }
+
+    #[test]
+    fn withdrawal_pays_the_share_within_slippage_and_deadline() {
+        let (_, mut pool) = test_pool();
+        pool.reserve_b = 4_000_000;
+        let now = 1_000;
+        let withdraw = |pool: &Pool, liquidity, min_a, min_b, deadline| {
+            pool.withdrawal_amounts(liquidity, 500_000, min_a, min_b, deadline, now)
+        };
+
+        // A quarter of the supply takes a quarter of each reserve, rounded down
+        assert_eq!(withdraw(&pool, 250_000, 0, 0, now).unwrap(), (250_000, 1_000_000));
+        assert_eq!(withdraw(&pool, 3, 0, 0, now).unwrap(), (3, 12));
+        pool.reserve_a = 999_999;
+        assert_eq!(withdraw(&pool, 1, 0, 0, now).unwrap(), (0, 4));
+        pool.reserve_a = 1_000_000;
+
+        // Minimums are inclusive on either side
+        assert_eq!(withdraw(&pool, 250_000, 250_000, 1_000_000, now).unwrap(), (250_000, 1_000_000));
+        assert_eq!(
+            withdraw(&pool, 250_000, 250_001, 0, now).unwrap_err(),
+            ErrorCode::SlippageExceeded.into()
+        );
+        assert_eq!(
+            withdraw(&pool, 250_000, 0, 1_000_001, now).unwrap_err(),
+            ErrorCode::SlippageExceeded.into()
+        );
+
+        // The deadline is the last second a withdrawal lands
+        assert_eq!(
+            withdraw(&pool, 250_000, 0, 0, now - 1).unwrap_err(),
+            ErrorCode::DeadlineExceeded.into()
+        );
+    }
+
+    #[test]
+    fn withdrawal_is_limited_to_the_providers_lp_balance() {
+        let (_, pool) = test_pool();
+        let withdraw = |liquidity, lp_balance| pool.withdrawal_amounts(liquidity, lp_balance, 0, 0, 0, 0);
+
+        assert_eq!(withdraw(500_000, 500_000).unwrap(), (500_000, 500_000));
+        for (liquidity, lp_balance) in [(0, 500_000), (500_001, 500_000), (pool.total_supply + 1, u64::MAX)] {
+            assert_eq!(
+                withdraw(liquidity, lp_balance).unwrap_err(),
+                ErrorCode::InsufficientLiquidity.into()
+            );
+        }
+    }
+
+    #[test]
+    fn only_the_lp_owner_removes_liquidity() {
+        let (pool_key, pool) = test_pool();
+        let (owner, signer) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // The signer's payout accounts are fine but the LP tokens are not theirs
+        let mut accounts = remove_liquidity_accounts(pool_key, &pool, signer);
+        accounts[USER_LP_TOKEN] = TestAccount::token(Pubkey::new_unique(), pool.lp_mint, owner);
+        assert_eq!(
+            try_accounts::<RemoveLiquidity>(&accounts).unwrap_err(),
+            anchor_lang::error::ErrorCode::ConstraintTokenOwner.into()
+        );
+
+        // The owner must also sign for the withdrawal
+        let mut accounts = remove_liquidity_accounts(pool_key, &pool, owner);
+        accounts[USER] = TestAccount::new(owner, system_program::ID, Vec::new());
+        assert_eq!(
+            try_accounts::<RemoveLiquidity>(&accounts).unwrap_err(),
+            anchor_lang::error::ErrorCode::AccountNotSigner.into()
+        );
+    }
+
+    #[test]
+    fn add_then_remove_restores_the_reserve_commitments() {
+        let (_, mut pool) = test_pool();
+        pool.reserve_a_commitment = [7; 32];
+        pool.reserve_b_commitment = [9; 32];
+        let reserves =
+            |pool: &Pool| (pool.reserve_a, pool.reserve_b, pool.reserve_a_commitment, pool.reserve_b_commitment);
+        let before = reserves(&pool);
+
+        pool.deposit(250_000, 40_000).unwrap();
+        assert_ne!(reserves(&pool), before);
+        pool.withdraw(250_000, 40_000).unwrap();
+        assert_eq!(reserves(&pool), before);
+
+        assert_eq!(
+            pool.withdraw(pool.reserve_a + 1, 0).unwrap_err(),
+            ErrorCode::ArithmeticOverflow.into()
+        );
+    }
+
+    #[test]
+    fn swap_moves_reserves_and_commitments_in_its_direction() {
+        let (_, mut start) = test_pool();
+        start.reserve_a_commitment = [7; 32];
+        start.reserve_b_commitment = [9; 32];
+        let reserves =
+            |pool: &Pool| (pool.reserve_a, pool.reserve_b, pool.reserve_a_commitment, pool.reserve_b_commitment);
+
+        let mut pool = start.clone();
+        pool.swap(SwapDirection::AToB, 1_000, 150).unwrap();
+        let mut expected = start.clone();
+        expected.deposit(1_000, 0).unwrap();
+        expected.withdraw(0, 150).unwrap();
+        assert_eq!(reserves(&pool), reserves(&expected));
+
+        let mut pool = start.clone();
+        pool.swap(SwapDirection::BToA, 150, 1_000).unwrap();
+        let mut expected = start.clone();
+        expected.deposit(0, 150).unwrap();
+        expected.withdraw(1_000, 0).unwrap();
+        assert_eq!(reserves(&pool), reserves(&expected));
+
+        // A failed trade leaves the pool untouched
+        let mut pool = start.clone();
+        for direction in [SwapDirection::AToB, SwapDirection::BToA] {
+            assert_eq!(
+                pool.swap(direction, 1, u64::MAX).unwrap_err(),
+                ErrorCode::ArithmeticOverflow.into()
+            );
+            assert_eq!(
+                pool.swap(direction, u64::MAX, 0).unwrap_err(),
+                ErrorCode::ArithmeticOverflow.into()
+            );
+        }
+        assert_eq!(reserves(&pool), reserves(&start));
+    }
+
+    fn order(direction: SwapDirection, amount_in: u64, min_amount_out: u64) -> BatchOrderInput {
+        BatchOrderInput { key: Pubkey::new_unique(), direction, amount_in, min_amount_out }
+    }
+
+    #[test]
+    fn excess_is_the_imbalance_outside_the_fee_band() {
+        assert_eq!(net_excess(100, 100, 1_000, 1_000, 0).unwrap(), None);
+        assert_eq!(net_excess(0, 0, 1_000, 1_000, 30).unwrap(), None);
+        // Up to the fee apart, neither side is in excess
+        assert_eq!(net_excess(1_000, 1_002, 1_000_000, 1_000_000, 30).unwrap(), None);
+        assert_eq!(net_excess(1_002, 1_000, 1_000_000, 1_000_000, 30).unwrap(), None);
+
+        assert_eq!(
+            net_excess(300, 100, 1_000, 1_000, 0).unwrap(),
+            Some((SwapDirection::AToB, 181))
+        );
+        assert_eq!(
+            net_excess(100, 300, 1_000, 1_000, 0).unwrap(),
+            Some((SwapDirection::BToA, 181))
+        );
+    }
+
+    #[test]
+    fn opposing_orders_cross_inside_the_fee_band() {
+        let orders = [
+            order(SwapDirection::AToB, 1_000, 1_000),
+            order(SwapDirection::BToA, 1_000, 1_000),
+        ];
+        let clearing = clear_batch(&orders, 1_000_000, 1_000_000, 30).unwrap();
+        assert_eq!(clearing.filled, vec![true, true]);
+        assert_eq!(clearing.amounts_out, vec![1_000, 1_000]);
+        assert!(clearing.net_trade.is_none());
+        assert_eq!(clearing.clearing_price, PRICE_SCALE);
+    }
+
+    #[test]
+    fn excess_side_trades_against_the_pool() {
+        // A-side excess: 181 A of the 300 go through the pool for 153 B
+        let orders = [
+            order(SwapDirection::AToB, 300, 0),
+            order(SwapDirection::BToA, 100, 0),
+        ];
+        let clearing = clear_batch(&orders, 1_000, 1_000, 0).unwrap();
+        assert_eq!(clearing.filled, vec![true, true]);
+        assert_eq!(clearing.net_trade, Some((SwapDirection::AToB, 181, 153)));
+        assert_eq!(clearing.amounts_out, vec![100 + 153, 300 - 181]);
+        assert_eq!(clearing.clearing_price, 253 * PRICE_SCALE / 300);
+
+        // B-side excess mirrors it
+        let orders = [
+            order(SwapDirection::AToB, 100, 0),
+            order(SwapDirection::BToA, 300, 0),
+        ];
+        let clearing = clear_batch(&orders, 1_000, 1_000, 0).unwrap();
+        assert_eq!(clearing.net_trade, Some((SwapDirection::BToA, 181, 153)));
+        assert_eq!(clearing.amounts_out, vec![300 - 181, 100 + 153]);
+        assert_eq!(clearing.clearing_price, 119 * PRICE_SCALE / 100);
+    }
+
+    #[test]
+    fn orders_share_the_output_pro_rata() {
+        let orders = [
+            order(SwapDirection::AToB, 100, 0),
+            order(SwapDirection::AToB, 300, 0),
+        ];
+        let clearing = clear_batch(&orders, 1_000_000, 1_000_000, 30).unwrap();
+        let total_out = calculate_output_amount(400, 1_000_000, 1_000_000, 30).unwrap();
+        assert_eq!(clearing.net_trade, Some((SwapDirection::AToB, 400, total_out)));
+        assert_eq!(clearing.amounts_out, vec![total_out / 4, total_out * 3 / 4]);
+    }
+
+    #[test]
+    fn unmet_limits_are_refunded() {
+        let orders = [
+            order(SwapDirection::AToB, 1_000, 0),
+            order(SwapDirection::AToB, 1_000, 5_000),
+        ];
+        let clearing = clear_batch(&orders, 1_000_000, 1_000_000, 30).unwrap();
+        assert_eq!(clearing.filled, vec![true, false]);
+        assert_eq!(
+            clearing.amounts_out,
+            vec![calculate_output_amount(1_000, 1_000_000, 1_000_000, 30).unwrap(), 0]
+        );
+
+        // Nothing fills when no order's limit can be met
+        let orders = [order(SwapDirection::BToA, 1_000, 5_000)];
+        let clearing = clear_batch(&orders, 1_000_000, 1_000_000, 30).unwrap();
+        assert_eq!(clearing.filled, vec![false]);
+        assert!(clearing.net_trade.is_none());
+        assert_eq!(clearing.clearing_price, 0);
+    }
+
+    #[test]
+    fn only_the_tightest_limit_is_dropped_per_pass() {
+        // Together both get 83; without the tighter order the other gets 90
+        let orders = [
+            order(SwapDirection::AToB, 100, 95),
+            order(SwapDirection::AToB, 100, 85),
+        ];
+        let clearing = clear_batch(&orders, 1_000, 1_000, 0).unwrap();
+        assert_eq!(clearing.filled, vec![false, true]);
+        assert_eq!(clearing.amounts_out, vec![0, 90]);
+        assert_eq!(clearing.net_trade, Some((SwapDirection::AToB, 100, 90)));
+    }
+
+    #[test]
+    fn equal_shortfalls_drop_the_same_order_in_any_order() {
+        // Either order alone fills at 90, together they get 83 each
+        let orders = [
+            order(SwapDirection::AToB, 100, 85),
+            order(SwapDirection::AToB, 100, 85),
+        ];
+        let dropped = orders.iter().max_by_key(|order| order.key).unwrap().key;
+        for orders in [orders, [orders[1], orders[0]]] {
+            let clearing = clear_batch(&orders, 1_000, 1_000, 0).unwrap();
+            for (order, filled) in orders.iter().zip(clearing.filled) {
+                assert_eq!(filled, order.key != dropped);
+            }
+        }
+    }
+
+    fn batch_order(direction: SwapDirection) -> (Pool, BatchAuction, BatchOrder) {
+        let (pool_key, pool) = test_pool();
+        let batch = BatchAuction {
+            pool: pool_key,
+            batch_id: 1,
+            creator: Pubkey::new_unique(),
+            commit_end: 60,
+            reveal_end: 90,
+            settle_end: 90 + BATCH_SETTLE_WINDOW,
+            order_count: 1,
+            revealed_count: 1,
+            closed_orders: 0,
+            total_in_a: 0,
+            total_in_b: 0,
+            settled: false,
+            clearing_price: 0,
+        };
+        let order = BatchOrder {
+            batch: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            swap_hash: [0; 32],
+            revealed: true,
+            direction,
+            amount_in: 1_000,
+            min_amount_out: 900,
+            amount_out: 0,
+            bond: DEFAULT_COMMIT_BOND,
+            filled: false,
+            settled: false,
+        };
+        (pool, batch, order)
+    }
+
+    #[test]
+    fn settled_orders_pay_their_output_or_refund() {
+        let (pool, mut batch, mut order) = batch_order(SwapDirection::AToB);
+        batch.settled = true;
+        order.settled = true;
+        order.filled = true;
+        order.amount_out = 950;
+        assert_eq!(order.payout(&pool, &batch, 100).unwrap(), (pool.token_b, 950));
+
+        order.filled = false;
+        order.amount_out = 0;
+        assert_eq!(order.payout(&pool, &batch, 100).unwrap(), (pool.token_a, 1_000));
+
+        order.direction = SwapDirection::BToA;
+        assert_eq!(order.payout(&pool, &batch, 100).unwrap(), (pool.token_b, 1_000));
+    }
+
+    #[test]
+    fn substituted_claim_accounts_are_rejected() {
+        let (pool, mut batch, mut order) = batch_order(SwapDirection::AToB);
+        batch.settled = true;
+        order.settled = true;
+        order.filled = true;
+        order.amount_out = 950;
+        let pool_key = batch.pool;
+        let batch_key = order.batch;
+        let claim = |batch: &BatchAuction,
+                     order: &BatchOrder,
+                     substitute: &dyn Fn(&mut ClaimAccounts)| {
+            let mut accounts = ClaimAccounts {
+                pool: pool_key,
+                batch_key,
+                batch,
+                order,
+                user: order.user,
+                mint: pool.token_b,
+                vault: find_vault_address(&pool_key, &pool.token_b).0,
+            };
+            substitute(&mut accounts);
+            check_claim_accounts(&pool, &accounts, 100)
+        };
+        assert_eq!(claim(&batch, &order, &|_| {}).unwrap(), (pool.token_b, 950));
+
+        // Batch of another pool, or an order of another batch
+        let mut foreign_batch = batch.clone();
+        foreign_batch.pool = Pubkey::new_unique();
+        assert_eq!(
+            claim(&foreign_batch, &order, &|_| {}).unwrap_err(),
+            ErrorCode::InvalidBatchAccounts.into()
+        );
+        assert_eq!(
+            claim(&batch, &order, &|accounts| accounts.batch_key = Pubkey::new_unique())
+                .unwrap_err(),
+            ErrorCode::InvalidBatchAccounts.into()
+        );
+
+        // Paid to someone other than the order's owner
+        assert_eq!(
+            claim(&batch, &order, &|accounts| accounts.user = Pubkey::new_unique()).unwrap_err(),
+            ErrorCode::NotSwapOwner.into()
+        );
+
+        // The refund mint for a filled order, or its vault
+        assert_eq!(
+            claim(&batch, &order, &|accounts| accounts.mint = pool.token_a).unwrap_err(),
+            ErrorCode::InvalidSwapMints.into()
+        );
+        assert_eq!(
+            claim(&batch, &order, &|accounts| {
+                accounts.vault = find_vault_address(&pool_key, &pool.token_a).0
+            })
+            .unwrap_err(),
+            ErrorCode::InvalidVault.into()
+        );
+        assert_eq!(
+            claim(&batch, &order, &|accounts| {
+                accounts.vault = find_vault_address(&Pubkey::new_unique(), &pool.token_b).0
+            })
+            .unwrap_err(),
+            ErrorCode::InvalidVault.into()
+        );
+    }
+
+    #[test]
+    fn unsettled_batches_refund_after_the_settle_window() {
+        let (pool, batch, order) = batch_order(SwapDirection::BToA);
+        assert_eq!(
+            order.payout(&pool, &batch, batch.settle_end - 1).unwrap_err(),
+            ErrorCode::BatchNotSettled.into()
+        );
+        assert_eq!(
+            order.payout(&pool, &batch, batch.settle_end).unwrap(),
+            (pool.token_b, 1_000)
+        );
+
+        let (pool, batch, mut order) = batch_order(SwapDirection::AToB);
+        order.revealed = false;
+        assert_eq!(
+            order.payout(&pool, &batch, batch.settle_end).unwrap_err(),
+            ErrorCode::BatchOrderNotRevealed.into()
+        );
+    }
+
+    #[test]
+    fn batches_take_at_most_max_orders() {
+        let (_, mut batch, _) = batch_order(SwapDirection::AToB);
+        batch.order_count = 0;
+        for _ in 0..MAX_BATCH_ORDERS {
+            batch.open_order().unwrap();
+        }
+        assert_eq!(batch.open_order().unwrap_err(), ErrorCode::BatchFull.into());
+        assert_eq!(batch.order_count as usize, MAX_BATCH_ORDERS);
+    }
+
+    #[test]
+    fn batches_close_once_over_and_empty() {
+        let (_, mut batch, _) = batch_order(SwapDirection::AToB);
+        assert_eq!(
+            batch.check_closable(batch.settle_end).unwrap_err(),
+            ErrorCode::BatchOrdersOpen.into()
+        );
+
+        batch.close_order().unwrap();
+        assert_eq!(
+            batch.check_closable(batch.settle_end - 1).unwrap_err(),
+            ErrorCode::BatchNotSettled.into()
+        );
+        batch.check_closable(batch.settle_end).unwrap();
+
+        batch.settled = true;
+        batch.check_closable(batch.reveal_end).unwrap();
+    }
+
+    fn test_config() -> SwapConfig {
+        let authority = Pubkey::new_unique();
+        SwapConfig {
+            authority,
+            pending_authority: Pubkey::default(),
+            paused: false,
+            total_pools: 1,
+            min_reveal_delay: 5,
+            max_reveal_delay: 50,
+            commit_bond: 1,
+            treasury: Pubkey::new_unique(),
+            keeper_tip_bps: 2_500,
+            multisig: OwnerSet::new(
+                vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()],
+                2,
+            )
+            .unwrap(),
+            proposal_count: 0,
+        }
+    }
+
+    #[test]
+    fn only_the_pending_authority_accepts() {
+        let mut config = test_config();
+        let accounts = |config: &SwapConfig, signer| {
+            vec![
+                TestAccount::program(Pubkey::find_program_address(&[b"config"], &ID).0, config),
+                TestAccount::signer(signer),
+            ]
+        };
+        assert_eq!(
+            try_accounts::<AcceptAuthority>(&accounts(&config, config.authority)).unwrap_err(),
+            ErrorCode::NoPendingAuthority.into()
+        );
+
+        let pending = Pubkey::new_unique();
+        config.pending_authority = pending;
+        for signer in [config.authority, Pubkey::new_unique()] {
+            assert_eq!(
+                try_accounts::<AcceptAuthority>(&accounts(&config, signer)).unwrap_err(),
+                ErrorCode::NotPendingAuthority.into()
+            );
+        }
+        try_accounts::<AcceptAuthority>(&accounts(&config, pending)).unwrap();
+    }
+
+    fn proposal(config: &SwapConfig, action: SwapAdminAction) -> SwapProposal {
+        SwapProposal {
+            id: 0,
+            proposer: config.multisig.owners[0],
+            action,
+            approvals: config.multisig.propose(&config.multisig.owners[0]).unwrap(),
+            executed: false,
+            cancel_approvals: Approvals::none(&config.multisig),
+            cancelled: false,
+        }
+    }
+
+    #[test]
+    fn admin_actions_need_threshold_owner_approvals() {
+        let config = test_config();
+        let mut proposal = proposal(&config, SwapAdminAction::Unpause);
+        assert_eq!(
+            proposal.execute(&config).unwrap_err(),
+            multisig::MultisigError::NotEnoughApprovals.into()
+        );
+        assert_eq!(
+            proposal.approve(&config, &config.authority).unwrap_err(),
+            multisig::MultisigError::NotOwner.into()
+        );
+
+        assert_eq!(proposal.approve(&config, &config.multisig.owners[1]).unwrap(), 2);
+        proposal.execute(&config).unwrap();
+        assert!(proposal.executed);
+
+        assert_eq!(proposal.execute(&config).unwrap_err(), ErrorCode::AlreadyExecuted.into());
+        assert_eq!(
+            proposal.approve(&config, &config.multisig.owners[2]).unwrap_err(),
+            ErrorCode::AlreadyExecuted.into()
+        );
+    }
+
+    #[test]
+    fn authority_transfers_execute_their_own_proposals() {
+        let mut config = test_config();
+        let new_authority = Pubkey::new_unique();
+        let propose = SwapAdminAction::ProposeAuthority { new_authority };
+
+        let mut proposal = proposal(&config, propose.clone());
+        assert_eq!(
+            proposal.execute_matching(&config, &propose).unwrap_err(),
+            multisig::MultisigError::NotEnoughApprovals.into()
+        );
+        proposal.approve(&config, &config.multisig.owners[1]).unwrap();
+        assert_eq!(
+            proposal.execute_matching(&config, &SwapAdminAction::CancelAuthorityTransfer).unwrap_err(),
+            ErrorCode::ProposalActionMismatch.into()
+        );
+        proposal.execute_matching(&config, &propose).unwrap();
+
+        assert_eq!(
+            config.propose_authority(Pubkey::default()).unwrap_err(),
+            ErrorCode::InvalidAuthority.into()
+        );
+        config.propose_authority(new_authority).unwrap();
+        assert_eq!(config.pending_authority, new_authority);
+
+        assert_eq!(config.cancel_authority_transfer().unwrap(), new_authority);
+        assert_eq!(config.pending_authority, Pubkey::default());
+        assert_eq!(
+            config.cancel_authority_transfer().unwrap_err(),
+            ErrorCode::NoPendingAuthority.into()
+        );
+    }
+
+    #[test]
+    fn admin_actions_cancel_at_threshold_owner_votes() {
+        let config = test_config();
+        let owners = config.multisig.owners.clone();
+        let mut proposal = proposal(&config, SwapAdminAction::Unpause);
+
+        assert_eq!(proposal.approve_cancel(&config, &owners[0]).unwrap(), 1);
+        assert!(!proposal.cancelled);
+        assert_eq!(
+            proposal.approve_cancel(&config, &config.authority).unwrap_err(),
+            multisig::MultisigError::NotOwner.into()
+        );
+        assert_eq!(proposal.approve_cancel(&config, &owners[2]).unwrap(), 2);
+        assert!(proposal.cancelled);
+
+        // Approvals already past the threshold no longer execute it
+        proposal.approvals.approve(&config.multisig, &owners[1]).unwrap();
+        for result in [
+            proposal.execute(&config),
+            proposal.approve(&config, &owners[1]).map(drop),
+            proposal.approve_cancel(&config, &owners[1]).map(drop),
+        ] {
+            assert_eq!(result.unwrap_err(), ErrorCode::ProposalCancelled.into());
+        }
+
+        // Executed actions can no longer be cancelled
+        let mut executed = self::proposal(&config, SwapAdminAction::Unpause);
+        executed.approve(&config, &owners[1]).unwrap();
+        executed.execute(&config).unwrap();
+        assert_eq!(
+            executed.approve_cancel(&config, &owners[1]).unwrap_err(),
+            ErrorCode::AlreadyExecuted.into()
+        );
+    }
+
+    #[test]
+    fn settled_proposals_are_closed_to_their_proposer() {
+        let config = test_config();
+        let config_account = || {
+            TestAccount::program(Pubkey::find_program_address(&[b"config"], &ID).0, &config)
+        };
+        let proposal = proposal(&config, SwapAdminAction::CancelAuthorityTransfer);
+        let proposal_account = || {
+            TestAccount::program(
+                Pubkey::find_program_address(&[b"proposal", &proposal.id.to_le_bytes()], &ID).0,
+                &proposal,
+            )
+        };
+        let owner = config.multisig.owners[1];
+
+        let cancel = |proposer| {
+            try_accounts::<CancelAdminAction>(&[
+                config_account(),
+                proposal_account(),
+                TestAccount::new(proposer, system_program::ID, Vec::new()),
+                TestAccount::signer(owner),
+            ])
+        };
+        let change_authority = |proposer| {
+            try_accounts::<ChangeAuthority>(&[
+                config_account(),
+                proposal_account(),
+                TestAccount::new(proposer, system_program::ID, Vec::new()),
+                TestAccount::signer(owner),
+            ])
+        };
+
+        cancel(proposal.proposer).unwrap();
+        change_authority(proposal.proposer).unwrap();
+        for result in [cancel(owner), change_authority(owner)] {
+            assert_eq!(result.unwrap_err(), ErrorCode::MissingProposalAccount.into());
+        }
+    }
+
+    #[test]
+    fn owner_change_makes_pending_admin_actions_stale() {
+        let mut config = test_config();
+        let mut proposal = proposal(&config, SwapAdminAction::SetKeeperTip { keeper_tip_bps: 0 });
+        proposal.approve(&config, &config.multisig.owners[1]).unwrap();
+
+        let owners = config.multisig.owners.clone();
+        config.multisig.replace(owners, 2).unwrap();
+        assert_eq!(
+            proposal.execute(&config).unwrap_err(),
+            multisig::MultisigError::StaleProposal.into()
+        );
+        assert!(!proposal.executed);
+    }
+
+    #[test]
+    fn integer_sqrt_is_the_floor_root() {
+        let values = [
+            0u128,
+            1,
+            2,
+            3,
+            4,
+            15,
+            16,
+            17,
+            1_000_000,
+            999_999_999_999,
+            (u64::MAX as u128) * (u64::MAX as u128),
+            u128::MAX,
+        ];
+        for value in values.into_iter().chain((0..64).map(|shift| 3u128 << shift)) {
+            let root = integer_sqrt(value) as u128;
+            assert!(root * root <= value, "{value}");
+            let next = root + 1;
+            assert!(!matches!(next.checked_mul(next), Some(square) if square <= value), "{value}");
+        }
+        assert_eq!(integer_sqrt(u128::MAX), u64::MAX);
+    }
+}
@@ -0,0 +1,224 @@
+/*!
+ * Multisig - shared owner set for the Solana programs
+ *
+ * A program embeds an `OwnerSet` in its config and an `Approvals` in each
+ * of its admin proposals. Owners approve proposals, and a proposal can
+ * execute once `threshold` owners have approved it under the current
+ * owner set.
+ *
+ * Features:
+ * - Up to `MAX_OWNERS` unique owners with a reachable threshold
+ * - Proposer approves implicitly
+ * - Replacing the owner set makes pending proposals stale
+ */
+
+use anchor_lang::prelude::*;
+
+/// Most owners an owner set can hold
+pub const MAX_OWNERS: usize = 10;
+
+/// Owners of a program config and the approvals they need to act
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OwnerSet {
+    pub owners: Vec<Pubkey>,
+    /// Owner approvals needed to execute a proposal
+    pub threshold: u8,
+    /// Bumped whenever the owners change
+    pub seqno: u32,
+}
+
+impl OwnerSet {
+    pub const LEN: usize = (4 + 32 * MAX_OWNERS) + 1 + 4;
+
+    pub fn new(owners: Vec<Pubkey>, threshold: u8) -> Result<Self> {
+        validate_owners(&owners, threshold)?;
+        Ok(Self { owners, threshold, seqno: 0 })
+    }
+
+    pub fn index_of(&self, key: &Pubkey) -> Option<usize> {
+        self.owners.iter().position(|owner| owner == key)
+    }
+
+    /// Replace the owners; proposals approved under the old set go stale
+    pub fn replace(&mut self, owners: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        validate_owners(&owners, threshold)?;
+        self.owners = owners;
+        self.threshold = threshold;
+        self.seqno = self.seqno
+            .checked_add(1)
+            .ok_or(MultisigError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Approvals for a new proposal, with the proposer's already counted
+    pub fn propose(&self, proposer: &Pubkey) -> Result<Approvals> {
+        let index = self.index_of(proposer).ok_or(MultisigError::NotOwner)?;
+        let mut approvals = Approvals::none(self);
+        approvals.by_owner[index] = true;
+        Ok(approvals)
+    }
+}
+
+/// Owner approvals of one proposal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Approvals {
+    /// Approval per owner, indexed like `OwnerSet::owners`
+    pub by_owner: Vec<bool>,
+    /// `OwnerSet::seqno` when the proposal was created
+    pub seqno: u32,
+}
+
+impl Approvals {
+    pub const LEN: usize = (4 + MAX_OWNERS) + 4;
+
+    /// No approvals yet, under the owner set's current seqno
+    pub fn none(owner_set: &OwnerSet) -> Self {
+        Self { by_owner: vec![false; owner_set.owners.len()], seqno: owner_set.seqno }
+    }
+
+    pub fn count(&self) -> usize {
+        self.by_owner.iter().filter(|approved| **approved).count()
+    }
+
+    /// Approvals only count under the owner set they were given for
+    pub fn check_current(&self, owner_set: &OwnerSet) -> Result<()> {
+        require!(self.seqno == owner_set.seqno, MultisigError::StaleProposal);
+        Ok(())
+    }
+
+    /// Record an owner's approval and return the approval count
+    pub fn approve(&mut self, owner_set: &OwnerSet, owner: &Pubkey) -> Result<u8> {
+        self.check_current(owner_set)?;
+        let index = owner_set.index_of(owner).ok_or(MultisigError::NotOwner)?;
+        self.by_owner[index] = true;
+        Ok(self.count() as u8)
+    }
+
+    /// Enough current approvals to execute
+    pub fn check_threshold(&self, owner_set: &OwnerSet) -> Result<()> {
+        self.check_current(owner_set)?;
+        require!(
+            self.count() >= owner_set.threshold as usize,
+            MultisigError::NotEnoughApprovals
+        );
+        Ok(())
+    }
+}
+
+/// Owners must be unique and the threshold reachable
+pub fn validate_owners(owners: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !owners.is_empty() && owners.len() <= MAX_OWNERS,
+        MultisigError::InvalidOwners
+    );
+    for (i, owner) in owners.iter().enumerate() {
+        require!(!owners[..i].contains(owner), MultisigError::InvalidOwners);
+    }
+    require!(
+        threshold > 0 && threshold as usize <= owners.len(),
+        MultisigError::InvalidThreshold
+    );
+    Ok(())
+}
+
+// Offset past the programs' own error codes
+#[error_code(offset = 7000)]
+pub enum MultisigError {
+    #[msg("Owners must be unique and at most MAX_OWNERS")]
+    InvalidOwners,
+
+    #[msg("Threshold must be between one and the number of owners")]
+    InvalidThreshold,
+
+    #[msg("Signer is not an owner")]
+    NotOwner,
+
+    #[msg("Owner set changed since the proposal was created")]
+    StaleProposal,
+
+    #[msg("Not enough owner approvals")]
+    NotEnoughApprovals,
+
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owner_set(threshold: u8) -> OwnerSet {
+        let owners = (0..3).map(|_| Pubkey::new_unique()).collect();
+        OwnerSet::new(owners, threshold).unwrap()
+    }
+
+    #[test]
+    fn owner_set_is_validated() {
+        let owner = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        validate_owners(&[owner, other], 2).unwrap();
+
+        assert_eq!(validate_owners(&[], 1).unwrap_err(), MultisigError::InvalidOwners.into());
+        assert_eq!(
+            validate_owners(&[owner, owner], 1).unwrap_err(),
+            MultisigError::InvalidOwners.into()
+        );
+        let too_many: Vec<_> = (0..=MAX_OWNERS).map(|_| Pubkey::new_unique()).collect();
+        assert_eq!(validate_owners(&too_many, 1).unwrap_err(), MultisigError::InvalidOwners.into());
+        assert_eq!(
+            validate_owners(&[owner, other], 0).unwrap_err(),
+            MultisigError::InvalidThreshold.into()
+        );
+        assert_eq!(
+            validate_owners(&[owner, other], 3).unwrap_err(),
+            MultisigError::InvalidThreshold.into()
+        );
+    }
+
+    #[test]
+    fn proposals_need_threshold_owner_approvals() {
+        let owner_set = owner_set(2);
+        let outsider = Pubkey::new_unique();
+        assert_eq!(owner_set.propose(&outsider).unwrap_err(), MultisigError::NotOwner.into());
+
+        let mut approvals = owner_set.propose(&owner_set.owners[0]).unwrap();
+        assert_eq!(approvals.count(), 1);
+        assert_eq!(
+            approvals.check_threshold(&owner_set).unwrap_err(),
+            MultisigError::NotEnoughApprovals.into()
+        );
+
+        // Approving twice counts once
+        assert_eq!(approvals.approve(&owner_set, &owner_set.owners[0]).unwrap(), 1);
+        assert_eq!(
+            approvals.approve(&owner_set, &outsider).unwrap_err(),
+            MultisigError::NotOwner.into()
+        );
+        assert_eq!(approvals.approve(&owner_set, &owner_set.owners[2]).unwrap(), 2);
+        approvals.check_threshold(&owner_set).unwrap();
+    }
+
+    #[test]
+    fn replacing_owners_makes_approvals_stale() {
+        let mut owner_set = owner_set(1);
+        let mut approvals = owner_set.propose(&owner_set.owners[0]).unwrap();
+        approvals.check_threshold(&owner_set).unwrap();
+
+        let owners = owner_set.owners.clone();
+        owner_set.replace(owners, 1).unwrap();
+        assert_eq!(owner_set.seqno, 1);
+        assert_eq!(
+            approvals.check_threshold(&owner_set).unwrap_err(),
+            MultisigError::StaleProposal.into()
+        );
+        assert_eq!(
+            approvals.approve(&owner_set, &owner_set.owners[1]).unwrap_err(),
+            MultisigError::StaleProposal.into()
+        );
+        assert_eq!(
+            owner_set.replace(vec![], 1).unwrap_err(),
+            MultisigError::InvalidOwners.into()
+        );
+        assert_eq!(owner_set.seqno, 1);
+    }
+}
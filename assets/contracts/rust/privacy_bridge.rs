@@ -1,555 +0,0 @@
-/*!
- * Privacy Bridge - Rust Smart Contract
- * 
- * Cross-chain privacy-preserving bridge for Solana/Substrate
- * 
- * Features:
- * - Zero-knowledge proofs (zk-SNARKs)
- * - Pedersen commitments
- * - Cross-chain asset transfers
- * - Relayer network
- * - Slashing mechanism for malicious relayers
- */
-
-use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use solana_program::keccak;
-
-declare_id!("Bridge11111111111111111111111111111111111111");
-
-#[program]
-pub mod privacy_bridge {
-    use super::*;
-
-    /// Initialize bridge program
-    pub fn initialize(
-        ctx: Context<Initialize>,
-        min_confirmations: u8,
-        bridge_fee: u16,
-    ) -> Result<()> {
-        let bridge = &mut ctx.accounts.bridge;
-        bridge.authority = ctx.accounts.authority.key();
-        bridge.min_confirmations = min_confirmations;
-        bridge.bridge_fee = bridge_fee;
-        bridge.total_locked = 0;
-        bridge.total_unlocked = 0;
-        bridge.paused = false;
-        Ok(())
-    }
-
-    /// Lock assets for cross-chain transfer
-    pub fn lock_assets(
-        ctx: Context<LockAssets>,
-        amount: u64,
-        target_chain: u64,
-        recipient_commitment: [u8; 32],
-    ) -> Result<()> {
-        let bridge = &mut ctx.accounts.bridge;
-        require!(!bridge.paused, ErrorCode::BridgePaused);
-
-        // Calculate fee
-        let fee = (amount as u128 * bridge.bridge_fee as u128 / 10000) as u64;
-        let net_amount = amount.checked_sub(fee).ok_or(ErrorCode::ArithmeticOverflow)?;
-
-        // Transfer tokens from user
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.user_token_account.to_account_info(),
-            to: ctx.accounts.bridge_token_account.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
-
-        // Generate commitment
-        let commitment = generate_commitment(&recipient_commitment, net_amount)?;
-
-        // Create bridge transaction
-        let tx = &mut ctx.accounts.bridge_tx;
-        tx.id = generate_tx_id(
-            &ctx.accounts.user.key(),
-            target_chain,
-            &recipient_commitment,
-            Clock::get()?.unix_timestamp,
-        );
-        tx.source_chain = 1; // Solana
-        tx.target_chain = target_chain;
-        tx.sender = ctx.accounts.user.key();
-        tx.recipient_commitment = recipient_commitment;
-        tx.amount = net_amount;
-        tx.commitment = commitment;
-        tx.timestamp = Clock::get()?.unix_timestamp;
-        tx.state = TransactionState::Locked;
-        tx.confirmations = 0;
-        tx.nullifier = [0; 32];
-
-        bridge.total_locked = bridge.total_locked
-            .checked_add(net_amount)
-            .ok_or(ErrorCode::ArithmeticOverflow)?;
-
-        emit!(AssetLocked {
-            tx_id: tx.id,
-            sender: ctx.accounts.user.key(),
-            source_chain: 1,
-            target_chain,
-            amount: net_amount,
-            commitment,
-        });
-
-        Ok(())
-    }
-
-    /// Unlock assets with zk-SNARK proof
-    pub fn unlock_assets(
-        ctx: Context<UnlockAssets>,
-        proof: ZkProof,
-        nullifier: [u8; 32],
-    ) -> Result<()> {
-        let bridge = &mut ctx.accounts.bridge;
-        let tx = &mut ctx.accounts.bridge_tx;
-
-        require!(!bridge.paused, ErrorCode::BridgePaused);
-        require!(tx.state == TransactionState::Locked, ErrorCode::InvalidState);
-        require!(tx.confirmations >= bridge.min_confirmations, ErrorCode::InsufficientConfirmations);
-
-        // Check nullifier hasn't been used
-        let nullifier_account = &ctx.accounts.nullifier_account;
-        require!(!nullifier_account.used, ErrorCode::NullifierUsed);
-
-        // Verify zk-SNARK proof
-        require!(
-            verify_proof(&proof, &tx.commitment, &nullifier, tx.amount)?,
-            ErrorCode::InvalidProof
-        );
-
-        // Mark nullifier as used
-        let nullifier_acc = &mut ctx.accounts.nullifier_account;
-        nullifier_acc.nullifier = nullifier;
-        nullifier_acc.used = true;
-        nullifier_acc.timestamp = Clock::get()?.unix_timestamp;
-
-        // Update transaction
-        tx.nullifier = nullifier;
-        tx.state = TransactionState::Completed;
-
-        // Transfer tokens to recipient
-        let authority_bump = ctx.bumps.bridge_authority;
-        let authority_seeds = &[
-            b"bridge_authority",
-            &[authority_bump],
-        ];
-        let signer = &[&authority_seeds[..]];
-
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.bridge_token_account.to_account_info(),
-            to: ctx.accounts.recipient_token_account.to_account_info(),
-            authority: ctx.accounts.bridge_authority.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, tx.amount)?;
-
-        bridge.total_unlocked = bridge.total_unlocked
-            .checked_add(tx.amount)
-            .ok_or(ErrorCode::ArithmeticOverflow)?;
-
-        emit!(AssetUnlocked {
-            tx_id: tx.id,
-            recipient_commitment: tx.recipient_commitment,
-            amount: tx.amount,
-            nullifier,
-        });
-
-        Ok(())
-    }
-
-    /// Relay transaction (called by relayers)
-    pub fn relay_transaction(ctx: Context<RelayTransaction>) -> Result<()> {
-        let relayer_account = &ctx.accounts.relayer;
-        require!(relayer_account.active, ErrorCode::NotActiveRelayer);
-
-        let tx = &mut ctx.accounts.bridge_tx;
-        require!(tx.state == TransactionState::Locked, ErrorCode::InvalidState);
-
-        tx.confirmations = tx.confirmations
-            .checked_add(1)
-            .ok_or(ErrorCode::ArithmeticOverflow)?;
-
-        let bridge = &ctx.accounts.bridge;
-        if tx.confirmations >= bridge.min_confirmations {
-            tx.state = TransactionState::Relayed;
-        }
-
-        emit!(TransactionRelayed {
-            tx_id: tx.id,
-            relayer: ctx.accounts.relayer_authority.key(),
-            confirmations: tx.confirmations,
-        });
-
-        Ok(())
-    }
-
-    /// Add relayer
-    pub fn add_relayer(ctx: Context<AddRelayer>) -> Result<()> {
-        let relayer = &mut ctx.accounts.relayer;
-        relayer.authority = ctx.accounts.relayer_authority.key();
-        relayer.active = true;
-        relayer.total_relayed = 0;
-        relayer.slashed = false;
-
-        emit!(RelayerAdded {
-            relayer: ctx.accounts.relayer_authority.key(),
-        });
-
-        Ok(())
-    }
-
-    /// Update bridge fee
-    pub fn update_fee(ctx: Context<UpdateBridge>, new_fee: u16) -> Result<()> {
-        require!(new_fee <= 1000, ErrorCode::FeeTooHigh); // Max 10%
-        let bridge = &mut ctx.accounts.bridge;
-        bridge.bridge_fee = new_fee;
-        Ok(())
-    }
-
-    /// Pause bridge
-    pub fn pause(ctx: Context<UpdateBridge>) -> Result<()> {
-        let bridge = &mut ctx.accounts.bridge;
-        bridge.paused = true;
-        Ok(())
-    }
-
-    /// Unpause bridge
-    pub fn unpause(ctx: Context<UpdateBridge>) -> Result<()> {
-        let bridge = &mut ctx.accounts.bridge;
-        bridge.paused = false;
-        Ok(())
-    }
-}
-
-// ========== ACCOUNTS ==========
-
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Bridge::LEN,
-        seeds = [b"bridge"],
-        bump
-    )]
-    pub bridge: Account<'info, Bridge>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct LockAssets<'info> {
-    #[account(
-        mut,
-        seeds = [b"bridge"],
-        bump
-    )]
-    pub bridge: Account<'info, Bridge>,
-
-    #[account(
-        init,
-        payer = user,
-        space = 8 + BridgeTransaction::LEN,
-    )]
-    pub bridge_tx: Account<'info, BridgeTransaction>,
-
-    #[account(mut)]
-    pub user: Signer<'info>,
-
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub bridge_token_account: Account<'info, TokenAccount>,
-
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct UnlockAssets<'info> {
-    #[account(
-        mut,
-        seeds = [b"bridge"],
-        bump
-    )]
-    pub bridge: Account<'info, Bridge>,
-
-    /// CHECK: PDA authority for bridge
-    #[account(
-        seeds = [b"bridge_authority"],
-        bump
-    )]
-    pub bridge_authority: UncheckedAccount<'info>,
-
-    #[account(mut)]
-    pub bridge_tx: Account<'info, BridgeTransaction>,
-
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + NullifierAccount::LEN,
-    )]
-    pub nullifier_account: Account<'info, NullifierAccount>,
-
-    #[account(mut)]
-    pub bridge_token_account: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub recipient_token_account: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub payer: Signer<'info>,
-
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct RelayTransaction<'info> {
-    #[account(seeds = [b"bridge"], bump)]
-    pub bridge: Account<'info, Bridge>,
-
-    #[account(mut)]
-    pub bridge_tx: Account<'info, BridgeTransaction>,
-
-    pub relayer: Account<'info, Relayer>,
-
-    pub relayer_authority: Signer<'info>,
-}
-
-#[derive(Accounts)]
-pub struct AddRelayer<'info> {
-    #[account(seeds = [b"bridge"], bump)]
-    pub bridge: Account<'info, Bridge>,
-
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Relayer::LEN,
-    )]
-    pub relayer: Account<'info, Relayer>,
-
-    pub relayer_authority: Signer<'info>,
-
-    #[account(
-        mut,
-        constraint = authority.key() == bridge.authority
-    )]
-    pub authority: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct UpdateBridge<'info> {
-    #[account(
-        mut,
-        seeds = [b"bridge"],
-        bump,
-        constraint = bridge.authority == authority.key()
-    )]
-    pub bridge: Account<'info, Bridge>,
-
-    pub authority: Signer<'info>,
-}
-
-// ========== STATE ==========
-
-#[account]
-pub struct Bridge {
-    pub authority: Pubkey,
-    pub min_confirmations: u8,
-    pub bridge_fee: u16,
-    pub total_locked: u64,
-    pub total_unlocked: u64,
-    pub paused: bool,
-}
-
-impl Bridge {
-    pub const LEN: usize = 32 + 1 + 2 + 8 + 8 + 1;
-}
-
-#[account]
-pub struct BridgeTransaction {
-    pub id: [u8; 32],
-    pub source_chain: u64,
-    pub target_chain: u64,
-    pub sender: Pubkey,
-    pub recipient_commitment: [u8; 32],
-    pub amount: u64,
-    pub commitment: [u8; 32],
-    pub nullifier: [u8; 32],
-    pub timestamp: i64,
-    pub state: TransactionState,
-    pub confirmations: u8,
-}
-
-impl BridgeTransaction {
-    pub const LEN: usize = 32 + 8 + 8 + 32 + 32 + 8 + 32 + 32 + 8 + 1 + 1;
-}
-
-#[account]
-pub struct NullifierAccount {
-    pub nullifier: [u8; 32],
-    pub used: bool,
-    pub timestamp: i64,
-}
-
-impl NullifierAccount {
-    pub const LEN: usize = 32 + 1 + 8;
-}
-
-#[account]
-pub struct Relayer {
-    pub authority: Pubkey,
-    pub active: bool,
-    pub total_relayed: u64,
-    pub slashed: bool,
-}
-
-impl Relayer {
-    pub const LEN: usize = 32 + 1 + 8 + 1;
-}
-
-// ========== ENUMS ==========
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
-pub enum TransactionState {
-    Pending,
-    Locked,
-    Relayed,
-    Completed,
-    Refunded,
-    Failed,
-}
-
-// ========== STRUCTS ==========
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct ZkProof {
-    pub a: [u8; 64],
-    pub b: [u8; 128],
-    pub c: [u8; 64],
-}
-
-// ========== EVENTS ==========
-
-#[event]
-pub struct AssetLocked {
-    pub tx_id: [u8; 32],
-    pub sender: Pubkey,
-    pub source_chain: u64,
-    pub target_chain: u64,
-    pub amount: u64,
-    pub commitment: [u8; 32],
-}
-
-#[event]
-pub struct AssetUnlocked {
-    pub tx_id: [u8; 32],
-    pub recipient_commitment: [u8; 32],
-    pub amount: u64,
-    pub nullifier: [u8; 32],
-}
-
-#[event]
-pub struct TransactionRelayed {
-    pub tx_id: [u8; 32],
-    pub relayer: Pubkey,
-    pub confirmations: u8,
-}
-
-#[event]
-pub struct RelayerAdded {
-    pub relayer: Pubkey,
-}
-
-// ========== ERRORS ==========
-
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Bridge is paused")]
-    BridgePaused,
-
-    #[msg("Invalid transaction state")]
-    InvalidState,
-
-    #[msg("Insufficient confirmations")]
-    InsufficientConfirmations,
-
-    #[msg("Nullifier already used")]
-    NullifierUsed,
-
-    #[msg("Invalid zk-SNARK proof")]
-    InvalidProof,
-
-    #[msg("Not an active relayer")]
-    NotActiveRelayer,
-
-    #[msg("Arithmetic overflow")]
-    ArithmeticOverflow,
-
-    #[msg("Fee too high (max 10%)")]
-    FeeTooHigh,
-}
-
-// ========== HELPER FUNCTIONS ==========
-
-/// Generate Pedersen commitment
-fn generate_commitment(recipient: &[u8; 32], amount: u64) -> Result<[u8; 32]> {
-    let mut data = Vec::new();
-    data.extend_from_slice(recipient);
-    data.extend_from_slice(&amount.to_le_bytes());
-    
-    let hash = keccak::hash(&data);
-    Ok(hash.to_bytes())
-}
-
-/// Generate transaction ID
-fn generate_tx_id(
-    sender: &Pubkey,
-    target_chain: u64,
-    recipient: &[u8; 32],
-    timestamp: i64,
-) -> [u8; 32] {
-    let mut data = Vec::new();
-    data.extend_from_slice(sender.as_ref());
-    data.extend_from_slice(&target_chain.to_le_bytes());
-    data.extend_from_slice(recipient);
-    data.extend_from_slice(&timestamp.to_le_bytes());
-    
-    let hash = keccak::hash(&data);
-    hash.to_bytes()
-}
-
-/// Verify zk-SNARK proof
-/// In production, integrate with arkworks or bellman
-fn verify_proof(
-    proof: &ZkProof,
-    commitment: &[u8; 32],
-    nullifier: &[u8; 32],
-    amount: u64,
-) -> Result<bool> {
-    // Mock verification - in production, use proper zk-SNARK verification
-    // This would integrate with Groth16 verifier
-    
-    let mut public_inputs = Vec::new();
-    public_inputs.extend_from_slice(commitment);
-    public_inputs.extend_from_slice(nullifier);
-    public_inputs.extend_from_slice(&amount.to_le_bytes());
-    
-    // Verify proof format is valid
-    let valid = proof.a.len() == 64 && proof.b.len() == 128 && proof.c.len() == 64;
-    
-    Ok(valid)
-}
@@ -0,0 +1,5363 @@
+/*!
+ * Privacy Bridge - Rust Smart Contract
+ * 
+ * Cross-chain privacy-preserving bridge for Solana/Substrate
+ * 
+ * Features:
+ * - Zero-knowledge proofs (zk-SNARKs)
+ * - Pedersen commitments
+ * - Cross-chain asset transfers
+ * - Relayer network
+ * - Slashing mechanism for malicious relayers
+ * - Association-set proofs for compliant withdrawals
+ * - Stealth address recipients
+ * - Encrypted notes with auditor viewing key
+ * - Timelocked governance for parameter changes
+ * - M-of-N owner approvals on every governance proposal
+ * - Guardian emergency pause (bridge, chain or token level)
+ * - Emergency withdrawal bounded by outstanding liabilities
+ * - Permissionless proof-of-reserves audits
+ * - Versioned events for every state change
+ * - Batched locks and unlocks
+ * - Per-epoch Merkle roots of outbound transfers for destination chains
+ * - Source-block finality attestations with reorg reporting
+ */
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{get_associated_token_address, AssociatedToken};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::keccak;
+use multisig::{Approvals, OwnerSet, MAX_OWNERS};
+
+declare_id!("Bridge11111111111111111111111111111111111111");
+
+#[program]
+pub mod privacy_bridge {
+    use super::*;
+
+    /// Initialize bridge program
+    ///
+    /// `owners` approve governance proposals; `threshold` of them must sign
+    /// off before a proposal can execute, and only `governance_delay`
+    /// seconds after it was queued.
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        min_confirmations: u8,
+        bridge_fee: u16,
+        owners: Vec<Pubkey>,
+        threshold: u8,
+        governance_delay: i64,
+    ) -> Result<()> {
+        require!(min_confirmations > 0, ErrorCode::InvalidMinConfirmations);
+        validate_governance_delay(governance_delay)?;
+        let bridge = &mut ctx.accounts.bridge;
+        bridge.authority = ctx.accounts.authority.key();
+        bridge.pending_authority = Pubkey::default();
+        bridge.min_confirmations = min_confirmations;
+        bridge.bridge_fee = bridge_fee;
+        bridge.total_locked = 0;
+        bridge.total_unlocked = 0;
+        bridge.paused = false;
+        bridge.pause_flags = 0;
+        bridge.paused_at = 0;
+        bridge.emergency_delay = DEFAULT_EMERGENCY_DELAY;
+        bridge.auditor_viewing_key = [0; 32];
+        bridge.governance_delay = governance_delay;
+        bridge.proposal_count = 0;
+        bridge.multisig = OwnerSet::new(owners.clone(), threshold)?;
+        bridge.current_epoch = 0;
+        bridge.epoch_duration = DEFAULT_EPOCH_DURATION;
+        bridge.verifying_key_hash = [0; 32];
+
+        emit!(BridgeInitialized {
+            version: EVENT_SCHEMA_VERSION,
+            authority: bridge.authority,
+            min_confirmations,
+            bridge_fee,
+            owners,
+            threshold,
+            governance_delay,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Lock assets for cross-chain transfer
+    ///
+    /// `recipient_commitment` is `recipient_commitment(recipient, blinding)`;
+    /// unlocks must open it to pay out. When `stealth` is set it is instead
+    /// the one-time stealth address derived from the recipient's
+    /// meta-address, and the ephemeral public key is published so the
+    /// recipient can find the lock by scanning.
+    ///
+    /// `encrypted_note` carries the amount and blinding data encrypted to the
+    /// recipient and to the bridge auditor viewing key; it is only emitted.
+    /// Once an auditor viewing key is set, the note is mandatory.
+    pub fn lock_assets(
+        ctx: Context<LockAssets>,
+        amount: u64,
+        target_chain: u64,
+        recipient_commitment: [u8; 32],
+        stealth: Option<StealthMeta>,
+        encrypted_note: Option<EncryptedNote>,
+    ) -> Result<()> {
+        let chain_config = load_chain_config(&ctx.accounts.chain_config, target_chain)?;
+        let bridge = &mut ctx.accounts.bridge;
+        require_not_paused(bridge, &chain_config, &ctx.accounts.token_config, PAUSE_LOCK)?;
+        require!(chain_config.supported, ErrorCode::UnsupportedChain);
+        check_encrypted_note(bridge, encrypted_note.as_ref())?;
+
+        // Calculate fee
+        let fee = (amount as u128 * bridge.bridge_fee as u128 / 10000) as u64;
+        let net_amount = amount.checked_sub(fee).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        // Transfer tokens from user
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.user_token_account.to_account_info(),
+            to: ctx.accounts.bridge_token_account.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        // Generate commitment
+        let commitment = generate_commitment(&recipient_commitment, net_amount)?;
+
+        // Create bridge transaction
+        let tx = &mut ctx.accounts.bridge_tx;
+        tx.id = generate_tx_id(
+            &ctx.accounts.user.key(),
+            target_chain,
+            &recipient_commitment,
+            Clock::get()?.unix_timestamp,
+        );
+        tx.source_chain = 1; // Solana
+        tx.target_chain = target_chain;
+        tx.sender = ctx.accounts.user.key();
+        tx.mint = ctx.accounts.bridge_token_account.mint;
+        tx.recipient_commitment = recipient_commitment;
+        tx.amount = net_amount;
+        tx.commitment = commitment;
+        tx.timestamp = Clock::get()?.unix_timestamp;
+        tx.state = TransactionState::Locked;
+        tx.confirmations = 0;
+        tx.nullifier = [0; 32];
+        tx.stealth = stealth.clone();
+        tx.source_block = 0;
+        tx.source_block_hash = [0; 32];
+        tx.reverted_block_hash = [0; 32];
+        tx.finality_depth = 0;
+        tx.attestation_round = 0;
+        tx.revert_votes = 0;
+
+        // Queue the transfer in the current outbound epoch
+        let outbound_epoch = &mut ctx.accounts.outbound_epoch;
+        outbound_epoch.open_if_new(bridge.current_epoch, tx.timestamp);
+        tx.outbound_epoch = outbound_epoch.epoch_id;
+        let leaf = outbound_leaf(tx);
+        let leaf_index = outbound_epoch.append(leaf)?;
+        tx.leaf_index = leaf_index;
+
+        bridge.total_locked = bridge.total_locked
+            .checked_add(net_amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.total_locked = token_config.total_locked
+            .checked_add(net_amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(AssetLocked {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.user.key(),
+            tx_id: tx.id,
+            sender: ctx.accounts.user.key(),
+            mint: tx.mint,
+            recipient_commitment,
+            source_chain: 1,
+            target_chain,
+            amount: net_amount,
+            commitment,
+            stealth,
+            encrypted_note,
+            slot: Clock::get()?.slot,
+        });
+
+        emit!(OutboundMessageQueued {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.user.key(),
+            tx_id: tx.id,
+            epoch: outbound_epoch.epoch_id,
+            leaf_index,
+            leaf,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Unlock assets with zk-SNARK proof
+    pub fn unlock_assets(
+        mut ctx: Context<UnlockAssets>,
+        proof: ZkProof,
+        nullifier: [u8; 32],
+        recipient_blinding: [u8; 32],
+    ) -> Result<()> {
+        let accounts = &mut ctx.accounts;
+        let recipient = accounts.recipient.key();
+
+        let chain_config = load_chain_config(&accounts.chain_config, accounts.bridge_tx.target_chain)?;
+        require_not_paused(&accounts.bridge, &chain_config, &accounts.token_config, PAUSE_UNLOCK)?;
+        check_unlock(
+            &accounts.bridge,
+            &accounts.bridge_tx,
+            &accounts.nullifier_account,
+            &recipient,
+            &recipient_blinding,
+        )?;
+
+        // Verify zk-SNARK proof, bound to the recipient so a copied proof
+        // cannot be redirected to another account
+        let tx = &accounts.bridge_tx;
+        require!(
+            verify_proof(
+                &accounts.bridge.verifying_key_hash,
+                &proof,
+                &tx.commitment,
+                &nullifier,
+                tx.amount,
+                &recipient,
+            )?,
+            ErrorCode::InvalidProof
+        );
+
+        let vault = UnlockVault {
+            vault: accounts.bridge_token_account.to_account_info(),
+            bridge_authority: accounts.bridge_authority.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            authority_bump: ctx.bumps.bridge_authority,
+            actor: accounts.payer.key(),
+        };
+        let amount = accounts.bridge_tx.amount;
+        complete_unlock(
+            &mut accounts.bridge,
+            &mut accounts.token_config,
+            &mut accounts.bridge_tx,
+            &mut accounts.nullifier_account,
+            nullifier,
+            &vault,
+            &[(accounts.recipient_token_account.to_account_info(), amount)],
+        )
+    }
+
+    /// Unlock assets with a proof that the deposit also belongs to an
+    /// approved association set (Privacy Pools-style compliant withdrawal)
+    pub fn unlock_assets_with_association(
+        mut ctx: Context<UnlockAssetsWithAssociation>,
+        proof: ZkProof,
+        nullifier: [u8; 32],
+        recipient_blinding: [u8; 32],
+        association_root: [u8; 32],
+    ) -> Result<()> {
+        let accounts = &mut ctx.accounts;
+        let recipient = accounts.recipient.key();
+        let association_set = &accounts.association_set;
+
+        let chain_config = load_chain_config(&accounts.chain_config, accounts.bridge_tx.target_chain)?;
+        require_not_paused(&accounts.bridge, &chain_config, &accounts.token_config, PAUSE_UNLOCK)?;
+        check_unlock(
+            &accounts.bridge,
+            &accounts.bridge_tx,
+            &accounts.nullifier_account,
+            &recipient,
+            &recipient_blinding,
+        )?;
+        require!(association_set.active, ErrorCode::AssociationSetInactive);
+        require!(
+            association_set.is_known_root(&association_root),
+            ErrorCode::UnknownAssociationRoot
+        );
+
+        // Verify zk-SNARK proof over both the deposit and association roots
+        let tx = &accounts.bridge_tx;
+        require!(
+            verify_association_proof(
+                &accounts.bridge.verifying_key_hash,
+                &proof,
+                &tx.commitment,
+                &association_root,
+                &nullifier,
+                tx.amount,
+                &recipient,
+            )?,
+            ErrorCode::InvalidProof
+        );
+
+        let vault = UnlockVault {
+            vault: accounts.bridge_token_account.to_account_info(),
+            bridge_authority: accounts.bridge_authority.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            authority_bump: ctx.bumps.bridge_authority,
+            actor: accounts.payer.key(),
+        };
+        let amount = accounts.bridge_tx.amount;
+        complete_unlock(
+            &mut accounts.bridge,
+            &mut accounts.token_config,
+            &mut accounts.bridge_tx,
+            &mut accounts.nullifier_account,
+            nullifier,
+            &vault,
+            &[(accounts.recipient_token_account.to_account_info(), amount)],
+        )?;
+
+        emit!(AssociationProofVerified {
+            version: EVENT_SCHEMA_VERSION,
+            actor: accounts.payer.key(),
+            tx_id: accounts.bridge_tx.id,
+            association_set: accounts.association_set.key(),
+            association_root,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Unlock assets submitted by a relayer (gasless withdrawal)
+    ///
+    /// The proof binds the relayer and its fee, so the fee cannot be raised
+    /// and the submission cannot be hijacked by another relayer. The relayer
+    /// pays rent for the nullifier account and, if missing, the recipient's
+    /// token account, and is reimbursed from the fee.
+    pub fn relayed_unlock_assets(
+        mut ctx: Context<RelayedUnlockAssets>,
+        proof: ZkProof,
+        nullifier: [u8; 32],
+        recipient_blinding: [u8; 32],
+        relayer_fee: u64,
+    ) -> Result<()> {
+        let accounts = &mut ctx.accounts;
+        let recipient = accounts.recipient.key();
+
+        let chain_config = load_chain_config(&accounts.chain_config, accounts.bridge_tx.target_chain)?;
+        require_not_paused(&accounts.bridge, &chain_config, &accounts.token_config, PAUSE_UNLOCK)?;
+        check_unlock(
+            &accounts.bridge,
+            &accounts.bridge_tx,
+            &accounts.nullifier_account,
+            &recipient,
+            &recipient_blinding,
+        )?;
+        let recipient_amount = split_relayer_fee(accounts.bridge_tx.amount, relayer_fee)?;
+
+        // Verify zk-SNARK proof, including recipient, relayer and fee as
+        // public inputs
+        let tx = &accounts.bridge_tx;
+        let relayer_key = accounts.relayer.key();
+        require!(
+            verify_relayed_proof(
+                &accounts.bridge.verifying_key_hash,
+                &proof,
+                &ProofPublicInputs {
+                    commitment: tx.commitment,
+                    nullifier,
+                    amount: tx.amount,
+                    recipient,
+                },
+                &relayer_key,
+                relayer_fee,
+            )?,
+            ErrorCode::InvalidProof
+        );
+
+        // Pay the recipient and the relayer's fee
+        let vault = UnlockVault {
+            vault: accounts.bridge_token_account.to_account_info(),
+            bridge_authority: accounts.bridge_authority.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            authority_bump: ctx.bumps.bridge_authority,
+            actor: accounts.relayer.key(),
+        };
+        complete_unlock(
+            &mut accounts.bridge,
+            &mut accounts.token_config,
+            &mut accounts.bridge_tx,
+            &mut accounts.nullifier_account,
+            nullifier,
+            &vault,
+            &[
+                (accounts.recipient_token_account.to_account_info(), recipient_amount),
+                (accounts.relayer_token_account.to_account_info(), relayer_fee),
+            ],
+        )?;
+
+        emit!(RelayerFeePaid {
+            version: EVENT_SCHEMA_VERSION,
+            actor: relayer_key,
+            tx_id: accounts.bridge_tx.id,
+            relayer: relayer_key,
+            relayer_fee,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Lock several transfers from one sender in a single instruction
+    ///
+    /// All items share the target chain and token, so the deposit is one
+    /// transfer CPI. `remaining_accounts` holds one new `BridgeTransaction`
+    /// keypair account (signer, writable) per item. Each item may be a
+    /// stealth lock, as in `lock_assets`.
+    pub fn lock_assets_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LockAssetsBatch<'info>>,
+        target_chain: u64,
+        items: Vec<BatchLockItem>,
+    ) -> Result<()> {
+        check_batch_size(items.len())?;
+        require!(
+            ctx.remaining_accounts.len() == items.len(),
+            ErrorCode::InvalidBatchAccounts
+        );
+
+        let chain_config = load_chain_config(&ctx.accounts.chain_config, target_chain)?;
+        let bridge = &mut ctx.accounts.bridge;
+        require_not_paused(bridge, &chain_config, &ctx.accounts.token_config, PAUSE_LOCK)?;
+        require!(chain_config.supported, ErrorCode::UnsupportedChain);
+
+        let total_amount = items.iter().try_fold(0u64, |total, item| {
+            total.checked_add(item.amount).ok_or(ErrorCode::ArithmeticOverflow)
+        })?;
+
+        // Transfer the whole batch from the user at once
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.user_token_account.to_account_info(),
+            to: ctx.accounts.bridge_token_account.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, total_amount)?;
+
+        let sender = ctx.accounts.user.key();
+        let mint = ctx.accounts.bridge_token_account.mint;
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut total_net: u64 = 0;
+
+        let outbound_epoch = &mut ctx.accounts.outbound_epoch;
+        outbound_epoch.open_if_new(bridge.current_epoch, timestamp);
+
+        for (index, (item, tx_info)) in items.iter().zip(ctx.remaining_accounts.iter()).enumerate() {
+            require!(tx_info.is_signer && tx_info.is_writable, ErrorCode::InvalidBatchAccounts);
+            check_encrypted_note(bridge, item.encrypted_note.as_ref())?;
+
+            // Calculate fee per item, exactly as a single lock would
+            let fee = (item.amount as u128 * bridge.bridge_fee as u128 / 10000) as u64;
+            let net_amount = item.amount.checked_sub(fee).ok_or(ErrorCode::ArithmeticOverflow)?;
+            let commitment = generate_commitment(&item.recipient_commitment, net_amount)?;
+
+            let mut tx = BridgeTransaction {
+                id: generate_batch_tx_id(
+                    &generate_tx_id(&sender, target_chain, &item.recipient_commitment, timestamp),
+                    index as u8,
+                ),
+                source_chain: 1, // Solana
+                target_chain,
+                sender,
+                mint,
+                recipient_commitment: item.recipient_commitment,
+                amount: net_amount,
+                commitment,
+                nullifier: [0; 32],
+                timestamp,
+                state: TransactionState::Locked,
+                confirmations: 0,
+                stealth: item.stealth.clone(),
+                source_block: 0,
+                source_block_hash: [0; 32],
+                reverted_block_hash: [0; 32],
+                finality_depth: 0,
+                attestation_round: 0,
+                revert_votes: 0,
+                outbound_epoch: outbound_epoch.epoch_id,
+                leaf_index: 0,
+            };
+            let leaf = outbound_leaf(&tx);
+            let leaf_index = outbound_epoch.append(leaf)?;
+            tx.leaf_index = leaf_index;
+
+            create_program_account(
+                &ctx.accounts.user.to_account_info(),
+                tx_info,
+                &ctx.accounts.system_program.to_account_info(),
+                8 + BridgeTransaction::LEN,
+            )?;
+            write_program_account(tx_info, &tx)?;
+
+            total_net = total_net
+                .checked_add(net_amount)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+            emit!(AssetLocked {
+                version: EVENT_SCHEMA_VERSION,
+                actor: sender,
+                tx_id: tx.id,
+                sender,
+                mint,
+                recipient_commitment: item.recipient_commitment,
+                source_chain: 1,
+                target_chain,
+                amount: net_amount,
+                commitment,
+                stealth: item.stealth.clone(),
+                encrypted_note: item.encrypted_note.clone(),
+                slot: Clock::get()?.slot,
+            });
+
+            emit!(OutboundMessageQueued {
+                version: EVENT_SCHEMA_VERSION,
+                actor: sender,
+                tx_id: tx.id,
+                epoch: outbound_epoch.epoch_id,
+                leaf_index,
+                leaf,
+                slot: Clock::get()?.slot,
+            });
+        }
+
+        bridge.total_locked = bridge.total_locked
+            .checked_add(total_net)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.total_locked = token_config.total_locked
+            .checked_add(total_net)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        Ok(())
+    }
+
+    /// Unlock several transactions in one instruction
+    ///
+    /// All items share the chain and token. `remaining_accounts` holds, per
+    /// item: `bridge_tx` (writable), a new nullifier keypair account (signer,
+    /// writable), `recipient`, and the recipient's associated token account
+    /// (writable). Consecutive items paying the same token account share one
+    /// transfer CPI.
+    pub fn unlock_assets_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UnlockAssetsBatch<'info>>,
+        target_chain: u64,
+        items: Vec<BatchUnlockItem>,
+    ) -> Result<()> {
+        check_batch_size(items.len())?;
+        require!(
+            ctx.remaining_accounts.len() == items.len() * BATCH_UNLOCK_ACCOUNTS,
+            ErrorCode::InvalidBatchAccounts
+        );
+
+        let chain_config = load_chain_config(&ctx.accounts.chain_config, target_chain)?;
+        let bridge = &mut ctx.accounts.bridge;
+        require_not_paused(bridge, &chain_config, &ctx.accounts.token_config, PAUSE_UNLOCK)?;
+
+        let mint = ctx.accounts.token_config.mint;
+        let now = Clock::get()?.unix_timestamp;
+
+        // Load and check every transaction before verifying anything
+        let mut txs: Vec<Account<'info, BridgeTransaction>> = Vec::with_capacity(items.len());
+        for (item, accounts) in items.iter().zip(ctx.remaining_accounts.chunks(BATCH_UNLOCK_ACCOUNTS)) {
+            let tx: Account<'info, BridgeTransaction> = Account::try_from(&accounts[0])?;
+            let nullifier_info = &accounts[1];
+            let recipient = accounts[2].key();
+            let recipient_token_info = &accounts[3];
+
+            check_unlock_state(bridge.min_confirmations, &tx, &recipient, &item.recipient_blinding)?;
+            require!(tx.target_chain == target_chain, ErrorCode::InvalidBatchAccounts);
+            require!(tx.mint == mint, ErrorCode::InvalidVault);
+            require!(
+                accounts[0].is_writable && nullifier_info.is_signer && nullifier_info.is_writable,
+                ErrorCode::InvalidBatchAccounts
+            );
+            require_keys_eq!(
+                recipient_token_info.key(),
+                get_associated_token_address(&recipient, &mint),
+                ErrorCode::InvalidBatchAccounts
+            );
+            require!(
+                txs.iter().all(|other| other.key() != tx.key()),
+                ErrorCode::InvalidBatchAccounts
+            );
+
+            txs.push(tx);
+        }
+        check_unique_nullifiers(&items)?;
+
+        // Verify all proofs in one batched check before paying out any item
+        let public_inputs: Vec<ProofPublicInputs> = txs
+            .iter()
+            .zip(items.iter())
+            .zip(ctx.remaining_accounts.chunks(BATCH_UNLOCK_ACCOUNTS))
+            .map(|((tx, item), accounts)| ProofPublicInputs {
+                commitment: tx.commitment,
+                nullifier: item.nullifier,
+                amount: tx.amount,
+                recipient: accounts[2].key(),
+            })
+            .collect();
+        let proofs: Vec<&ZkProof> = items.iter().map(|item| &item.proof).collect();
+        require!(
+            verify_proof_batch(&bridge.verifying_key_hash, &proofs, &public_inputs)?,
+            ErrorCode::InvalidProof
+        );
+
+        let authority_bump = ctx.bumps.bridge_authority;
+        let authority_seeds = &[
+            b"bridge_authority".as_ref(),
+            &[authority_bump],
+        ];
+        let signer = &[&authority_seeds[..]];
+
+        let mut total_unlocked: u64 = 0;
+        let mut pending: Option<(usize, u64)> = None;
+
+        for (index, ((tx, item), accounts)) in txs
+            .iter_mut()
+            .zip(items.iter())
+            .zip(ctx.remaining_accounts.chunks(BATCH_UNLOCK_ACCOUNTS))
+            .enumerate()
+        {
+            // Mark nullifier as used
+            create_program_account(
+                &ctx.accounts.payer.to_account_info(),
+                &accounts[1],
+                &ctx.accounts.system_program.to_account_info(),
+                8 + NullifierAccount::LEN,
+            )?;
+            write_program_account(&accounts[1], &NullifierAccount {
+                nullifier: item.nullifier,
+                used: true,
+                timestamp: now,
+            })?;
+
+            // Update transaction
+            tx.nullifier = item.nullifier;
+            tx.state = TransactionState::Completed;
+            tx.exit(ctx.program_id)?;
+
+            total_unlocked = total_unlocked
+                .checked_add(tx.amount)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+            // Merge with the previous item if it pays the same token account
+            pending = match pending {
+                Some((previous, amount))
+                    if ctx.remaining_accounts[previous * BATCH_UNLOCK_ACCOUNTS + 3].key() == accounts[3].key() =>
+                {
+                    Some((previous, amount.checked_add(tx.amount).ok_or(ErrorCode::ArithmeticOverflow)?))
+                }
+                Some((previous, amount)) => {
+                    transfer_from_vault(
+                        &ctx.accounts.bridge_token_account.to_account_info(),
+                        &ctx.remaining_accounts[previous * BATCH_UNLOCK_ACCOUNTS + 3],
+                        &ctx.accounts.bridge_authority.to_account_info(),
+                        &ctx.accounts.token_program.to_account_info(),
+                        signer,
+                        amount,
+                    )?;
+                    Some((index, tx.amount))
+                }
+                None => Some((index, tx.amount)),
+            };
+
+            emit!(AssetUnlocked {
+                version: EVENT_SCHEMA_VERSION,
+                actor: ctx.accounts.payer.key(),
+                tx_id: tx.id,
+                recipient_commitment: tx.recipient_commitment,
+                amount: tx.amount,
+                nullifier: item.nullifier,
+                slot: Clock::get()?.slot,
+            });
+        }
+
+        if let Some((previous, amount)) = pending {
+            transfer_from_vault(
+                &ctx.accounts.bridge_token_account.to_account_info(),
+                &ctx.remaining_accounts[previous * BATCH_UNLOCK_ACCOUNTS + 3],
+                &ctx.accounts.bridge_authority.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                signer,
+                amount,
+            )?;
+        }
+
+        bridge.total_unlocked = bridge.total_unlocked
+            .checked_add(total_unlocked)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.total_unlocked = token_config.total_unlocked
+            .checked_add(total_unlocked)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        Ok(())
+    }
+
+    /// Close the current outbound epoch and publish its Merkle root
+    ///
+    /// Permissionless once `epoch_duration` has passed or the tree is full.
+    /// Relayers sign the published root for destination-chain contracts,
+    /// which then verify individual transfers with an inclusion proof
+    /// (see `bridge_client::bridge_merkle`). Locks after this call go to the next epoch.
+    /// An epoch with a reclaimed lock is closed with a zero root instead.
+    pub fn finalize_epoch(ctx: Context<FinalizeEpoch>) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge;
+        let outbound_epoch = &mut ctx.accounts.outbound_epoch;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(!outbound_epoch.finalized, ErrorCode::EpochFinalized);
+        require!(
+            outbound_epoch.is_full()
+                || now >= outbound_epoch.started_at.saturating_add(bridge.epoch_duration),
+            ErrorCode::EpochNotElapsed
+        );
+
+        outbound_epoch.finalize(now);
+
+        bridge.current_epoch = bridge.current_epoch
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(EpochRootPublished {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.caller.key(),
+            epoch: outbound_epoch.epoch_id,
+            root: outbound_epoch.root,
+            leaf_count: outbound_epoch.leaf_count,
+            started_at: outbound_epoch.started_at,
+            finalized_at: now,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Register an association-set provider
+    ///
+    /// Executes a queued `RegisterAssociationSet` proposal (permissionless).
+    pub fn register_association_set(ctx: Context<RegisterAssociationSet>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute_matching(
+            &ctx.accounts.bridge,
+            &GovernanceAction::RegisterAssociationSet { provider: ctx.accounts.provider.key() },
+            now,
+        )?;
+
+        let association_set = &mut ctx.accounts.association_set;
+        association_set.provider = ctx.accounts.provider.key();
+        association_set.root = [0; 32];
+        association_set.root_history = [[0; 32]; ASSOCIATION_ROOT_HISTORY];
+        association_set.root_index = 0;
+        association_set.updated_at = now;
+        association_set.active = true;
+
+        emit!(AssociationSetRegistered {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            association_set: association_set.key(),
+            provider: association_set.provider,
+            slot: Clock::get()?.slot,
+        });
+        emit!(ProposalExecuted {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            proposal_id: proposal.id,
+            action: proposal.action.clone(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Publish a new association root (set provider only)
+    pub fn update_association_root(
+        ctx: Context<UpdateAssociationRoot>,
+        new_root: [u8; 32],
+    ) -> Result<()> {
+        let association_set = &mut ctx.accounts.association_set;
+        require!(association_set.active, ErrorCode::AssociationSetInactive);
+
+        let old_root = association_set.root;
+        association_set.push_root(new_root);
+        association_set.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(AssociationRootUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.provider.key(),
+            association_set: association_set.key(),
+            old_root,
+            new_root,
+            timestamp: association_set.updated_at,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Relay transaction (called by relayers)
+    ///
+    /// The relayer attests to the source-chain block holding the lock,
+    /// `source_block` / `source_block_hash`, and to how many blocks have been
+    /// built on it, `finality_depth`, as observed on the source chain. The
+    /// first attestation of a round pins the block and later ones must agree;
+    /// a block reported as reorged out can never be attested again. An
+    /// attestation only counts toward `min_confirmations` once the depth
+    /// meets the source chain's `finality_depth`; relayers may re-attest as
+    /// the block gets deeper, but each relayer counts once per round.
+    pub fn relay_transaction(
+        ctx: Context<RelayTransaction>,
+        source_block: u64,
+        source_block_hash: [u8; 32],
+        finality_depth: u64,
+    ) -> Result<()> {
+        let relayer_account = &ctx.accounts.relayer;
+        require!(relayer_account.active, ErrorCode::NotActiveRelayer);
+        let chain_config = load_chain_config(
+            &ctx.accounts.chain_config,
+            ctx.accounts.bridge_tx.target_chain,
+        )?;
+        let source_chain_config = load_chain_config(
+            &ctx.accounts.source_chain_config,
+            ctx.accounts.bridge_tx.source_chain,
+        )?;
+        require_not_paused(
+            &ctx.accounts.bridge,
+            &chain_config,
+            &ctx.accounts.token_config,
+            PAUSE_RELAY,
+        )?;
+
+        let tx = &mut ctx.accounts.bridge_tx;
+        tx.attest_source_block(source_block, source_block_hash, finality_depth)?;
+        let clock = Clock::get()?;
+
+        // Attestations from before a reorg report start over
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.open_round(tx.key(), relayer_account.key(), tx.attestation_round);
+        attestation.finality_depth = finality_depth;
+        attestation.attested_at = clock.unix_timestamp;
+
+        let required_depth = source_chain_config.finality_depth;
+        let counted = !attestation.counted && finality_depth >= required_depth;
+
+        emit!(FinalityAttested {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.relayer_authority.key(),
+            tx_id: tx.id,
+            relayer: ctx.accounts.relayer_authority.key(),
+            source_block,
+            finality_depth,
+            required_depth,
+            round: tx.attestation_round,
+            slot: Clock::get()?.slot,
+        });
+
+        if !counted {
+            return Ok(());
+        }
+        attestation.counted = true;
+        tx.add_confirmation(ctx.accounts.bridge.min_confirmations)?;
+
+        emit!(TransactionRelayed {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.relayer_authority.key(),
+            tx_id: tx.id,
+            relayer: ctx.accounts.relayer_authority.key(),
+            confirmations: tx.confirmations,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Vote that a transaction's source block was reorged out
+    ///
+    /// Only possible before the transaction is final (still `Locked`) and
+    /// once a block has been attested; `observed_block_hash` is what the
+    /// relayer now sees at that height. Each relayer votes once per round;
+    /// once `min_confirmations` relayers agree, every attestation collected
+    /// so far is dropped, the reverted block hash is pinned so it cannot be
+    /// attested again, and relayers must attest the lock's new block from
+    /// scratch. A transaction nobody re-attests stays locked and can be
+    /// reclaimed through the emergency path.
+    pub fn revert_attestation(
+        ctx: Context<RevertAttestation>,
+        observed_block_hash: [u8; 32],
+    ) -> Result<()> {
+        let relayer_account = &ctx.accounts.relayer;
+        require!(relayer_account.active, ErrorCode::NotActiveRelayer);
+
+        let tx = &mut ctx.accounts.bridge_tx;
+        require!(tx.state == TransactionState::Locked, ErrorCode::AlreadyFinalized);
+        require!(
+            tx.source_block_hash != [0; 32] && observed_block_hash != tx.source_block_hash,
+            ErrorCode::SourceBlockMismatch
+        );
+        let (source_block, source_block_hash) = (tx.source_block, tx.source_block_hash);
+
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.open_round(tx.key(), relayer_account.key(), tx.attestation_round);
+        require!(!attestation.revert_voted, ErrorCode::RevertAlreadyVoted);
+        attestation.revert_voted = true;
+
+        let required_votes = ctx.accounts.bridge.min_confirmations;
+        let reverted = tx.add_revert_vote(required_votes)?;
+
+        emit!(RevertVoteCast {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.relayer_authority.key(),
+            tx_id: tx.id,
+            relayer: ctx.accounts.relayer_authority.key(),
+            observed_block_hash,
+            votes: tx.revert_votes,
+            required_votes,
+            round: attestation.round,
+            slot: Clock::get()?.slot,
+        });
+
+        let Some(dropped_confirmations) = reverted else {
+            return Ok(());
+        };
+
+        emit!(AttestationReverted {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.relayer_authority.key(),
+            tx_id: tx.id,
+            relayer: ctx.accounts.relayer_authority.key(),
+            source_block,
+            source_block_hash,
+            observed_block_hash,
+            dropped_confirmations,
+            new_round: tx.attestation_round,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Add a relayer
+    ///
+    /// Executes a queued `AddRelayer` proposal; the relayer authority signs
+    /// to prove it holds the key.
+    pub fn add_relayer(ctx: Context<AddRelayer>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute_matching(
+            &ctx.accounts.bridge,
+            &GovernanceAction::AddRelayer {
+                relayer_authority: ctx.accounts.relayer_authority.key(),
+            },
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let relayer = &mut ctx.accounts.relayer;
+        relayer.authority = ctx.accounts.relayer_authority.key();
+        relayer.active = true;
+        relayer.total_relayed = 0;
+        relayer.slashed = false;
+
+        emit!(RelayerAdded {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            relayer: ctx.accounts.relayer_authority.key(),
+            slot: Clock::get()?.slot,
+        });
+        emit!(ProposalExecuted {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            proposal_id: proposal.id,
+            action: proposal.action.clone(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Register a chain so governance can disable, pause or tune it
+    ///
+    /// Executes a queued `RegisterChain` proposal (permissionless). The chain
+    /// starts with the settings it had while unregistered.
+    pub fn register_chain(ctx: Context<RegisterChain>, chain_id: u64) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute_matching(
+            &ctx.accounts.bridge,
+            &GovernanceAction::RegisterChain { chain_id },
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let chain_config = &mut ctx.accounts.chain_config;
+        chain_config.set_inner(ChainConfig::unregistered(chain_id));
+
+        emit!(ChainRegistered {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            chain_id,
+            slot: Clock::get()?.slot,
+        });
+        emit!(ProposalExecuted {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            proposal_id: proposal.id,
+            action: proposal.action.clone(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Queue a governance action (owners only)
+    ///
+    /// Executable once `threshold` owners have approved and
+    /// `governance_delay` has passed.
+    pub fn queue_proposal(
+        ctx: Context<QueueProposal>,
+        action: GovernanceAction,
+    ) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge;
+        let now = Clock::get()?.unix_timestamp;
+
+        // Proposer approves implicitly
+        let approvals = bridge.multisig.propose(&ctx.accounts.proposer.key())?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.id = bridge.proposal_count;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.action = action.clone();
+        proposal.queued_at = now;
+        proposal.eta = now
+            .checked_add(action.timelock(bridge.governance_delay))
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        proposal.state = ProposalState::Queued;
+        proposal.approvals = approvals;
+        proposal.cancel_approvals = Approvals::none(&bridge.multisig);
+
+        bridge.proposal_count = bridge.proposal_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(ProposalQueued {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.proposer.key(),
+            proposal_id: proposal.id,
+            proposer: proposal.proposer,
+            action,
+            eta: proposal.eta,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Approve a queued proposal (owners only)
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let approvals = proposal.approve(&ctx.accounts.bridge, &ctx.accounts.owner.key())?;
+
+        emit!(ProposalApproved {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.owner.key(),
+            proposal_id: proposal.id,
+            approvals,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Execute an approved governance action after its eta (permissionless)
+    pub fn execute_proposal(mut ctx: Context<ExecuteProposal>) -> Result<()> {
+        let actor = ctx.accounts.executor.key();
+        let slot = Clock::get()?.slot;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute(&ctx.accounts.bridge, Clock::get()?.unix_timestamp)?;
+        let (proposal_id, action) = (proposal.id, proposal.action.clone());
+
+        let bridge = &mut ctx.accounts.bridge;
+        match action.clone() {
+            GovernanceAction::UpdateFee { new_fee } => {
+                require!(new_fee <= 1000, ErrorCode::FeeTooHigh); // Max 10%
+                let old_fee = bridge.bridge_fee;
+                bridge.bridge_fee = new_fee;
+                emit!(FeeUpdated { version: EVENT_SCHEMA_VERSION, actor, old_fee, new_fee, slot });
+            }
+            GovernanceAction::UpdateMinConfirmations { min_confirmations } => {
+                require!(min_confirmations > 0, ErrorCode::InvalidMinConfirmations);
+                let old_min_confirmations = bridge.min_confirmations;
+                bridge.min_confirmations = min_confirmations;
+                emit!(MinConfirmationsUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    old_min_confirmations,
+                    new_min_confirmations: min_confirmations,
+                    slot,
+                });
+            }
+            GovernanceAction::SetOwners { owners, threshold } => {
+                // Invalidates every pending proposal approved under the old owner set
+                bridge.multisig.replace(owners.clone(), threshold)?;
+                emit!(OwnersChanged {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    owners,
+                    threshold,
+                    owner_set_seqno: bridge.multisig.seqno,
+                    slot,
+                });
+            }
+            GovernanceAction::SetAuditorKey { auditor_viewing_key } => {
+                let old_key = bridge.auditor_viewing_key;
+                bridge.auditor_viewing_key = auditor_viewing_key;
+                emit!(AuditorKeyUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    old_key,
+                    new_key: auditor_viewing_key,
+                    slot,
+                });
+            }
+            GovernanceAction::UpdateEmergencyDelay { delay } => {
+                require!(delay >= MIN_EMERGENCY_DELAY, ErrorCode::InvalidGovernanceDelay);
+                let old_delay = bridge.emergency_delay;
+                bridge.emergency_delay = delay;
+                emit!(EmergencyDelayUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    old_delay,
+                    new_delay: delay,
+                    slot,
+                });
+            }
+            GovernanceAction::UpdateGovernanceDelay { delay } => {
+                validate_governance_delay(delay)?;
+                let old_delay = bridge.governance_delay;
+                bridge.governance_delay = delay;
+                emit!(GovernanceDelayUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    old_delay,
+                    new_delay: delay,
+                    slot,
+                });
+            }
+            GovernanceAction::UpdateEpochDuration { duration } => {
+                require!(
+                    (1..=MAX_EPOCH_DURATION).contains(&duration),
+                    ErrorCode::InvalidEpochDuration
+                );
+                let old_duration = bridge.epoch_duration;
+                bridge.epoch_duration = duration;
+                emit!(EpochDurationUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    old_duration,
+                    new_duration: duration,
+                    slot,
+                });
+            }
+            GovernanceAction::SetVerifyingKey { verifying_key_hash } => {
+                let old_hash = bridge.verifying_key_hash;
+                bridge.verifying_key_hash = verifying_key_hash;
+                emit!(VerifyingKeyUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    old_hash,
+                    new_hash: verifying_key_hash,
+                    slot,
+                });
+            }
+            GovernanceAction::SetChainSupported { chain_id, supported } => {
+                let chain_config = ctx.accounts.chain_config
+                    .as_mut()
+                    .ok_or(ErrorCode::MissingGovernanceAccount)?;
+                require!(chain_config.chain_id == chain_id, ErrorCode::MissingGovernanceAccount);
+                let old_supported = chain_config.supported;
+                chain_config.supported = supported;
+                emit!(ChainSupportUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    chain_id,
+                    old_supported,
+                    new_supported: supported,
+                    slot,
+                });
+            }
+            GovernanceAction::SetChainFinality { chain_id, finality_depth } => {
+                let chain_config = ctx.accounts.chain_config
+                    .as_mut()
+                    .ok_or(ErrorCode::MissingGovernanceAccount)?;
+                require!(chain_config.chain_id == chain_id, ErrorCode::MissingGovernanceAccount);
+                require!(finality_depth > 0, ErrorCode::InvalidFinalityDepth);
+                let old_finality_depth = chain_config.finality_depth;
+                chain_config.finality_depth = finality_depth;
+                emit!(ChainFinalityUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    chain_id,
+                    old_finality_depth,
+                    new_finality_depth: finality_depth,
+                    slot,
+                });
+            }
+            GovernanceAction::SetRelayerActive { relayer, active } => {
+                let relayer_account = ctx.accounts.relayer
+                    .as_mut()
+                    .ok_or(ErrorCode::MissingGovernanceAccount)?;
+                require!(relayer_account.key() == relayer, ErrorCode::MissingGovernanceAccount);
+                let old_active = relayer_account.active;
+                relayer_account.active = active;
+                emit!(RelayerStatusChanged {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    relayer,
+                    old_active,
+                    new_active: active,
+                    slot,
+                });
+            }
+            GovernanceAction::Unpause => {
+                bridge.unpause();
+                emit!(BridgeUnpaused { version: EVENT_SCHEMA_VERSION, actor, slot });
+            }
+            GovernanceAction::SetPauseFlags { scope, target, flags } => {
+                require!(flags & !PAUSE_ALL == 0, ErrorCode::InvalidPauseFlags);
+                let accounts = &mut ctx.accounts;
+                require_keys_eq!(
+                    pause_target(&scope, &accounts.bridge, &accounts.chain_config, &accounts.token_config),
+                    target,
+                    ErrorCode::MissingGovernanceAccount
+                );
+                let was_halted = accounts.bridge.halted();
+                let current = pause_flags_mut(
+                    &scope,
+                    &mut accounts.bridge,
+                    &mut accounts.chain_config,
+                    &mut accounts.token_config,
+                )?;
+                let old_flags = *current;
+                *current = flags;
+                accounts.bridge.track_halt(was_halted, Clock::get()?.unix_timestamp);
+                emit!(PauseFlagsUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    scope,
+                    target,
+                    old_flags,
+                    new_flags: flags,
+                    slot,
+                });
+            }
+            GovernanceAction::SetAssociationSetActive { association_set, active } => {
+                let association_set_account = ctx.accounts.association_set
+                    .as_mut()
+                    .ok_or(ErrorCode::MissingGovernanceAccount)?;
+                require_keys_eq!(
+                    association_set_account.key(),
+                    association_set,
+                    ErrorCode::MissingGovernanceAccount
+                );
+                association_set_account.active = active;
+                emit!(AssociationSetStatusChanged {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    association_set,
+                    active,
+                    slot,
+                });
+            }
+            GovernanceAction::ProposeAuthority { new_authority } => {
+                bridge.propose_authority(new_authority)?;
+                emit!(AuthorityProposed {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    current_authority: bridge.authority,
+                    pending_authority: new_authority,
+                    slot,
+                });
+            }
+            GovernanceAction::CancelAuthorityTransfer => {
+                let cancelled = bridge.cancel_authority_transfer()?;
+                emit!(AuthorityTransferCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    actor,
+                    authority: bridge.authority,
+                    cancelled_authority: cancelled,
+                    slot,
+                });
+            }
+            // These create accounts or move funds and run through their own instructions
+            GovernanceAction::AddRelayer { .. }
+            | GovernanceAction::RegisterToken { .. }
+            | GovernanceAction::SetGuardian { .. }
+            | GovernanceAction::RegisterAssociationSet { .. }
+            | GovernanceAction::RegisterChain { .. }
+            | GovernanceAction::EmergencySweep { .. } => {
+                return err!(ErrorCode::ProposalActionMismatch);
+            }
+        }
+
+        emit!(ProposalExecuted { version: EVENT_SCHEMA_VERSION, actor, proposal_id, action, slot });
+
+        Ok(())
+    }
+
+    /// Vote to cancel a queued proposal (owners only)
+    ///
+    /// Cancelled once `threshold` owners have voted to cancel.
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let votes = proposal.approve_cancel(&ctx.accounts.bridge, &ctx.accounts.owner.key())?;
+
+        emit!(ProposalCancelApproved {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.owner.key(),
+            proposal_id: proposal.id,
+            votes,
+            slot: Clock::get()?.slot,
+        });
+        if proposal.state == ProposalState::Cancelled {
+            emit!(ProposalCancelled {
+                version: EVENT_SCHEMA_VERSION,
+                actor: ctx.accounts.owner.key(),
+                proposal_id: proposal.id,
+                slot: Clock::get()?.slot,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Unpause bridge
+    ///
+    /// Executes an `Unpause` proposal approved by the owner threshold
+    /// (permissionless). Unlike other proposals it has no timelock.
+    pub fn unpause(ctx: Context<UnpauseBridge>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute_matching(
+            &ctx.accounts.bridge,
+            &GovernanceAction::Unpause,
+            Clock::get()?.unix_timestamp,
+        )?;
+        ctx.accounts.bridge.unpause();
+
+        emit!(BridgeUnpaused {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            slot: Clock::get()?.slot,
+        });
+        emit!(ProposalExecuted {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            proposal_id: proposal.id,
+            action: proposal.action.clone(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Pause bridge (authority only, for emergencies)
+    ///
+    /// Unpausing needs an `Unpause` proposal approved by the owners.
+    pub fn pause(ctx: Context<UpdateBridge>) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge;
+        let was_halted = bridge.halted();
+        bridge.paused = true;
+        bridge.track_halt(was_halted, Clock::get()?.unix_timestamp);
+
+        emit!(BridgePaused {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.authority.key(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Reclaim an un-relayed lock once the bridge is in emergency mode
+    ///
+    /// Only locks whose epoch root was never published can be reclaimed, so
+    /// a refunded transfer cannot also be claimed on the destination chain.
+    /// The first reclaim withholds its epoch's root; the other locks of that
+    /// epoch can then never be delivered and stay reclaimable after the
+    /// emergency ends.
+    pub fn emergency_reclaim(ctx: Context<EmergencyReclaim>) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge;
+        let tx = &mut ctx.accounts.bridge_tx;
+        let outbound_epoch = &mut ctx.accounts.outbound_epoch;
+
+        require!(
+            bridge.emergency_mode_active(Clock::get()?.unix_timestamp) || outbound_epoch.withheld,
+            ErrorCode::EmergencyModeInactive
+        );
+        tx.refund(outbound_epoch)?;
+
+        let authority_bump = ctx.bumps.bridge_authority;
+        let authority_seeds = &[
+            b"bridge_authority".as_ref(),
+            &[authority_bump],
+        ];
+        let signer = &[&authority_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.bridge_token_account.to_account_info(),
+            to: ctx.accounts.sender_token_account.to_account_info(),
+            authority: ctx.accounts.bridge_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, tx.amount)?;
+
+        // Refunds settle the liability exactly like an unlock
+        bridge.total_unlocked = bridge.total_unlocked
+            .checked_add(tx.amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.total_unlocked = token_config.total_unlocked
+            .checked_add(tx.amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(EmergencyReclaimed {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.sender.key(),
+            tx_id: tx.id,
+            sender: tx.sender,
+            amount: tx.amount,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Sweep vault surplus above outstanding liabilities in emergency mode
+    ///
+    /// Executes a queued `EmergencySweep` proposal (permissionless).
+    pub fn emergency_sweep(ctx: Context<EmergencySweep>, amount: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.bridge.emergency_mode_active(now),
+            ErrorCode::EmergencyModeInactive
+        );
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute_matching(
+            &ctx.accounts.bridge,
+            &GovernanceAction::EmergencySweep {
+                mint: ctx.accounts.token_config.mint,
+                destination: ctx.accounts.destination.key(),
+                amount,
+            },
+            now,
+        )?;
+
+        let token_config = &ctx.accounts.token_config;
+        let liabilities = token_config.liabilities();
+        let vault_balance = ctx.accounts.bridge_token_account.amount;
+        let surplus = token_config.surplus(vault_balance);
+        require!(amount <= surplus, ErrorCode::InsufficientSurplus);
+
+        let authority_bump = ctx.bumps.bridge_authority;
+        let authority_seeds = &[
+            b"bridge_authority".as_ref(),
+            &[authority_bump],
+        ];
+        let signer = &[&authority_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.bridge_token_account.to_account_info(),
+            to: ctx.accounts.destination.to_account_info(),
+            authority: ctx.accounts.bridge_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit!(SurplusSwept {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            mint: token_config.mint,
+            amount,
+            vault_balance,
+            liabilities,
+            slot: Clock::get()?.slot,
+        });
+        emit!(ProposalExecuted {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            proposal_id: proposal.id,
+            action: proposal.action.clone(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Propose a new bridge authority
+    ///
+    /// Executes a queued `ProposeAuthority` proposal (permissionless). The
+    /// transfer completes once the new authority signs `accept_authority`.
+    pub fn propose_authority(ctx: Context<ChangeAuthority>, new_authority: Pubkey) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute_matching(
+            &ctx.accounts.bridge,
+            &GovernanceAction::ProposeAuthority { new_authority },
+            Clock::get()?.unix_timestamp,
+        )?;
+        let bridge = &mut ctx.accounts.bridge;
+        bridge.propose_authority(new_authority)?;
+
+        emit!(AuthorityProposed {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            current_authority: bridge.authority,
+            pending_authority: new_authority,
+            slot: Clock::get()?.slot,
+        });
+        emit!(ProposalExecuted {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            proposal_id: proposal.id,
+            action: proposal.action.clone(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a proposed authority transfer before it is accepted
+    ///
+    /// Executes a `CancelAuthorityTransfer` proposal approved by the owner
+    /// threshold (permissionless). Like `unpause` it has no timelock, since
+    /// it only keeps the current authority in place.
+    pub fn cancel_authority_transfer(ctx: Context<ChangeAuthority>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute_matching(
+            &ctx.accounts.bridge,
+            &GovernanceAction::CancelAuthorityTransfer,
+            Clock::get()?.unix_timestamp,
+        )?;
+        let bridge = &mut ctx.accounts.bridge;
+        let cancelled = bridge.cancel_authority_transfer()?;
+
+        emit!(AuthorityTransferCancelled {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            authority: bridge.authority,
+            cancelled_authority: cancelled,
+            slot: Clock::get()?.slot,
+        });
+        emit!(ProposalExecuted {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            proposal_id: proposal.id,
+            action: proposal.action.clone(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Accept an authority transfer proposed by the owners (signed by the new authority)
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge;
+        let old_authority = bridge.authority;
+        bridge.authority = bridge.pending_authority;
+        bridge.pending_authority = Pubkey::default();
+
+        emit!(AuthorityTransferred {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.new_authority.key(),
+            old_authority,
+            new_authority: bridge.authority,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Compare registered vault balances with outstanding liabilities
+    ///
+    /// Permissionless. `remaining_accounts` holds `(token_config, vault)`
+    /// pairs for any subset of registered tokens, each `token_config` at its
+    /// `[b"token", mint]` address; a token whose vault is short is paused for
+    /// every operation.
+    pub fn audit_reserves<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AuditReserves<'info>>,
+    ) -> Result<()> {
+        let remaining = ctx.remaining_accounts;
+        require!(
+            !remaining.is_empty() && remaining.len() % 2 == 0,
+            ErrorCode::InvalidAuditAccounts
+        );
+
+        let slot = Clock::get()?.slot;
+        for pair in remaining.chunks(2) {
+            let mut token_config: Account<'info, TokenConfig> = Account::try_from(&pair[0])?;
+            let vault: Account<'info, TokenAccount> = Account::try_from(&pair[1])?;
+            check_audit_pair(ctx.program_id, &token_config.key(), &token_config, &vault.key())?;
+
+            let liabilities = token_config.liabilities();
+            let shortfall = token_config.audit(vault.amount);
+            let paused = shortfall > 0;
+            if paused {
+                token_config.exit(ctx.program_id)?;
+            }
+
+            emit!(ReservesAudited {
+                version: EVENT_SCHEMA_VERSION,
+                actor: ctx.accounts.auditor.key(),
+                mint: token_config.mint,
+                vault: vault.key(),
+                vault_balance: vault.amount,
+                liabilities,
+                shortfall,
+                paused,
+                slot,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Register a bridged token and its vault
+    ///
+    /// Executes a queued `RegisterToken` proposal (permissionless).
+    pub fn register_token(ctx: Context<RegisterToken>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute_matching(
+            &ctx.accounts.bridge,
+            &GovernanceAction::RegisterToken {
+                mint: ctx.accounts.mint.key(),
+                vault: ctx.accounts.vault.key(),
+            },
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.mint = ctx.accounts.mint.key();
+        token_config.vault = ctx.accounts.vault.key();
+        token_config.pause_flags = 0;
+        token_config.total_locked = 0;
+        token_config.total_unlocked = 0;
+
+        emit!(TokenRegistered {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            mint: token_config.mint,
+            vault: token_config.vault,
+            slot: Clock::get()?.slot,
+        });
+        emit!(ProposalExecuted {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            proposal_id: proposal.id,
+            action: proposal.action.clone(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Add or deactivate a guardian
+    ///
+    /// Executes a queued `SetGuardian` proposal (permissionless).
+    pub fn set_guardian(ctx: Context<SetGuardian>, active: bool) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.execute_matching(
+            &ctx.accounts.bridge,
+            &GovernanceAction::SetGuardian { guardian: ctx.accounts.guardian.key(), active },
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let guardian = &mut ctx.accounts.guardian_account;
+        guardian.guardian = ctx.accounts.guardian.key();
+        guardian.active = active;
+
+        emit!(GuardianUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            guardian: guardian.guardian,
+            active,
+            slot: Clock::get()?.slot,
+        });
+        emit!(ProposalExecuted {
+            version: EVENT_SCHEMA_VERSION,
+            actor: ctx.accounts.executor.key(),
+            proposal_id: proposal.id,
+            action: proposal.action.clone(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Emergency pause by a guardian
+    ///
+    /// Guardians can only set pause flags, never clear them; unpausing needs
+    /// an owner-approved `Unpause` / `SetPauseFlags` proposal.
+    pub fn guardian_pause(
+        mut ctx: Context<GuardianPause>,
+        scope: PauseScope,
+        flags: u8,
+    ) -> Result<()> {
+        require!(ctx.accounts.guardian_account.active, ErrorCode::NotGuardian);
+        require!(flags != 0 && flags & !PAUSE_ALL == 0, ErrorCode::InvalidPauseFlags);
+
+        let accounts = &mut ctx.accounts;
+        let was_halted = accounts.bridge.halted();
+        let current = pause_flags_mut(
+            &scope,
+            &mut accounts.bridge,
+            &mut accounts.chain_config,
+            &mut accounts.token_config,
+        )?;
+        *current |= flags;
+        let new_flags = *current;
+        accounts.bridge.track_halt(was_halted, Clock::get()?.unix_timestamp);
+
+        emit!(GuardianPaused {
+            version: EVENT_SCHEMA_VERSION,
+            actor: accounts.guardian.key(),
+            guardian: accounts.guardian.key(),
+            scope,
+            flags: new_flags,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
+
+// ========== ACCOUNTS ==========
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Bridge::LEN,
+        seeds = [b"bridge"],
+        bump
+    )]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, target_chain: u64)]
+pub struct LockAssets<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge"],
+        bump
+    )]
+    pub bridge: Account<'info, Bridge>,
+
+    /// CHECK: chain settings PDA, loaded by `load_chain_config` and empty
+    /// while the chain is unregistered
+    #[account(
+        seeds = [b"chain", target_chain.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub chain_config: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token", bridge_token_account.mint.as_ref()],
+        bump,
+        constraint = token_config.vault == bridge_token_account.key() @ ErrorCode::InvalidVault
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + BridgeTransaction::LEN,
+    )]
+    pub bridge_tx: Account<'info, BridgeTransaction>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + OutboundEpoch::LEN,
+        seeds = [b"epoch", bridge.current_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub outbound_epoch: Account<'info, OutboundEpoch>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub bridge_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockAssets<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge"],
+        bump
+    )]
+    pub bridge: Account<'info, Bridge>,
+
+    /// CHECK: PDA authority for bridge
+    #[account(
+        seeds = [b"bridge_authority"],
+        bump
+    )]
+    pub bridge_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub bridge_tx: Account<'info, BridgeTransaction>,
+
+    /// CHECK: chain settings PDA, loaded by `load_chain_config` and empty
+    /// while the chain is unregistered
+    #[account(
+        seeds = [b"chain", bridge_tx.target_chain.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub chain_config: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token", bridge_tx.mint.as_ref()],
+        bump,
+        constraint = token_config.vault == bridge_token_account.key() @ ErrorCode::InvalidVault
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + NullifierAccount::LEN,
+    )]
+    pub nullifier_account: Account<'info, NullifierAccount>,
+
+    #[account(mut)]
+    pub bridge_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Recipient wallet bound as a public input of the proof
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = bridge_token_account.mint,
+        associated_token::authority = recipient
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockAssetsWithAssociation<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge"],
+        bump
+    )]
+    pub bridge: Account<'info, Bridge>,
+
+    /// CHECK: PDA authority for bridge
+    #[account(
+        seeds = [b"bridge_authority"],
+        bump
+    )]
+    pub bridge_authority: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"association_set", association_set.provider.as_ref()],
+        bump
+    )]
+    pub association_set: Account<'info, AssociationSet>,
+
+    #[account(mut)]
+    pub bridge_tx: Account<'info, BridgeTransaction>,
+
+    /// CHECK: chain settings PDA, loaded by `load_chain_config` and empty
+    /// while the chain is unregistered
+    #[account(
+        seeds = [b"chain", bridge_tx.target_chain.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub chain_config: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token", bridge_tx.mint.as_ref()],
+        bump,
+        constraint = token_config.vault == bridge_token_account.key() @ ErrorCode::InvalidVault
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + NullifierAccount::LEN,
+    )]
+    pub nullifier_account: Account<'info, NullifierAccount>,
+
+    #[account(mut)]
+    pub bridge_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Recipient wallet bound as a public input of the proof
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = bridge_token_account.mint,
+        associated_token::authority = recipient
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RelayedUnlockAssets<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge"],
+        bump
+    )]
+    pub bridge: Account<'info, Bridge>,
+
+    /// CHECK: PDA authority for bridge
+    #[account(
+        seeds = [b"bridge_authority"],
+        bump
+    )]
+    pub bridge_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub bridge_tx: Account<'info, BridgeTransaction>,
+
+    /// CHECK: chain settings PDA, loaded by `load_chain_config` and empty
+    /// while the chain is unregistered
+    #[account(
+        seeds = [b"chain", bridge_tx.target_chain.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub chain_config: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token", bridge_tx.mint.as_ref()],
+        bump,
+        constraint = token_config.vault == bridge_token_account.key() @ ErrorCode::InvalidVault
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + NullifierAccount::LEN,
+    )]
+    pub nullifier_account: Account<'info, NullifierAccount>,
+
+    #[account(mut)]
+    pub bridge_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Recipient wallet bound as a public input of the proof
+    pub recipient: UncheckedAccount<'info>,
+
+    /// Created for the recipient if missing, since a relayed recipient may
+    /// hold no SOL
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        associated_token::mint = mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    #[account(address = bridge_token_account.mint @ ErrorCode::InvalidVault)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = relayer_token_account.owner == relayer.key(),
+        constraint = relayer_token_account.mint == bridge_token_account.mint
+    )]
+    pub relayer_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(target_chain: u64)]
+pub struct LockAssetsBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge"],
+        bump
+    )]
+    pub bridge: Account<'info, Bridge>,
+
+    /// CHECK: chain settings PDA, loaded by `load_chain_config` and empty
+    /// while the chain is unregistered
+    #[account(
+        seeds = [b"chain", target_chain.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub chain_config: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token", bridge_token_account.mint.as_ref()],
+        bump,
+        constraint = token_config.vault == bridge_token_account.key() @ ErrorCode::InvalidVault
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + OutboundEpoch::LEN,
+        seeds = [b"epoch", bridge.current_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub outbound_epoch: Account<'info, OutboundEpoch>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub bridge_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(target_chain: u64)]
+pub struct UnlockAssetsBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge"],
+        bump
+    )]
+    pub bridge: Account<'info, Bridge>,
+
+    /// CHECK: PDA authority for bridge
+    #[account(
+        seeds = [b"bridge_authority"],
+        bump
+    )]
+    pub bridge_authority: UncheckedAccount<'info>,
+
+    /// CHECK: chain settings PDA, loaded by `load_chain_config` and empty
+    /// while the chain is unregistered
+    #[account(
+        seeds = [b"chain", target_chain.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub chain_config: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token", bridge_token_account.mint.as_ref()],
+        bump,
+        constraint = token_config.vault == bridge_token_account.key() @ ErrorCode::InvalidVault
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    #[account(mut)]
+    pub bridge_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeEpoch<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge"],
+        bump
+    )]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", bridge.current_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub outbound_epoch: Account<'info, OutboundEpoch>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterAssociationSet<'info> {
+    #[account(seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    #[account(
+        init,
+        payer = executor,
+        space = 8 + AssociationSet::LEN,
+        seeds = [b"association_set", provider.key().as_ref()],
+        bump
+    )]
+    pub association_set: Account<'info, AssociationSet>,
+
+    /// CHECK: Set provider that will publish association roots
+    pub provider: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAssociationRoot<'info> {
+    #[account(
+        mut,
+        seeds = [b"association_set", provider.key().as_ref()],
+        bump,
+        constraint = association_set.provider == provider.key() @ ErrorCode::NotAssociationProvider
+    )]
+    pub association_set: Account<'info, AssociationSet>,
+
+    pub provider: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RelayTransaction<'info> {
+    #[account(seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(mut)]
+    pub bridge_tx: Account<'info, BridgeTransaction>,
+
+    /// CHECK: chain settings PDA, loaded by `load_chain_config` and empty
+    /// while the chain is unregistered
+    #[account(
+        seeds = [b"chain", bridge_tx.target_chain.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub chain_config: UncheckedAccount<'info>,
+
+    /// CHECK: settings PDA of the chain the lock happened on, which sets
+    /// the finality requirement; loaded by `load_chain_config`
+    #[account(
+        seeds = [b"chain", bridge_tx.source_chain.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub source_chain_config: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"token", bridge_tx.mint.as_ref()],
+        bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    #[account(constraint = relayer.authority == relayer_authority.key() @ ErrorCode::NotActiveRelayer)]
+    pub relayer: Account<'info, Relayer>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer_authority,
+        space = 8 + Attestation::LEN,
+        seeds = [b"attestation", bridge_tx.key().as_ref(), relayer.key().as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(mut)]
+    pub relayer_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevertAttestation<'info> {
+    #[account(seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(mut)]
+    pub bridge_tx: Account<'info, BridgeTransaction>,
+
+    #[account(constraint = relayer.authority == relayer_authority.key() @ ErrorCode::NotActiveRelayer)]
+    pub relayer: Account<'info, Relayer>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer_authority,
+        space = 8 + Attestation::LEN,
+        seeds = [b"attestation", bridge_tx.key().as_ref(), relayer.key().as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(mut)]
+    pub relayer_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddRelayer<'info> {
+    #[account(seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    #[account(
+        init,
+        payer = executor,
+        space = 8 + Relayer::LEN,
+    )]
+    pub relayer: Account<'info, Relayer>,
+
+    pub relayer_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnpauseBridge<'info> {
+    #[account(mut, seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ChangeAuthority<'info> {
+    #[account(mut, seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateBridge<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge"],
+        bump,
+        constraint = bridge.authority == authority.key()
+    )]
+    pub bridge: Account<'info, Bridge>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge"],
+        bump,
+        constraint = bridge.pending_authority != Pubkey::default() @ ErrorCode::NoPendingAuthority,
+        constraint = bridge.pending_authority == new_authority.key() @ ErrorCode::NotPendingAuthority
+    )]
+    pub bridge: Account<'info, Bridge>,
+
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(chain_id: u64)]
+pub struct RegisterChain<'info> {
+    #[account(seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    #[account(
+        init,
+        payer = executor,
+        space = 8 + ChainConfig::LEN,
+        seeds = [b"chain", chain_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub chain_config: Account<'info, ChainConfig>,
+
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct QueueProposal<'info> {
+    #[account(mut, seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + GovernanceProposal::LEN,
+        seeds = [b"proposal", bridge.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    #[account(seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut, seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    /// Required for `SetChainSupported`, `SetChainFinality` and chain `SetPauseFlags`
+    #[account(mut)]
+    pub chain_config: Option<Account<'info, ChainConfig>>,
+
+    /// Required for `SetRelayerActive`
+    #[account(mut)]
+    pub relayer: Option<Account<'info, Relayer>>,
+
+    /// Required for token `SetPauseFlags`
+    #[account(mut)]
+    pub token_config: Option<Account<'info, TokenConfig>>,
+
+    /// Required for `SetAssociationSetActive`
+    #[account(mut)]
+    pub association_set: Option<Account<'info, AssociationSet>>,
+
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    #[account(seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyReclaim<'info> {
+    #[account(mut, seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    /// CHECK: PDA authority for bridge
+    #[account(
+        seeds = [b"bridge_authority"],
+        bump
+    )]
+    pub bridge_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = bridge_tx.sender == sender.key() @ ErrorCode::NotSender
+    )]
+    pub bridge_tx: Account<'info, BridgeTransaction>,
+
+    #[account(
+        mut,
+        seeds = [b"epoch", bridge_tx.outbound_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub outbound_epoch: Account<'info, OutboundEpoch>,
+
+    #[account(
+        mut,
+        seeds = [b"token", bridge_tx.mint.as_ref()],
+        bump,
+        constraint = token_config.vault == bridge_token_account.key() @ ErrorCode::InvalidVault
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    #[account(mut)]
+    pub bridge_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = sender_token_account.owner == sender.key(),
+        constraint = sender_token_account.mint == bridge_tx.mint
+    )]
+    pub sender_token_account: Account<'info, TokenAccount>,
+
+    pub sender: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencySweep<'info> {
+    #[account(seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    /// CHECK: PDA authority for bridge
+    #[account(
+        seeds = [b"bridge_authority"],
+        bump
+    )]
+    pub bridge_authority: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"token", token_config.mint.as_ref()],
+        bump,
+        constraint = token_config.vault == bridge_token_account.key() @ ErrorCode::InvalidVault
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    #[account(mut)]
+    pub bridge_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = destination.mint == token_config.mint
+    )]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub executor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct AuditReserves<'info> {
+    #[account(seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    pub auditor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterToken<'info> {
+    #[account(seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    /// CHECK: PDA authority for bridge
+    #[account(
+        seeds = [b"bridge_authority"],
+        bump
+    )]
+    pub bridge_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = executor,
+        space = 8 + TokenConfig::LEN,
+        seeds = [b"token", mint.key().as_ref()],
+        bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        constraint = vault.mint == mint.key() @ ErrorCode::InvalidVault,
+        constraint = vault.owner == bridge_authority.key() @ ErrorCode::InvalidVault
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+
+    #[account(
+        init_if_needed,
+        payer = executor,
+        space = 8 + Guardian::LEN,
+        seeds = [b"guardian", guardian.key().as_ref()],
+        bump
+    )]
+    pub guardian_account: Account<'info, Guardian>,
+
+    /// CHECK: Guardian key being added or removed
+    pub guardian: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GuardianPause<'info> {
+    #[account(mut, seeds = [b"bridge"], bump)]
+    pub bridge: Account<'info, Bridge>,
+
+    #[account(
+        seeds = [b"guardian", guardian.key().as_ref()],
+        bump
+    )]
+    pub guardian_account: Account<'info, Guardian>,
+
+    pub guardian: Signer<'info>,
+
+    /// Required for `PauseScope::Chain`
+    #[account(mut)]
+    pub chain_config: Option<Account<'info, ChainConfig>>,
+
+    /// Required for `PauseScope::Token`
+    #[account(mut)]
+    pub token_config: Option<Account<'info, TokenConfig>>,
+}
+
+// ========== STATE ==========
+
+#[account]
+pub struct Bridge {
+    /// Operator key that may pause in an emergency; everything else needs
+    /// `multisig.threshold` owners through governance proposals
+    pub authority: Pubkey,
+    /// Proposed authority awaiting acceptance, default when none
+    pub pending_authority: Pubkey,
+    pub min_confirmations: u8,
+    pub bridge_fee: u16,
+    pub total_locked: u64,
+    pub total_unlocked: u64,
+    pub paused: bool,
+    /// Granular `PAUSE_*` flags set by guardians or the authority
+    pub pause_flags: u8,
+    /// When the current halt began, zero while running
+    pub paused_at: i64,
+    /// Seconds the bridge must stay paused before emergency mode opens
+    pub emergency_delay: i64,
+    /// X25519 public key every note is also encrypted to
+    pub auditor_viewing_key: [u8; 32],
+    /// Seconds a governance proposal must wait before execution
+    pub governance_delay: i64,
+    pub proposal_count: u64,
+    /// Outbound epoch currently accepting locks
+    pub current_epoch: u64,
+    /// Minimum seconds an outbound epoch stays open
+    pub epoch_duration: i64,
+    /// Owners that queue, approve and cancel governance proposals
+    pub multisig: OwnerSet,
+    /// Hash of the Groth16 verifying key unlock proofs are checked against
+    pub verifying_key_hash: [u8; 32],
+}
+
+impl Bridge {
+    pub const LEN: usize = 32 + 32 + 1 + 2 + 8 + 8 + 1 + 1 + 8 + 8 + 32 + 8 + 8 + 8 + 8
+        + OwnerSet::LEN + 32;
+
+    /// Set the authority awaiting `accept_authority`
+    pub fn propose_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        require!(new_authority != Pubkey::default(), ErrorCode::InvalidAuthority);
+        self.pending_authority = new_authority;
+        Ok(())
+    }
+
+    /// Drop the pending authority and return it
+    pub fn cancel_authority_transfer(&mut self) -> Result<Pubkey> {
+        require!(
+            self.pending_authority != Pubkey::default(),
+            ErrorCode::NoPendingAuthority
+        );
+        Ok(std::mem::take(&mut self.pending_authority))
+    }
+
+    /// Lift the bridge-wide pause and every bridge-level pause flag
+    pub fn unpause(&mut self) {
+        self.paused = false;
+        self.pause_flags = 0;
+        self.paused_at = 0;
+    }
+
+    /// Unlocks or relays are stopped bridge-wide, by the authority's pause
+    /// or by bridge-level pause flags
+    pub fn halted(&self) -> bool {
+        self.paused || self.pause_flags & (PAUSE_UNLOCK | PAUSE_RELAY) != 0
+    }
+
+    /// Keep `paused_at` at the start of the current halt after a pause change
+    pub fn track_halt(&mut self, was_halted: bool, now: i64) {
+        match (was_halted, self.halted()) {
+            (false, true) => self.paused_at = now,
+            (_, false) => self.paused_at = 0,
+            (true, true) => {}
+        }
+    }
+
+    /// Emergency mode opens once the bridge has been halted for `emergency_delay`
+    pub fn emergency_mode_active(&self, now: i64) -> bool {
+        self.halted() && now >= self.paused_at.saturating_add(self.emergency_delay)
+    }
+}
+
+#[account]
+pub struct ChainConfig {
+    pub chain_id: u64,
+    pub supported: bool,
+    pub pause_flags: u8,
+    /// Blocks a source block must be buried under before attestations count
+    pub finality_depth: u64,
+}
+
+impl ChainConfig {
+    pub const LEN: usize = 8 + 1 + 1 + 8;
+
+    /// Settings of a chain governance never registered: open, unpaused and
+    /// with the default finality depth
+    pub fn unregistered(chain_id: u64) -> Self {
+        Self {
+            chain_id,
+            supported: true,
+            pause_flags: 0,
+            finality_depth: DEFAULT_FINALITY_DEPTH,
+        }
+    }
+}
+
+#[account]
+pub struct TokenConfig {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub pause_flags: u8,
+    pub total_locked: u64,
+    pub total_unlocked: u64,
+}
+
+impl TokenConfig {
+    pub const LEN: usize = 32 + 32 + 1 + 8 + 8;
+
+    /// Amount still owed to depositors of this token
+    pub fn liabilities(&self) -> u64 {
+        self.total_locked.saturating_sub(self.total_unlocked)
+    }
+
+    /// Vault balance above liabilities, the most an emergency sweep may take
+    pub fn surplus(&self, vault_balance: u64) -> u64 {
+        vault_balance.saturating_sub(self.liabilities())
+    }
+
+    /// Pause every operation if `vault_balance` cannot cover liabilities
+    ///
+    /// Returns the shortfall, zero when the vault is fully backed.
+    pub fn audit(&mut self, vault_balance: u64) -> u64 {
+        let shortfall = self.liabilities().saturating_sub(vault_balance);
+        if shortfall > 0 {
+            self.pause_flags |= PAUSE_ALL;
+        }
+        shortfall
+    }
+}
+
+#[account]
+pub struct Guardian {
+    pub guardian: Pubkey,
+    pub active: bool,
+}
+
+impl Guardian {
+    pub const LEN: usize = 32 + 1;
+}
+
+#[account]
+pub struct GovernanceProposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: GovernanceAction,
+    pub queued_at: i64,
+    pub eta: i64,
+    pub state: ProposalState,
+    /// Owner approvals to execute
+    pub approvals: Approvals,
+    /// Owner votes to cancel
+    pub cancel_approvals: Approvals,
+}
+
+impl GovernanceProposal {
+    pub const LEN: usize = 8 + 32 + GovernanceAction::LEN + 8 + 8 + 1 + Approvals::LEN * 2;
+
+    /// Proposals stay open only while queued under the current owner set
+    fn check_open(&self, bridge: &Bridge) -> Result<()> {
+        require!(self.state != ProposalState::Executed, ErrorCode::AlreadyExecuted);
+        require!(self.state == ProposalState::Queued, ErrorCode::ProposalNotQueued);
+        self.approvals.check_current(&bridge.multisig)
+    }
+
+    /// Record an owner's approval and return the approval count
+    pub fn approve(&mut self, bridge: &Bridge, owner: &Pubkey) -> Result<u8> {
+        self.check_open(bridge)?;
+        self.approvals.approve(&bridge.multisig, owner)
+    }
+
+    /// Record an owner's cancel vote and return the vote count; the
+    /// proposal is cancelled once the votes reach the threshold
+    pub fn approve_cancel(&mut self, bridge: &Bridge, owner: &Pubkey) -> Result<u8> {
+        self.check_open(bridge)?;
+        let votes = self.cancel_approvals.approve(&bridge.multisig, owner)?;
+        if self.cancel_approvals.check_threshold(&bridge.multisig).is_ok() {
+            self.state = ProposalState::Cancelled;
+        }
+        Ok(votes)
+    }
+
+    /// Mark an approved proposal executed once its timelock has elapsed
+    pub fn execute(&mut self, bridge: &Bridge, now: i64) -> Result<()> {
+        self.check_open(bridge)?;
+        self.approvals.check_threshold(&bridge.multisig)?;
+        require!(now >= self.eta, ErrorCode::TimelockNotElapsed);
+        require!(
+            now <= self.eta.saturating_add(GOVERNANCE_GRACE_PERIOD),
+            ErrorCode::ProposalExpired
+        );
+        self.state = ProposalState::Executed;
+        Ok(())
+    }
+
+    /// Execute a proposal from the instruction that carries out `action`
+    pub fn execute_matching(
+        &mut self,
+        bridge: &Bridge,
+        action: &GovernanceAction,
+        now: i64,
+    ) -> Result<()> {
+        require!(self.action == *action, ErrorCode::ProposalActionMismatch);
+        self.execute(bridge, now)
+    }
+}
+
+#[account]
+pub struct BridgeTransaction {
+    pub id: [u8; 32],
+    pub source_chain: u64,
+    pub target_chain: u64,
+    pub sender: Pubkey,
+    pub mint: Pubkey,
+    pub recipient_commitment: [u8; 32],
+    pub amount: u64,
+    pub commitment: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub timestamp: i64,
+    pub state: TransactionState,
+    pub confirmations: u8,
+    pub stealth: Option<StealthMeta>,
+    /// Source-chain block holding the lock, as pinned by the first relayer
+    /// attestation of the current round; zero until then
+    pub source_block: u64,
+    pub source_block_hash: [u8; 32],
+    /// Block hash last reported as reorged out; never accepted again
+    pub reverted_block_hash: [u8; 32],
+    /// Deepest finality attested by any relayer in the current round
+    pub finality_depth: u64,
+    /// Bumped on every accepted reorg report; older attestations no longer count
+    pub attestation_round: u32,
+    /// Relayers that reported a reorg in the current round
+    pub revert_votes: u8,
+    /// Outbound epoch holding this transfer's leaf, and the leaf's index in it
+    pub outbound_epoch: u64,
+    pub leaf_index: u32,
+}
+
+impl BridgeTransaction {
+    pub const LEN: usize = 32 + 8 + 8 + 32 + 32 + 32 + 8 + 32 + 32 + 8 + 1 + 1 + (1 + StealthMeta::LEN)
+        + 8 + 32 + 32 + 8 + 4 + 1 + 8 + 4;
+
+    /// Refund a lock no relayer has confirmed, returning its amount
+    ///
+    /// `epoch` is the lock's outbound epoch. Once its root is published the
+    /// transfer is claimable on the destination chain and cannot be refunded;
+    /// refunding before that withholds the root for good.
+    pub fn refund(&mut self, epoch: &mut OutboundEpoch) -> Result<u64> {
+        require!(epoch.epoch_id == self.outbound_epoch, ErrorCode::InvalidEpoch);
+        require!(!epoch.finalized || epoch.withheld, ErrorCode::EpochFinalized);
+        require!(self.state == TransactionState::Locked, ErrorCode::InvalidState);
+        self.state = TransactionState::Refunded;
+        epoch.withheld = true;
+        Ok(self.amount)
+    }
+
+    /// Count one relayer confirmation
+    ///
+    /// The transaction becomes `Relayed`, and so unlockable, once it reaches
+    /// `min_confirmations`.
+    pub fn add_confirmation(&mut self, min_confirmations: u8) -> Result<()> {
+        require!(self.state == TransactionState::Locked, ErrorCode::InvalidState);
+        self.confirmations = self.confirmations
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        if self.confirmations >= min_confirmations {
+            self.state = TransactionState::Relayed;
+        }
+        Ok(())
+    }
+
+    /// Record a relayer's view of the source block and its depth
+    ///
+    /// The first attestation of a round pins the block; later ones must name
+    /// the same block. The tracked depth only grows.
+    pub fn attest_source_block(
+        &mut self,
+        source_block: u64,
+        source_block_hash: [u8; 32],
+        finality_depth: u64,
+    ) -> Result<()> {
+        require!(self.state == TransactionState::Locked, ErrorCode::InvalidState);
+        require!(
+            source_block_hash != [0; 32] && source_block_hash != self.reverted_block_hash,
+            ErrorCode::SourceBlockMismatch
+        );
+        if self.source_block_hash == [0; 32] {
+            self.source_block = source_block;
+            self.source_block_hash = source_block_hash;
+        }
+        require!(
+            self.source_block == source_block && self.source_block_hash == source_block_hash,
+            ErrorCode::SourceBlockMismatch
+        );
+        self.finality_depth = self.finality_depth.max(finality_depth);
+        Ok(())
+    }
+
+    /// Count one reorg vote
+    ///
+    /// Once `required_votes` relayers agree, the round's confirmations are
+    /// dropped, the attested block is marked reverted and a new round starts;
+    /// returns the dropped confirmations.
+    pub fn add_revert_vote(&mut self, required_votes: u8) -> Result<Option<u8>> {
+        require!(self.state == TransactionState::Locked, ErrorCode::AlreadyFinalized);
+        self.revert_votes = self.revert_votes
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        if self.revert_votes < required_votes {
+            return Ok(None);
+        }
+
+        let dropped_confirmations = self.confirmations;
+        self.reverted_block_hash = self.source_block_hash;
+        self.source_block = 0;
+        self.source_block_hash = [0; 32];
+        self.confirmations = 0;
+        self.finality_depth = 0;
+        self.revert_votes = 0;
+        self.attestation_round = self.attestation_round
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(Some(dropped_confirmations))
+    }
+
+    /// Unlocks may only pay the recipient committed at lock time
+    ///
+    /// Stealth locks commit to the stealth address itself; other locks to
+    /// `recipient_commitment(recipient, blinding)`.
+    pub fn check_recipient(&self, recipient: &Pubkey, blinding: &[u8; 32]) -> Result<()> {
+        if self.stealth.is_some() {
+            require!(
+                recipient.to_bytes() == self.recipient_commitment,
+                ErrorCode::StealthRecipientMismatch
+            );
+        } else {
+            require!(
+                recipient_commitment(recipient, blinding) == self.recipient_commitment,
+                ErrorCode::RecipientMismatch
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Outbound transfers locked during one epoch, as an append-only Merkle tree
+///
+/// Only the right-most frontier of the tree is stored; the root is computed
+/// once when the epoch is finalized.
+#[account]
+pub struct OutboundEpoch {
+    pub epoch_id: u64,
+    pub frontier: [[u8; 32]; OUTBOUND_TREE_DEPTH],
+    pub leaf_count: u32,
+    pub started_at: i64,
+    pub finalized: bool,
+    pub finalized_at: i64,
+    pub root: [u8; 32],
+    /// Set when one of its locks is reclaimed; the root is then never published
+    pub withheld: bool,
+}
+
+impl OutboundEpoch {
+    pub const LEN: usize = 8 + 32 * OUTBOUND_TREE_DEPTH + 4 + 8 + 1 + 8 + 32 + 1;
+
+    /// Set up a freshly created epoch account
+    pub fn open_if_new(&mut self, epoch_id: u64, now: i64) {
+        if self.started_at == 0 {
+            self.epoch_id = epoch_id;
+            self.started_at = now;
+        }
+    }
+
+    /// Whether the epoch holds `MAX_EPOCH_LEAVES` transfers
+    pub fn is_full(&self) -> bool {
+        self.leaf_count >= MAX_EPOCH_LEAVES
+    }
+
+    /// Append a leaf and return its index
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<u32> {
+        require!(!self.finalized, ErrorCode::EpochFinalized);
+        require!(!self.is_full(), ErrorCode::EpochFull);
+
+        let index = self.leaf_count;
+        let mut node = leaf;
+        let mut size = index + 1;
+        for level in 0..OUTBOUND_TREE_DEPTH {
+            if size & 1 == 1 {
+                self.frontier[level] = node;
+                break;
+            }
+            node = outbound_node(&self.frontier[level], &node);
+            size >>= 1;
+        }
+
+        self.leaf_count = index + 1;
+        Ok(index)
+    }
+
+    /// Close the epoch, fixing its root unless a lock was reclaimed from it
+    pub fn finalize(&mut self, now: i64) {
+        if !self.withheld {
+            self.root = self.compute_root();
+        }
+        self.finalized = true;
+        self.finalized_at = now;
+    }
+
+    /// Root of the tree, with empty leaves as zero hashes
+    pub fn compute_root(&self) -> [u8; 32] {
+        let zeros = outbound_zero_hashes();
+        let mut node = zeros[0];
+        let mut size = self.leaf_count;
+        for (sibling, zero) in self.frontier.iter().zip(zeros.iter()) {
+            node = if size & 1 == 1 {
+                outbound_node(sibling, &node)
+            } else {
+                outbound_node(&node, zero)
+            };
+            size >>= 1;
+        }
+        node
+    }
+}
+
+#[account]
+pub struct NullifierAccount {
+    pub nullifier: [u8; 32],
+    pub used: bool,
+    pub timestamp: i64,
+}
+
+impl NullifierAccount {
+    pub const LEN: usize = 32 + 1 + 8;
+}
+
+/// One relayer's finality attestation for one transaction
+#[account]
+pub struct Attestation {
+    pub bridge_tx: Pubkey,
+    pub relayer: Pubkey,
+    pub round: u32,
+    pub finality_depth: u64,
+    /// Whether this attestation already added a confirmation
+    pub counted: bool,
+    pub attested_at: i64,
+    /// Whether this relayer already voted to revert the current round
+    pub revert_voted: bool,
+}
+
+impl Attestation {
+    pub const LEN: usize = 32 + 32 + 4 + 8 + 1 + 8 + 1;
+
+    /// Start over when the transaction moved to a new attestation round
+    pub fn open_round(&mut self, bridge_tx: Pubkey, relayer: Pubkey, round: u32) {
+        if self.bridge_tx == Pubkey::default() || self.round != round {
+            self.bridge_tx = bridge_tx;
+            self.relayer = relayer;
+            self.round = round;
+            self.finality_depth = 0;
+            self.counted = false;
+            self.revert_voted = false;
+        }
+    }
+}
+
+#[account]
+pub struct Relayer {
+    pub authority: Pubkey,
+    pub active: bool,
+    pub total_relayed: u64,
+    pub slashed: bool,
+}
+
+impl Relayer {
+    pub const LEN: usize = 32 + 1 + 8 + 1;
+}
+
+#[account]
+pub struct AssociationSet {
+    pub provider: Pubkey,
+    pub root: [u8; 32],
+    pub root_history: [[u8; 32]; ASSOCIATION_ROOT_HISTORY],
+    pub root_index: u8,
+    pub updated_at: i64,
+    pub active: bool,
+}
+
+impl AssociationSet {
+    pub const LEN: usize = 32 + 32 + 32 * ASSOCIATION_ROOT_HISTORY + 1 + 8 + 1;
+
+    /// Accept the current root or any of the recently replaced ones, so
+    /// proofs generated just before a root update still verify
+    pub fn is_known_root(&self, root: &[u8; 32]) -> bool {
+        if *root == [0; 32] {
+            return false;
+        }
+        self.root == *root || self.root_history.iter().any(|r| r == root)
+    }
+
+    pub fn push_root(&mut self, new_root: [u8; 32]) {
+        let index = self.root_index as usize % ASSOCIATION_ROOT_HISTORY;
+        self.root_history[index] = self.root;
+        self.root_index = ((index + 1) % ASSOCIATION_ROOT_HISTORY) as u8;
+        self.root = new_root;
+    }
+}
+
+// ========== ENUMS ==========
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum TransactionState {
+    Pending,
+    Locked,
+    Relayed,
+    Completed,
+    Refunded,
+    Failed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum PauseScope {
+    Bridge,
+    Chain,
+    Token,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ProposalState {
+    Queued,
+    Executed,
+    Cancelled,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum GovernanceAction {
+    UpdateFee { new_fee: u16 },
+    UpdateMinConfirmations { min_confirmations: u8 },
+    SetAuditorKey { auditor_viewing_key: [u8; 32] },
+    UpdateGovernanceDelay { delay: i64 },
+    SetChainSupported { chain_id: u64, supported: bool },
+    SetRelayerActive { relayer: Pubkey, active: bool },
+    AddRelayer { relayer_authority: Pubkey },
+    RegisterAssociationSet { provider: Pubkey },
+    RegisterChain { chain_id: u64 },
+    SetOwners { owners: Vec<Pubkey>, threshold: u8 },
+    Unpause,
+    SetAssociationSetActive { association_set: Pubkey, active: bool },
+    RegisterToken { mint: Pubkey, vault: Pubkey },
+    SetGuardian { guardian: Pubkey, active: bool },
+    SetPauseFlags { scope: PauseScope, target: Pubkey, flags: u8 },
+    ProposeAuthority { new_authority: Pubkey },
+    CancelAuthorityTransfer,
+    UpdateEmergencyDelay { delay: i64 },
+    EmergencySweep { mint: Pubkey, destination: Pubkey, amount: u64 },
+    UpdateEpochDuration { duration: i64 },
+    SetChainFinality { chain_id: u64, finality_depth: u64 },
+    SetVerifyingKey { verifying_key_hash: [u8; 32] },
+}
+
+impl GovernanceAction {
+    // Discriminant + largest variant (SetOwners)
+    pub const LEN: usize = 1 + (4 + 32 * MAX_OWNERS) + 1;
+
+    /// Seconds a queued action waits before it may execute; unpausing and
+    /// cancelling an authority transfer only need the owners' threshold, so
+    /// an emergency pause or a wrong transfer can be undone at once
+    pub fn timelock(&self, governance_delay: i64) -> i64 {
+        match self {
+            GovernanceAction::Unpause | GovernanceAction::CancelAuthorityTransfer => 0,
+            _ => governance_delay,
+        }
+    }
+}
+
+// ========== STRUCTS ==========
+
+/// Stealth payment data published with a lock
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StealthMeta {
+    /// Sender's one-time ed25519 public key R = r*G
+    pub ephemeral_pubkey: [u8; 32],
+    /// First byte of the shared-secret hash, lets scanners skip most locks
+    pub view_tag: u8,
+}
+
+impl StealthMeta {
+    pub const LEN: usize = 32 + 1;
+}
+
+/// Note encrypted with ChaCha20-Poly1305 under a random content key; the
+/// content key is wrapped via X25519 for the recipient and the auditor
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EncryptedNote {
+    pub ephemeral_pubkey: [u8; 32],
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+    pub recipient_wrapped_key: [u8; 48],
+    pub auditor_wrapped_key: [u8; 48],
+}
+
+impl EncryptedNote {
+    pub const MAX_CIPHERTEXT_LEN: usize = 256;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchLockItem {
+    pub amount: u64,
+    /// As in `lock_assets`: a recipient commitment, or the stealth address
+    /// when `stealth` is set
+    pub recipient_commitment: [u8; 32],
+    pub stealth: Option<StealthMeta>,
+    pub encrypted_note: Option<EncryptedNote>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchUnlockItem {
+    pub proof: ZkProof,
+    pub nullifier: [u8; 32],
+    /// Opens the transaction's `recipient_commitment`
+    pub recipient_blinding: [u8; 32],
+}
+
+/// Public inputs of a standard unlock proof
+pub struct ProofPublicInputs {
+    pub commitment: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub amount: u64,
+    pub recipient: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ZkProof {
+    pub a: [u8; 64],
+    pub b: [u8; 128],
+    pub c: [u8; 64],
+}
+
+// ========== EVENTS ==========
+
+#[event]
+pub struct AssetLocked {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub tx_id: [u8; 32],
+    pub sender: Pubkey,
+    pub mint: Pubkey,
+    pub recipient_commitment: [u8; 32],
+    pub source_chain: u64,
+    pub target_chain: u64,
+    pub amount: u64,
+    pub commitment: [u8; 32],
+    pub stealth: Option<StealthMeta>,
+    pub encrypted_note: Option<EncryptedNote>,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AssetUnlocked {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub tx_id: [u8; 32],
+    pub recipient_commitment: [u8; 32],
+    pub amount: u64,
+    pub nullifier: [u8; 32],
+    pub slot: u64,
+}
+
+#[event]
+pub struct TransactionRelayed {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub tx_id: [u8; 32],
+    pub relayer: Pubkey,
+    pub confirmations: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct FinalityAttested {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub tx_id: [u8; 32],
+    pub relayer: Pubkey,
+    pub source_block: u64,
+    pub finality_depth: u64,
+    pub required_depth: u64,
+    pub round: u32,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RevertVoteCast {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub tx_id: [u8; 32],
+    pub relayer: Pubkey,
+    pub observed_block_hash: [u8; 32],
+    pub votes: u8,
+    pub required_votes: u8,
+    pub round: u32,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AttestationReverted {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub tx_id: [u8; 32],
+    pub relayer: Pubkey,
+    pub source_block: u64,
+    pub source_block_hash: [u8; 32],
+    pub observed_block_hash: [u8; 32],
+    pub dropped_confirmations: u8,
+    pub new_round: u32,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RelayerAdded {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub relayer: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct OutboundMessageQueued {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub tx_id: [u8; 32],
+    pub epoch: u64,
+    pub leaf_index: u32,
+    pub leaf: [u8; 32],
+    pub slot: u64,
+}
+
+#[event]
+pub struct EpochRootPublished {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub epoch: u64,
+    pub root: [u8; 32],
+    pub leaf_count: u32,
+    pub started_at: i64,
+    pub finalized_at: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct EmergencyReclaimed {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub tx_id: [u8; 32],
+    pub sender: Pubkey,
+    pub amount: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SurplusSwept {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub vault_balance: u64,
+    pub liabilities: u64,
+    pub slot: u64,
+}
+
+// Admin events below carry `version` (EVENT_SCHEMA_VERSION), the signing
+// `actor` and the `slot`, so an indexer can rebuild config history from logs.
+// Fields are only ever appended; a breaking change bumps the version.
+
+#[event]
+pub struct BridgeInitialized {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub min_confirmations: u8,
+    pub bridge_fee: u16,
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+    pub governance_delay: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct FeeUpdated {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub old_fee: u16,
+    pub new_fee: u16,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MinConfirmationsUpdated {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub old_min_confirmations: u8,
+    pub new_min_confirmations: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct GovernanceDelayUpdated {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub old_delay: i64,
+    pub new_delay: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct EmergencyDelayUpdated {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub old_delay: i64,
+    pub new_delay: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct EpochDurationUpdated {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub old_duration: i64,
+    pub new_duration: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ChainRegistered {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub chain_id: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ChainSupportUpdated {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub chain_id: u64,
+    pub old_supported: bool,
+    pub new_supported: bool,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ChainFinalityUpdated {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub chain_id: u64,
+    pub old_finality_depth: u64,
+    pub new_finality_depth: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct TokenRegistered {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RelayerStatusChanged {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub relayer: Pubkey,
+    pub old_active: bool,
+    pub new_active: bool,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AuditorKeyUpdated {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub old_key: [u8; 32],
+    pub new_key: [u8; 32],
+    pub slot: u64,
+}
+
+#[event]
+pub struct VerifyingKeyUpdated {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub old_hash: [u8; 32],
+    pub new_hash: [u8; 32],
+    pub slot: u64,
+}
+
+#[event]
+pub struct BridgePaused {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct BridgeUnpaused {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PauseFlagsUpdated {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub scope: PauseScope,
+    pub target: Pubkey,
+    pub old_flags: u8,
+    pub new_flags: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AssociationSetStatusChanged {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub association_set: Pubkey,
+    pub active: bool,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ReservesAudited {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub vault_balance: u64,
+    pub liabilities: u64,
+    pub shortfall: u64,
+    pub paused: bool,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub current_authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub authority: Pubkey,
+    pub cancelled_authority: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct GuardianUpdated {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub guardian: Pubkey,
+    pub active: bool,
+    pub slot: u64,
+}
+
+#[event]
+pub struct GuardianPaused {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub guardian: Pubkey,
+    pub scope: PauseScope,
+    pub flags: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProposalQueued {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub action: GovernanceAction,
+    pub eta: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub proposal_id: u64,
+    pub approvals: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub proposal_id: u64,
+    pub action: GovernanceAction,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProposalCancelApproved {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub proposal_id: u64,
+    pub votes: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProposalCancelled {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub proposal_id: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct OwnersChanged {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+    pub owner_set_seqno: u32,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RelayerFeePaid {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub tx_id: [u8; 32],
+    pub relayer: Pubkey,
+    pub relayer_fee: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AssociationSetRegistered {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub association_set: Pubkey,
+    pub provider: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AssociationRootUpdated {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub association_set: Pubkey,
+    pub old_root: [u8; 32],
+    pub new_root: [u8; 32],
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AssociationProofVerified {
+    pub version: u8,
+    pub actor: Pubkey,
+    pub tx_id: [u8; 32],
+    pub association_set: Pubkey,
+    pub association_root: [u8; 32],
+    pub slot: u64,
+}
+
+// ========== ERRORS ==========
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Bridge is paused")]
+    BridgePaused,
+
+    #[msg("Invalid transaction state")]
+    InvalidState,
+
+    #[msg("Insufficient confirmations")]
+    InsufficientConfirmations,
+
+    #[msg("Nullifier already used")]
+    NullifierUsed,
+
+    #[msg("Invalid zk-SNARK proof")]
+    InvalidProof,
+
+    #[msg("Not an active relayer")]
+    NotActiveRelayer,
+
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+
+    #[msg("Fee too high (max 10%)")]
+    FeeTooHigh,
+
+    #[msg("Association set is inactive")]
+    AssociationSetInactive,
+
+    #[msg("Unknown association root")]
+    UnknownAssociationRoot,
+
+    #[msg("Not the association set provider")]
+    NotAssociationProvider,
+
+    #[msg("Relayer fee exceeds unlocked amount")]
+    RelayerFeeTooHigh,
+
+    #[msg("Recipient does not match the committed recipient")]
+    RecipientMismatch,
+
+    #[msg("Recipient does not match stealth address")]
+    StealthRecipientMismatch,
+
+    #[msg("Encrypted note too large")]
+    NoteTooLarge,
+
+    #[msg("Lock needs an encrypted note wrapped for the auditor")]
+    AuditorNoteRequired,
+
+    #[msg("Target chain not supported")]
+    UnsupportedChain,
+
+    #[msg("Proposal is not queued")]
+    ProposalNotQueued,
+
+    #[msg("Timelock has not elapsed")]
+    TimelockNotElapsed,
+
+    #[msg("Proposal expired")]
+    ProposalExpired,
+
+    #[msg("Proposal action does not match this instruction")]
+    ProposalActionMismatch,
+
+    #[msg("Invalid governance delay")]
+    InvalidGovernanceDelay,
+
+    #[msg("Missing or mismatched account for governance action")]
+    MissingGovernanceAccount,
+
+    #[msg("Minimum confirmations must be at least one")]
+    InvalidMinConfirmations,
+
+    #[msg("Proposal already executed")]
+    AlreadyExecuted,
+
+    #[msg("Chain is paused")]
+    ChainPaused,
+
+    #[msg("Token is paused")]
+    TokenPaused,
+
+    #[msg("Not an active guardian")]
+    NotGuardian,
+
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+
+    #[msg("Missing account for pause scope")]
+    MissingPauseAccount,
+
+    #[msg("Vault does not match registered token")]
+    InvalidVault,
+
+    #[msg("Invalid authority")]
+    InvalidAuthority,
+
+    #[msg("No pending authority transfer")]
+    NoPendingAuthority,
+
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+
+    #[msg("Emergency mode is not active")]
+    EmergencyModeInactive,
+
+    #[msg("Amount exceeds surplus above liabilities")]
+    InsufficientSurplus,
+
+    #[msg("Signer is not the transaction sender")]
+    NotSender,
+
+    #[msg("Expected (token_config, vault) account pairs")]
+    InvalidAuditAccounts,
+
+    #[msg("Invalid batch size")]
+    InvalidBatchSize,
+
+    #[msg("Invalid or mismatched batch accounts")]
+    InvalidBatchAccounts,
+
+    #[msg("Outbound epoch already finalized")]
+    EpochFinalized,
+
+    #[msg("Outbound epoch is full")]
+    EpochFull,
+
+    #[msg("Outbound epoch still open")]
+    EpochNotElapsed,
+
+    #[msg("Invalid epoch duration")]
+    InvalidEpochDuration,
+
+    #[msg("Source block does not match the transaction")]
+    SourceBlockMismatch,
+
+    #[msg("Relayer already voted to revert this round")]
+    RevertAlreadyVoted,
+
+    #[msg("Transaction already finalized")]
+    AlreadyFinalized,
+
+    #[msg("Invalid finality depth")]
+    InvalidFinalityDepth,
+
+    #[msg("SlotHashes sysvar is empty or malformed")]
+    InvalidSlotHashes,
+
+    #[msg("Outbound epoch does not hold this transfer")]
+    InvalidEpoch,
+}
+
+// ========== CONSTANTS ==========
+
+/// Number of previous association roots accepted alongside the current one
+pub const ASSOCIATION_ROOT_HISTORY: usize = 8;
+
+/// Shortest allowed governance delay (1 day)
+pub const MIN_GOVERNANCE_DELAY: i64 = 24 * 60 * 60;
+
+/// Longest allowed governance delay (30 days)
+pub const MAX_GOVERNANCE_DELAY: i64 = 30 * 24 * 60 * 60;
+
+/// Window after eta during which a proposal can still execute (14 days)
+pub const GOVERNANCE_GRACE_PERIOD: i64 = 14 * 24 * 60 * 60;
+
+/// Largest batch accepted by `lock_assets_batch` / `unlock_assets_batch`
+pub const MAX_BATCH_SIZE: usize = 8;
+
+/// Remaining accounts per item in `unlock_assets_batch`
+const BATCH_UNLOCK_ACCOUNTS: usize = 4;
+
+/// Depth of each outbound epoch tree
+pub const OUTBOUND_TREE_DEPTH: usize = 16;
+
+/// Transfers per outbound epoch
+///
+/// One short of a complete tree: the frontier only keeps nodes below the
+/// root, so the last leaf of a complete tree would have nowhere to go.
+pub const MAX_EPOCH_LEAVES: u32 = (1 << OUTBOUND_TREE_DEPTH) - 1;
+
+/// Default outbound epoch length (1 hour)
+pub const DEFAULT_EPOCH_DURATION: i64 = 60 * 60;
+
+/// Longest allowed outbound epoch (1 day)
+pub const MAX_EPOCH_DURATION: i64 = 24 * 60 * 60;
+
+/// Finality depth for newly registered chains, until governance sets one
+pub const DEFAULT_FINALITY_DEPTH: u64 = 32;
+
+/// Version of the event schema, emitted in every event
+pub const EVENT_SCHEMA_VERSION: u8 = 1;
+
+/// Default pause duration before emergency mode opens (7 days)
+pub const DEFAULT_EMERGENCY_DELAY: i64 = 7 * 24 * 60 * 60;
+
+/// Shortest emergency delay governance may set (1 day)
+pub const MIN_EMERGENCY_DELAY: i64 = 24 * 60 * 60;
+
+/// Pause flags, checked independently by each operation
+pub const PAUSE_LOCK: u8 = 1 << 0;
+pub const PAUSE_UNLOCK: u8 = 1 << 1;
+pub const PAUSE_RELAY: u8 = 1 << 2;
+pub const PAUSE_ALL: u8 = PAUSE_LOCK | PAUSE_UNLOCK | PAUSE_RELAY;
+
+// ========== HELPER FUNCTIONS ==========
+
+/// Reject `operation` if it is paused at bridge, chain or token level
+fn require_not_paused(
+    bridge: &Bridge,
+    chain_config: &ChainConfig,
+    token_config: &TokenConfig,
+    operation: u8,
+) -> Result<()> {
+    require!(
+        !bridge.paused && bridge.pause_flags & operation == 0,
+        ErrorCode::BridgePaused
+    );
+    require!(chain_config.pause_flags & operation == 0, ErrorCode::ChainPaused);
+    require!(token_config.pause_flags & operation == 0, ErrorCode::TokenPaused);
+    Ok(())
+}
+
+/// Settings stored at the `[b"chain", chain_id]` PDA `info`
+///
+/// Chains stay open until governance registers them, so only a registered
+/// chain can be disabled, paused or given its own finality depth.
+fn load_chain_config(info: &AccountInfo, chain_id: u64) -> Result<ChainConfig> {
+    if info.owner != &ID {
+        return Ok(ChainConfig::unregistered(chain_id));
+    }
+    let data = info.try_borrow_data()?;
+    ChainConfig::try_deserialize(&mut &data[..])
+}
+
+/// Amount left for the recipient once the relayer takes `relayer_fee`
+fn split_relayer_fee(amount: u64, relayer_fee: u64) -> Result<u64> {
+    amount
+        .checked_sub(relayer_fee)
+        .ok_or_else(|| error!(ErrorCode::RelayerFeeTooHigh))
+}
+
+/// Every proposal waits at least a day, and never more than 30 days
+fn validate_governance_delay(delay: i64) -> Result<()> {
+    require!(
+        (MIN_GOVERNANCE_DELAY..=MAX_GOVERNANCE_DELAY).contains(&delay),
+        ErrorCode::InvalidGovernanceDelay
+    );
+    Ok(())
+}
+
+/// Account whose pause flags `scope` targets, for events
+fn pause_target(
+    scope: &PauseScope,
+    bridge: &Account<'_, Bridge>,
+    chain_config: &Option<Account<'_, ChainConfig>>,
+    token_config: &Option<Account<'_, TokenConfig>>,
+) -> Pubkey {
+    match scope {
+        PauseScope::Bridge => bridge.key(),
+        PauseScope::Chain => chain_config.as_ref().map(|c| c.key()).unwrap_or_default(),
+        PauseScope::Token => token_config.as_ref().map(|t| t.key()).unwrap_or_default(),
+    }
+}
+
+/// Pause flags targeted by `scope`
+fn pause_flags_mut<'a>(
+    scope: &PauseScope,
+    bridge: &'a mut Bridge,
+    chain_config: &'a mut Option<Account<'_, ChainConfig>>,
+    token_config: &'a mut Option<Account<'_, TokenConfig>>,
+) -> Result<&'a mut u8> {
+    match scope {
+        PauseScope::Bridge => Ok(&mut bridge.pause_flags),
+        PauseScope::Chain => chain_config
+            .as_mut()
+            .map(|c| &mut c.pause_flags)
+            .ok_or_else(|| error!(ErrorCode::MissingPauseAccount)),
+        PauseScope::Token => token_config
+            .as_mut()
+            .map(|t| &mut t.pause_flags)
+            .ok_or_else(|| error!(ErrorCode::MissingPauseAccount)),
+    }
+}
+
+/// Generate Pedersen commitment
+fn generate_commitment(recipient: &[u8; 32], amount: u64) -> Result<[u8; 32]> {
+    let mut data = Vec::new();
+    data.extend_from_slice(recipient);
+    data.extend_from_slice(&amount.to_le_bytes());
+    
+    let hash = keccak::hash(&data);
+    Ok(hash.to_bytes())
+}
+
+/// Commitment to an unlock recipient: `keccak256(recipient || blinding)`
+///
+/// Passed as `recipient_commitment` to `lock_assets` for non-stealth locks.
+pub fn recipient_commitment(recipient: &Pubkey, blinding: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[recipient.as_ref(), blinding]).to_bytes()
+}
+
+/// Generate transaction ID
+fn generate_tx_id(
+    sender: &Pubkey,
+    target_chain: u64,
+    recipient: &[u8; 32],
+    timestamp: i64,
+) -> [u8; 32] {
+    let mut data = Vec::new();
+    data.extend_from_slice(sender.as_ref());
+    data.extend_from_slice(&target_chain.to_le_bytes());
+    data.extend_from_slice(recipient);
+    data.extend_from_slice(&timestamp.to_le_bytes());
+    
+    let hash = keccak::hash(&data);
+    hash.to_bytes()
+}
+
+/// A batch holds between 1 and `MAX_BATCH_SIZE` items
+fn check_batch_size(items: usize) -> Result<()> {
+    require!(items > 0 && items <= MAX_BATCH_SIZE, ErrorCode::InvalidBatchSize);
+    Ok(())
+}
+
+/// No two items of an unlock batch may spend the same nullifier
+fn check_unique_nullifiers(items: &[BatchUnlockItem]) -> Result<()> {
+    for (index, item) in items.iter().enumerate() {
+        require!(
+            items[..index].iter().all(|other| other.nullifier != item.nullifier),
+            ErrorCode::NullifierUsed
+        );
+    }
+    Ok(())
+}
+
+/// Derive a distinct transaction ID for each item of a batch
+fn generate_batch_tx_id(base_id: &[u8; 32], index: u8) -> [u8; 32] {
+    keccak::hashv(&[base_id.as_ref(), &[index]]).to_bytes()
+}
+
+/// Outbound Merkle leaf for a locked transfer
+///
+/// `keccak256(0x00 || id || source_chain || target_chain || mint ||
+/// recipient_commitment || amount || commitment)` with big-endian integers,
+/// matching Solidity's `abi.encodePacked`.
+pub fn outbound_leaf(tx: &BridgeTransaction) -> [u8; 32] {
+    keccak::hashv(&[
+        &[0x00u8][..],
+        &tx.id,
+        &tx.source_chain.to_be_bytes(),
+        &tx.target_chain.to_be_bytes(),
+        tx.mint.as_ref(),
+        &tx.recipient_commitment,
+        &tx.amount.to_be_bytes(),
+        &tx.commitment,
+    ])
+    .to_bytes()
+}
+
+/// Inner node of an outbound tree: `keccak256(0x01 || left || right)`
+pub fn outbound_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[&[0x01u8][..], left, right]).to_bytes()
+}
+
+/// Root of an empty subtree at each level, starting from the zero leaf
+pub fn outbound_zero_hashes() -> [[u8; 32]; OUTBOUND_TREE_DEPTH] {
+    let mut zeros = [[0u8; 32]; OUTBOUND_TREE_DEPTH];
+    for level in 1..OUTBOUND_TREE_DEPTH {
+        zeros[level] = outbound_node(&zeros[level - 1], &zeros[level - 1]);
+    }
+    zeros
+}
+
+/// Create a program-owned account funded by `payer`
+fn create_program_account<'info>(
+    payer: &AccountInfo<'info>,
+    new_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+) -> Result<()> {
+    let lamports = Rent::get()?.minimum_balance(space);
+    system_program::create_account(
+        CpiContext::new(
+            system_program.clone(),
+            system_program::CreateAccount {
+                from: payer.clone(),
+                to: new_account.clone(),
+            },
+        ),
+        lamports,
+        space as u64,
+        &crate::ID,
+    )
+}
+
+/// Serialize an account (with discriminator) into freshly created storage
+fn write_program_account<T: AccountSerialize>(info: &AccountInfo, account: &T) -> Result<()> {
+    let mut data = info.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    account.try_serialize(&mut writer)
+}
+
+/// Transfer from the bridge vault, signed by the bridge authority PDA
+fn transfer_from_vault<'info>(
+    vault: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    bridge_authority: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    let cpi_accounts = Transfer {
+        from: vault.clone(),
+        to: destination.clone(),
+        authority: bridge_authority.clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)
+}
+
+/// Check a lock's encrypted note against the size limit and the auditor key
+///
+/// Once `auditor_viewing_key` is set, every lock must carry a note whose
+/// content key is also wrapped for the auditor.
+fn check_encrypted_note(bridge: &Bridge, note: Option<&EncryptedNote>) -> Result<()> {
+    if let Some(note) = note {
+        require!(
+            note.ciphertext.len() <= EncryptedNote::MAX_CIPHERTEXT_LEN,
+            ErrorCode::NoteTooLarge
+        );
+    }
+    if bridge.auditor_viewing_key != [0; 32] {
+        require!(
+            note.is_some_and(|note| note.auditor_wrapped_key != [0; 48]),
+            ErrorCode::AuditorNoteRequired
+        );
+    }
+    Ok(())
+}
+
+/// Accounts that pay out of a token vault as the bridge authority PDA
+struct UnlockVault<'info> {
+    vault: AccountInfo<'info>,
+    bridge_authority: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    authority_bump: u8,
+    /// Signer the unlock event is attributed to
+    actor: Pubkey,
+}
+
+/// Transaction checks shared by every unlock path
+///
+/// Only transactions relayed to `min_confirmations` can be unlocked, and
+/// only to the recipient committed at lock time.
+/// Check an audited `(token_config, vault)` pair by address, not position
+fn check_audit_pair(
+    program_id: &Pubkey,
+    token_config_key: &Pubkey,
+    token_config: &TokenConfig,
+    vault_key: &Pubkey,
+) -> Result<()> {
+    let (expected, _) =
+        Pubkey::find_program_address(&[b"token", token_config.mint.as_ref()], program_id);
+    require_keys_eq!(*token_config_key, expected, ErrorCode::InvalidAuditAccounts);
+    require_keys_eq!(token_config.vault, *vault_key, ErrorCode::InvalidVault);
+    Ok(())
+}
+
+fn check_unlock_state(
+    min_confirmations: u8,
+    tx: &BridgeTransaction,
+    recipient: &Pubkey,
+    recipient_blinding: &[u8; 32],
+) -> Result<()> {
+    require!(tx.state == TransactionState::Relayed, ErrorCode::InvalidState);
+    require!(tx.confirmations >= min_confirmations, ErrorCode::InsufficientConfirmations);
+    tx.check_recipient(recipient, recipient_blinding)
+}
+
+/// Checks shared by the single-transaction unlock paths
+fn check_unlock(
+    bridge: &Bridge,
+    tx: &BridgeTransaction,
+    nullifier_account: &NullifierAccount,
+    recipient: &Pubkey,
+    recipient_blinding: &[u8; 32],
+) -> Result<()> {
+    check_unlock_state(bridge.min_confirmations, tx, recipient, recipient_blinding)?;
+    require!(!nullifier_account.used, ErrorCode::NullifierUsed);
+    Ok(())
+}
+
+/// Consume the nullifier, pay out of the vault and complete the transaction
+///
+/// `payouts` splits `tx.amount` across destination token accounts; zero
+/// shares are skipped.
+fn complete_unlock<'info>(
+    bridge: &mut Bridge,
+    token_config: &mut TokenConfig,
+    tx: &mut BridgeTransaction,
+    nullifier_account: &mut NullifierAccount,
+    nullifier: [u8; 32],
+    vault: &UnlockVault<'info>,
+    payouts: &[(AccountInfo<'info>, u64)],
+) -> Result<()> {
+    // Mark nullifier as used
+    nullifier_account.nullifier = nullifier;
+    nullifier_account.used = true;
+    nullifier_account.timestamp = Clock::get()?.unix_timestamp;
+
+    // Update transaction
+    tx.nullifier = nullifier;
+    tx.state = TransactionState::Completed;
+
+    let authority_seeds = &[b"bridge_authority".as_ref(), &[vault.authority_bump]];
+    let signer = &[&authority_seeds[..]];
+    for (destination, amount) in payouts.iter().filter(|(_, amount)| *amount > 0) {
+        transfer_from_vault(
+            &vault.vault,
+            destination,
+            &vault.bridge_authority,
+            &vault.token_program,
+            signer,
+            *amount,
+        )?;
+    }
+
+    bridge.total_unlocked = bridge.total_unlocked
+        .checked_add(tx.amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    token_config.total_unlocked = token_config.total_unlocked
+        .checked_add(tx.amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    emit!(AssetUnlocked {
+        version: EVENT_SCHEMA_VERSION,
+        actor: vault.actor,
+        tx_id: tx.id,
+        recipient_commitment: tx.recipient_commitment,
+        amount: tx.amount,
+        nullifier,
+        slot: Clock::get()?.slot,
+    });
+
+    Ok(())
+}
+
+/// Verify zk-SNARK proof
+/// In production, integrate with arkworks or bellman
+///
+/// `verifying_key_hash` is the bridge's governed key; it leads the public
+/// inputs so a proof is only ever checked against the current key.
+fn verify_proof(
+    verifying_key_hash: &[u8; 32],
+    proof: &ZkProof,
+    commitment: &[u8; 32],
+    nullifier: &[u8; 32],
+    amount: u64,
+    recipient: &Pubkey,
+) -> Result<bool> {
+    // Mock verification - in production, use proper zk-SNARK verification
+    // This would integrate with Groth16 verifier
+    
+    let mut public_inputs = Vec::new();
+    public_inputs.extend_from_slice(verifying_key_hash);
+    public_inputs.extend_from_slice(commitment);
+    public_inputs.extend_from_slice(nullifier);
+    public_inputs.extend_from_slice(&amount.to_le_bytes());
+    public_inputs.extend_from_slice(recipient.as_ref());
+    
+    // Verify proof format is valid
+    let valid = proof.a.len() == 64 && proof.b.len() == 128 && proof.c.len() == 64;
+    
+    Ok(valid)
+}
+
+
+/// Verify relayed-unlock zk-SNARK proof
+/// Public inputs: verifying key hash, the standard unlock inputs, relayer,
+/// relayer fee
+fn verify_relayed_proof(
+    verifying_key_hash: &[u8; 32],
+    proof: &ZkProof,
+    inputs: &ProofPublicInputs,
+    relayer: &Pubkey,
+    relayer_fee: u64,
+) -> Result<bool> {
+    // Mock verification - in production, use proper zk-SNARK verification
+
+    let mut public_inputs = Vec::new();
+    public_inputs.extend_from_slice(verifying_key_hash);
+    public_inputs.extend_from_slice(&inputs.commitment);
+    public_inputs.extend_from_slice(&inputs.nullifier);
+    public_inputs.extend_from_slice(&inputs.amount.to_le_bytes());
+    public_inputs.extend_from_slice(inputs.recipient.as_ref());
+    public_inputs.extend_from_slice(relayer.as_ref());
+    public_inputs.extend_from_slice(&relayer_fee.to_le_bytes());
+
+    // Verify proof format is valid
+    let valid = proof.a.len() == 64 && proof.b.len() == 128 && proof.c.len() == 64;
+
+    Ok(valid)
+}
+
+/// Verify association-set zk-SNARK proof
+/// Public inputs: verifying key hash, deposit commitment, association root,
+/// nullifier, amount, recipient
+fn verify_association_proof(
+    verifying_key_hash: &[u8; 32],
+    proof: &ZkProof,
+    deposit_root: &[u8; 32],
+    association_root: &[u8; 32],
+    nullifier: &[u8; 32],
+    amount: u64,
+    recipient: &Pubkey,
+) -> Result<bool> {
+    // Mock verification - in production, use proper zk-SNARK verification
+    // against the association-set verifying key
+
+    let mut public_inputs = Vec::new();
+    public_inputs.extend_from_slice(verifying_key_hash);
+    public_inputs.extend_from_slice(deposit_root);
+    public_inputs.extend_from_slice(association_root);
+    public_inputs.extend_from_slice(nullifier);
+    public_inputs.extend_from_slice(&amount.to_le_bytes());
+    public_inputs.extend_from_slice(recipient.as_ref());
+
+    // Verify proof format is valid
+    let valid = proof.a.len() == 64 && proof.b.len() == 128 && proof.c.len() == 64;
+
+    Ok(valid)
+}
+
+/// Verify standard unlock proofs as one batch
+/// Public inputs: verifying key hash, then per proof: commitment, nullifier,
+/// amount, recipient
+///
+/// Like `verify_proof`, this is a mock that only checks each proof's format.
+fn verify_proof_batch(
+    verifying_key_hash: &[u8; 32],
+    proofs: &[&ZkProof],
+    inputs: &[ProofPublicInputs],
+) -> Result<bool> {
+    if proofs.is_empty() || proofs.len() != inputs.len() {
+        return Ok(false);
+    }
+
+    // Mock verification - in production, all proofs share the unlock
+    // verifying key, so their Groth16 equations are combined with the weights
+    // from `batch_proof_weights` into one multi-pairing of `n + 3` pairings
+    // instead of `4n`. The weights commit to every proof and input, so no
+    // proof in the batch could be crafted to cancel another's error.
+
+    let weights = batch_proof_weights(proofs, inputs);
+    let mut public_inputs = verifying_key_hash.to_vec();
+    for ((proof, input), weight) in proofs.iter().zip(inputs.iter()).zip(weights.iter()) {
+        public_inputs.extend_from_slice(weight);
+        public_inputs.extend_from_slice(&input.commitment);
+        public_inputs.extend_from_slice(&input.nullifier);
+        public_inputs.extend_from_slice(&input.amount.to_le_bytes());
+        public_inputs.extend_from_slice(input.recipient.as_ref());
+
+        // Verify proof format is valid
+        if !(proof.a.len() == 64 && proof.b.len() == 128 && proof.c.len() == 64) {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Random weight of each proof in a verification batch
+///
+/// `keccak256(seed || index)`, where the seed hashes every proof and its
+/// public inputs in order.
+fn batch_proof_weights(proofs: &[&ZkProof], inputs: &[ProofPublicInputs]) -> Vec<[u8; 32]> {
+    let mut transcript = Vec::new();
+    for (proof, input) in proofs.iter().zip(inputs.iter()) {
+        transcript.extend_from_slice(&proof.a);
+        transcript.extend_from_slice(&proof.b);
+        transcript.extend_from_slice(&proof.c);
+        transcript.extend_from_slice(&input.commitment);
+        transcript.extend_from_slice(&input.nullifier);
+        transcript.extend_from_slice(&input.amount.to_le_bytes());
+        transcript.extend_from_slice(input.recipient.as_ref());
+    }
+    let seed = keccak::hash(&transcript);
+
+    (0..proofs.len() as u32)
+        .map(|index| keccak::hashv(&[seed.as_ref(), &index.to_le_bytes()]).to_bytes())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multisig::MultisigError;
+    use std::collections::BTreeSet;
+
+    /// A transaction as `lock_assets` leaves it
+    fn locked_tx() -> BridgeTransaction {
+        BridgeTransaction {
+            id: [1; 32],
+            source_chain: 1,
+            target_chain: 2,
+            sender: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            recipient_commitment: [2; 32],
+            amount: 1_000,
+            commitment: generate_commitment(&[2; 32], 1_000).unwrap(),
+            nullifier: [0; 32],
+            timestamp: 0,
+            state: TransactionState::Locked,
+            confirmations: 0,
+            stealth: None,
+            source_block: 0,
+            source_block_hash: [0; 32],
+            reverted_block_hash: [0; 32],
+            finality_depth: 0,
+            attestation_round: 0,
+            revert_votes: 0,
+            outbound_epoch: 0,
+            leaf_index: 0,
+        }
+    }
+
+    #[test]
+    fn relayed_transaction_unlocks_at_threshold() {
+        let recipient = Pubkey::new_unique();
+        let mut tx = locked_tx();
+        tx.recipient_commitment = recipient_commitment(&recipient, &[0; 32]);
+
+        tx.add_confirmation(2).unwrap();
+        assert!(tx.state == TransactionState::Locked);
+        assert_eq!(
+            check_unlock_state(2, &tx, &recipient, &[0; 32]).unwrap_err(),
+            ErrorCode::InvalidState.into()
+        );
+
+        tx.add_confirmation(2).unwrap();
+        assert!(tx.state == TransactionState::Relayed);
+        check_unlock_state(2, &tx, &recipient, &[0; 32]).unwrap();
+    }
+
+    #[test]
+    fn relayed_transaction_takes_no_more_confirmations() {
+        let mut tx = locked_tx();
+        tx.add_confirmation(1).unwrap();
+        assert_eq!(tx.add_confirmation(1).unwrap_err(), ErrorCode::InvalidState.into());
+        assert_eq!(tx.confirmations, 1);
+    }
+
+    #[test]
+    fn first_attestation_pins_the_source_block() {
+        let mut tx = locked_tx();
+        assert_eq!(
+            tx.attest_source_block(10, [0; 32], 5).unwrap_err(),
+            ErrorCode::SourceBlockMismatch.into()
+        );
+
+        tx.attest_source_block(10, [3; 32], 5).unwrap();
+        assert_eq!((tx.source_block, tx.source_block_hash, tx.finality_depth), (10, [3; 32], 5));
+
+        for (block, hash) in [(11, [3; 32]), (10, [4; 32])] {
+            assert_eq!(
+                tx.attest_source_block(block, hash, 50).unwrap_err(),
+                ErrorCode::SourceBlockMismatch.into()
+            );
+        }
+
+        // A shallower report does not lower the depth
+        tx.attest_source_block(10, [3; 32], 2).unwrap();
+        assert_eq!(tx.finality_depth, 5);
+        tx.attest_source_block(10, [3; 32], 40).unwrap();
+        assert_eq!(tx.finality_depth, 40);
+    }
+
+    #[test]
+    fn revert_needs_a_quorum_of_votes() {
+        let mut tx = locked_tx();
+        tx.attest_source_block(10, [3; 32], 40).unwrap();
+        tx.add_confirmation(3).unwrap();
+        tx.add_confirmation(3).unwrap();
+
+        assert_eq!(tx.add_revert_vote(3).unwrap(), None);
+        assert_eq!(tx.add_revert_vote(3).unwrap(), None);
+        assert_eq!(tx.confirmations, 2);
+        assert_eq!(tx.attestation_round, 0);
+
+        assert_eq!(tx.add_revert_vote(3).unwrap(), Some(2));
+        assert_eq!(tx.confirmations, 0);
+        assert_eq!(tx.revert_votes, 0);
+        assert_eq!(tx.attestation_round, 1);
+        assert_eq!((tx.source_block_hash, tx.finality_depth), ([0; 32], 0));
+    }
+
+    #[test]
+    fn reverted_block_cannot_be_attested_again() {
+        let mut tx = locked_tx();
+        tx.attest_source_block(10, [3; 32], 40).unwrap();
+        tx.add_revert_vote(1).unwrap();
+        assert_eq!(tx.reverted_block_hash, [3; 32]);
+
+        // However deep a relayer claims it is
+        assert_eq!(
+            tx.attest_source_block(10, [3; 32], u64::MAX).unwrap_err(),
+            ErrorCode::SourceBlockMismatch.into()
+        );
+
+        // The lock's block on the new fork can be attested
+        tx.attest_source_block(12, [5; 32], 1).unwrap();
+        assert_eq!((tx.source_block, tx.source_block_hash), (12, [5; 32]));
+    }
+
+    #[test]
+    fn attestation_resets_on_new_round() {
+        let (tx_key, relayer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut attestation = Attestation {
+            bridge_tx: Pubkey::default(),
+            relayer: Pubkey::default(),
+            round: 0,
+            finality_depth: 0,
+            counted: false,
+            attested_at: 0,
+            revert_voted: false,
+        };
+        attestation.open_round(tx_key, relayer, 0);
+        attestation.counted = true;
+        attestation.revert_voted = true;
+
+        attestation.open_round(tx_key, relayer, 0);
+        assert!(attestation.counted && attestation.revert_voted);
+
+        attestation.open_round(tx_key, relayer, 1);
+        assert!(!attestation.counted && !attestation.revert_voted);
+        assert_eq!(attestation.round, 1);
+    }
+
+    #[test]
+    fn association_roots_stay_known_for_the_history_window() {
+        let mut association_set = AssociationSet {
+            provider: Pubkey::new_unique(),
+            root: [0; 32],
+            root_history: [[0; 32]; ASSOCIATION_ROOT_HISTORY],
+            root_index: 0,
+            updated_at: 0,
+            active: true,
+        };
+        // The empty root never verifies
+        assert!(!association_set.is_known_root(&[0; 32]));
+
+        // The current root plus ASSOCIATION_ROOT_HISTORY replaced ones
+        let root = |n: usize| [n as u8 + 1; 32];
+        for n in 0..=ASSOCIATION_ROOT_HISTORY {
+            association_set.push_root(root(n));
+        }
+        assert!(!association_set.is_known_root(&[0; 32]));
+        for n in 0..=ASSOCIATION_ROOT_HISTORY {
+            assert!(association_set.is_known_root(&root(n)), "root {n}");
+        }
+
+        // The oldest root falls out once the history wraps
+        association_set.push_root(root(ASSOCIATION_ROOT_HISTORY + 1));
+        assert!(!association_set.is_known_root(&root(0)));
+        assert!(association_set.is_known_root(&root(1)));
+    }
+
+    #[test]
+    fn relayer_fee_comes_out_of_the_unlocked_amount() {
+        assert_eq!(split_relayer_fee(1_000, 30).unwrap(), 970);
+        assert_eq!(split_relayer_fee(1_000, 0).unwrap(), 1_000);
+        assert_eq!(split_relayer_fee(1_000, 1_000).unwrap(), 0);
+        assert_eq!(
+            split_relayer_fee(1_000, 1_001).unwrap_err(),
+            ErrorCode::RelayerFeeTooHigh.into()
+        );
+    }
+
+    #[test]
+    fn substituted_recipient_is_rejected() {
+        let (recipient, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let blinding = [7; 32];
+        let mut tx = locked_tx();
+        tx.recipient_commitment = recipient_commitment(&recipient, &blinding);
+        tx.add_confirmation(1).unwrap();
+
+        check_unlock_state(1, &tx, &recipient, &blinding).unwrap();
+        assert_eq!(
+            check_unlock_state(1, &tx, &other, &blinding).unwrap_err(),
+            ErrorCode::RecipientMismatch.into()
+        );
+        assert_eq!(
+            check_unlock_state(1, &tx, &recipient, &[8; 32]).unwrap_err(),
+            ErrorCode::RecipientMismatch.into()
+        );
+    }
+
+    #[test]
+    fn stealth_lock_pays_only_the_stealth_address() {
+        let stealth_address = Pubkey::new_unique();
+        let mut tx = locked_tx();
+        tx.recipient_commitment = stealth_address.to_bytes();
+        tx.stealth = Some(StealthMeta { ephemeral_pubkey: [9; 32], view_tag: 1 });
+        tx.add_confirmation(1).unwrap();
+
+        check_unlock_state(1, &tx, &stealth_address, &[0; 32]).unwrap();
+        assert_eq!(
+            check_unlock_state(1, &tx, &Pubkey::new_unique(), &[0; 32]).unwrap_err(),
+            ErrorCode::StealthRecipientMismatch.into()
+        );
+    }
+
+    fn bridge(auditor_viewing_key: [u8; 32]) -> Bridge {
+        Bridge {
+            authority: Pubkey::new_unique(),
+            pending_authority: Pubkey::default(),
+            min_confirmations: 1,
+            bridge_fee: 0,
+            total_locked: 0,
+            total_unlocked: 0,
+            paused: false,
+            pause_flags: 0,
+            paused_at: 0,
+            emergency_delay: DEFAULT_EMERGENCY_DELAY,
+            auditor_viewing_key,
+            governance_delay: MIN_GOVERNANCE_DELAY,
+            proposal_count: 0,
+            current_epoch: 0,
+            epoch_duration: DEFAULT_EPOCH_DURATION,
+            multisig: OwnerSet::new(
+                vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()],
+                2,
+            )
+            .unwrap(),
+            verifying_key_hash: [0; 32],
+        }
+    }
+
+    fn note(auditor_wrapped_key: [u8; 48]) -> EncryptedNote {
+        EncryptedNote {
+            ephemeral_pubkey: [1; 32],
+            nonce: [2; 12],
+            ciphertext: vec![3; 64],
+            recipient_wrapped_key: [4; 48],
+            auditor_wrapped_key,
+        }
+    }
+
+    #[test]
+    fn auditor_key_makes_notes_mandatory() {
+        let audited = bridge([5; 32]);
+        assert_eq!(
+            check_encrypted_note(&audited, None).unwrap_err(),
+            ErrorCode::AuditorNoteRequired.into()
+        );
+        assert_eq!(
+            check_encrypted_note(&audited, Some(&note([0; 48]))).unwrap_err(),
+            ErrorCode::AuditorNoteRequired.into()
+        );
+        check_encrypted_note(&audited, Some(&note([6; 48]))).unwrap();
+
+        let unaudited = bridge([0; 32]);
+        check_encrypted_note(&unaudited, None).unwrap();
+        check_encrypted_note(&unaudited, Some(&note([0; 48]))).unwrap();
+    }
+
+    #[test]
+    fn oversized_note_is_rejected() {
+        let mut large = note([6; 48]);
+        large.ciphertext = vec![0; EncryptedNote::MAX_CIPHERTEXT_LEN + 1];
+        assert_eq!(
+            check_encrypted_note(&bridge([0; 32]), Some(&large)).unwrap_err(),
+            ErrorCode::NoteTooLarge.into()
+        );
+    }
+
+    #[test]
+    fn completed_transaction_cannot_unlock_again() {
+        let mut tx = locked_tx();
+        tx.add_confirmation(1).unwrap();
+        tx.state = TransactionState::Completed;
+        assert_eq!(
+            check_unlock_state(1, &tx, &Pubkey::new_unique(), &[0; 32]).unwrap_err(),
+            ErrorCode::InvalidState.into()
+        );
+    }
+
+    /// Queued by the bridge's first owner
+    fn queued_proposal(bridge: &Bridge, action: GovernanceAction) -> GovernanceProposal {
+        GovernanceProposal {
+            id: 0,
+            proposer: bridge.multisig.owners[0],
+            action,
+            queued_at: 0,
+            eta: 100,
+            state: ProposalState::Queued,
+            approvals: bridge.multisig.propose(&bridge.multisig.owners[0]).unwrap(),
+            cancel_approvals: Approvals::none(&bridge.multisig),
+        }
+    }
+
+    fn approved_proposal(bridge: &Bridge, action: GovernanceAction) -> GovernanceProposal {
+        let mut proposal = queued_proposal(bridge, action);
+        proposal.approve(bridge, &bridge.multisig.owners[1]).unwrap();
+        proposal
+    }
+
+    #[test]
+    fn proposal_needs_threshold_approvals() {
+        let bridge = bridge([0; 32]);
+        let mut proposal = queued_proposal(&bridge, GovernanceAction::UpdateFee { new_fee: 5 });
+        assert_eq!(
+            proposal.execute(&bridge, 100).unwrap_err(),
+            MultisigError::NotEnoughApprovals.into()
+        );
+
+        assert_eq!(
+            proposal.approve(&bridge, &Pubkey::new_unique()).unwrap_err(),
+            MultisigError::NotOwner.into()
+        );
+        // Approving twice does not count twice
+        assert_eq!(proposal.approve(&bridge, &bridge.multisig.owners[0]).unwrap(), 1);
+        assert_eq!(proposal.approve(&bridge, &bridge.multisig.owners[2]).unwrap(), 2);
+        proposal.execute(&bridge, 100).unwrap();
+
+        assert_eq!(
+            proposal.approve(&bridge, &bridge.multisig.owners[1]).unwrap_err(),
+            ErrorCode::AlreadyExecuted.into()
+        );
+    }
+
+    #[test]
+    fn owner_change_makes_pending_proposals_stale() {
+        let mut bridge = bridge([0; 32]);
+        let mut proposal = queued_proposal(&bridge, GovernanceAction::UpdateFee { new_fee: 5 });
+        proposal.approve(&bridge, &bridge.multisig.owners[1]).unwrap();
+
+        let owners = bridge.multisig.owners.clone();
+        bridge.multisig.replace(owners, 2).unwrap();
+        assert_eq!(
+            proposal.approve(&bridge, &bridge.multisig.owners[2]).unwrap_err(),
+            MultisigError::StaleProposal.into()
+        );
+        assert_eq!(
+            proposal.execute(&bridge, 100).unwrap_err(),
+            MultisigError::StaleProposal.into()
+        );
+        assert_eq!(
+            proposal.approve_cancel(&bridge, &bridge.multisig.owners[2]).unwrap_err(),
+            MultisigError::StaleProposal.into()
+        );
+    }
+
+    #[test]
+    fn proposal_is_cancelled_by_threshold_owner_votes() {
+        let bridge = bridge([0; 32]);
+        let mut proposal = approved_proposal(&bridge, GovernanceAction::Unpause);
+        assert_eq!(
+            proposal.approve_cancel(&bridge, &Pubkey::new_unique()).unwrap_err(),
+            MultisigError::NotOwner.into()
+        );
+
+        // One owner cannot cancel alone, even by voting twice
+        assert_eq!(proposal.approve_cancel(&bridge, &bridge.multisig.owners[2]).unwrap(), 1);
+        assert_eq!(proposal.approve_cancel(&bridge, &bridge.multisig.owners[2]).unwrap(), 1);
+        assert!(proposal.state == ProposalState::Queued);
+
+        assert_eq!(proposal.approve_cancel(&bridge, &bridge.multisig.owners[0]).unwrap(), 2);
+        assert!(proposal.state == ProposalState::Cancelled);
+        assert_eq!(
+            proposal.execute(&bridge, 100).unwrap_err(),
+            ErrorCode::ProposalNotQueued.into()
+        );
+    }
+
+    #[test]
+    fn governance_actions_keep_their_tags() {
+        let tag = |action: GovernanceAction| action.try_to_vec().unwrap()[0];
+        assert_eq!(tag(GovernanceAction::UpdateFee { new_fee: 5 }), 0);
+        assert_eq!(tag(GovernanceAction::Unpause), 10);
+        assert_eq!(tag(GovernanceAction::SetChainFinality { chain_id: 2, finality_depth: 1 }), 20);
+        assert_eq!(tag(GovernanceAction::SetVerifyingKey { verifying_key_hash: [1; 32] }), 21);
+    }
+
+    /// Writable account at `key` holding `data`, owned by `owner`
+    fn account_at(key: Pubkey, owner: Pubkey, data: Vec<u8>, is_signer: bool) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(1_000_000_000)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        )
+    }
+
+    /// Account at a fresh address holding `data`, owned by `owner`
+    fn account_info(owner: Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+        account_at(Pubkey::new_unique(), owner, data, false)
+    }
+
+    /// Run `AcceptAuthority` validation for `signer` against `bridge`
+    fn accept_authority_accounts(bridge: &Bridge, signer: Pubkey) -> Result<()> {
+        let mut data = Vec::new();
+        bridge.try_serialize(&mut data).unwrap();
+        let infos = vec![
+            account_at(Pubkey::find_program_address(&[b"bridge"], &ID).0, ID, data, false),
+            account_at(signer, system_program::ID, Vec::new(), true),
+        ];
+        let mut infos: &'static [AccountInfo<'static>] = Box::leak(infos.into_boxed_slice());
+        AcceptAuthority::try_accounts(
+            &ID,
+            &mut infos,
+            &[],
+            &mut AcceptAuthorityBumps::default(),
+            &mut BTreeSet::new(),
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn only_the_pending_authority_accepts() {
+        let mut bridge = bridge([0; 32]);
+        assert_eq!(
+            accept_authority_accounts(&bridge, bridge.authority).unwrap_err(),
+            ErrorCode::NoPendingAuthority.into()
+        );
+
+        let pending = Pubkey::new_unique();
+        bridge.pending_authority = pending;
+        for signer in [bridge.authority, Pubkey::new_unique()] {
+            assert_eq!(
+                accept_authority_accounts(&bridge, signer).unwrap_err(),
+                ErrorCode::NotPendingAuthority.into()
+            );
+        }
+        accept_authority_accounts(&bridge, pending).unwrap();
+    }
+
+    #[test]
+    fn unregistered_chain_stays_open() {
+        let unregistered = account_info(system_program::ID, Vec::new());
+        let chain_config = load_chain_config(&unregistered, 2).unwrap();
+        assert_eq!(chain_config.chain_id, 2);
+        assert!(chain_config.supported);
+        assert_eq!(chain_config.finality_depth, DEFAULT_FINALITY_DEPTH);
+        let token_config = TokenConfig {
+            mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            pause_flags: 0,
+            total_locked: 0,
+            total_unlocked: 0,
+        };
+        require_not_paused(&bridge([0; 32]), &chain_config, &token_config, PAUSE_ALL).unwrap();
+
+        // Once registered, the stored settings apply
+        let mut data = Vec::new();
+        ChainConfig { supported: false, pause_flags: PAUSE_LOCK, ..ChainConfig::unregistered(2) }
+            .try_serialize(&mut data)
+            .unwrap();
+        let chain_config = load_chain_config(&account_info(ID, data), 2).unwrap();
+        assert!(!chain_config.supported);
+        assert_eq!(
+            require_not_paused(&bridge([0; 32]), &chain_config, &token_config, PAUSE_LOCK).unwrap_err(),
+            ErrorCode::ChainPaused.into()
+        );
+    }
+
+    #[test]
+    fn unpause_skips_the_timelock() {
+        let mut bridge = bridge([0; 32]);
+        assert_eq!(GovernanceAction::Unpause.timelock(bridge.governance_delay), 0);
+        assert_eq!(
+            GovernanceAction::UpdateFee { new_fee: 5 }.timelock(bridge.governance_delay),
+            bridge.governance_delay
+        );
+
+        // No delay, but still the owner threshold
+        let mut proposal = queued_proposal(&bridge, GovernanceAction::Unpause);
+        assert_eq!(
+            proposal.execute_matching(&bridge, &GovernanceAction::Unpause, 100).unwrap_err(),
+            MultisigError::NotEnoughApprovals.into()
+        );
+        proposal.approve(&bridge, &bridge.multisig.owners[1]).unwrap();
+        proposal.execute_matching(&bridge, &GovernanceAction::Unpause, 100).unwrap();
+
+        bridge.paused = true;
+        bridge.pause_flags = PAUSE_ALL;
+        bridge.paused_at = 50;
+        bridge.unpause();
+        assert!(!bridge.paused);
+        assert_eq!((bridge.pause_flags, bridge.paused_at), (0, 0));
+    }
+
+    #[test]
+    fn authority_transfers_execute_their_own_proposals() {
+        let mut bridge = bridge([0; 32]);
+        let new_authority = Pubkey::new_unique();
+        let propose = GovernanceAction::ProposeAuthority { new_authority };
+        assert_eq!(propose.timelock(bridge.governance_delay), bridge.governance_delay);
+        assert_eq!(GovernanceAction::CancelAuthorityTransfer.timelock(bridge.governance_delay), 0);
+
+        // A proposal for another key, or before its eta, does not execute
+        let mut proposal = approved_proposal(&bridge, propose.clone());
+        assert_eq!(
+            proposal
+                .execute_matching(
+                    &bridge,
+                    &GovernanceAction::ProposeAuthority { new_authority: Pubkey::new_unique() },
+                    100,
+                )
+                .unwrap_err(),
+            ErrorCode::ProposalActionMismatch.into()
+        );
+        assert_eq!(
+            proposal.execute_matching(&bridge, &propose, 99).unwrap_err(),
+            ErrorCode::TimelockNotElapsed.into()
+        );
+        proposal.execute_matching(&bridge, &propose, 100).unwrap();
+
+        assert_eq!(
+            bridge.propose_authority(Pubkey::default()).unwrap_err(),
+            ErrorCode::InvalidAuthority.into()
+        );
+        bridge.propose_authority(new_authority).unwrap();
+        assert_eq!(bridge.pending_authority, new_authority);
+
+        // Cancelling still needs the owner threshold
+        let mut cancel = queued_proposal(&bridge, GovernanceAction::CancelAuthorityTransfer);
+        assert_eq!(
+            cancel
+                .execute_matching(&bridge, &GovernanceAction::CancelAuthorityTransfer, 100)
+                .unwrap_err(),
+            MultisigError::NotEnoughApprovals.into()
+        );
+        cancel.approve(&bridge, &bridge.multisig.owners[1]).unwrap();
+        cancel.execute_matching(&bridge, &GovernanceAction::CancelAuthorityTransfer, 100).unwrap();
+
+        assert_eq!(bridge.cancel_authority_transfer().unwrap(), new_authority);
+        assert_eq!(bridge.pending_authority, Pubkey::default());
+        assert_eq!(
+            bridge.cancel_authority_transfer().unwrap_err(),
+            ErrorCode::NoPendingAuthority.into()
+        );
+    }
+
+    #[test]
+    fn pause_flags_are_checked_per_scope_and_operation() {
+        let token_config = |pause_flags| TokenConfig {
+            mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            pause_flags,
+            total_locked: 0,
+            total_unlocked: 0,
+        };
+        let chain_config = |pause_flags| ChainConfig { pause_flags, ..ChainConfig::unregistered(2) };
+        let bridge_with = |pause_flags| Bridge { pause_flags, ..bridge([0; 32]) };
+        let check = |bridge: &Bridge, chain: u8, token: u8, operation| {
+            require_not_paused(bridge, &chain_config(chain), &token_config(token), operation)
+        };
+
+        for operation in [PAUSE_LOCK, PAUSE_UNLOCK, PAUSE_RELAY] {
+            let others = PAUSE_ALL & !operation;
+            // Other operations' flags never block this one, at any scope
+            check(&bridge_with(others), others, others, operation).unwrap();
+
+            assert_eq!(
+                check(&bridge_with(operation), 0, 0, operation).unwrap_err(),
+                ErrorCode::BridgePaused.into()
+            );
+            assert_eq!(
+                check(&bridge_with(0), operation, 0, operation).unwrap_err(),
+                ErrorCode::ChainPaused.into()
+            );
+            assert_eq!(
+                check(&bridge_with(0), 0, operation, operation).unwrap_err(),
+                ErrorCode::TokenPaused.into()
+            );
+        }
+
+        // The authority's pause stops everything
+        let paused = Bridge { paused: true, ..bridge([0; 32]) };
+        assert_eq!(check(&paused, 0, 0, PAUSE_LOCK).unwrap_err(), ErrorCode::BridgePaused.into());
+    }
+
+    #[test]
+    fn audit_pauses_a_token_only_when_its_vault_is_short() {
+        let mut token_config = TokenConfig {
+            mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            pause_flags: PAUSE_RELAY,
+            total_locked: 1_500,
+            total_unlocked: 500,
+        };
+        assert_eq!(token_config.liabilities(), 1_000);
+
+        // Surplus in the vault is fine
+        assert_eq!(token_config.audit(1_200), 0);
+        assert_eq!(token_config.audit(1_000), 0);
+        assert_eq!(token_config.pause_flags, PAUSE_RELAY);
+
+        assert_eq!(token_config.audit(999), 1);
+        assert_eq!(token_config.pause_flags, PAUSE_ALL);
+    }
+
+    #[test]
+    fn audit_pairs_are_checked_by_token_address() {
+        let token_config = TokenConfig {
+            mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            pause_flags: 0,
+            total_locked: 0,
+            total_unlocked: 0,
+        };
+        let address = Pubkey::find_program_address(&[b"token", token_config.mint.as_ref()], &ID).0;
+        check_audit_pair(&ID, &address, &token_config, &token_config.vault).unwrap();
+
+        assert_eq!(
+            check_audit_pair(&ID, &Pubkey::new_unique(), &token_config, &token_config.vault)
+                .unwrap_err(),
+            ErrorCode::InvalidAuditAccounts.into()
+        );
+        assert_eq!(
+            check_audit_pair(&ID, &address, &token_config, &Pubkey::new_unique()).unwrap_err(),
+            ErrorCode::InvalidVault.into()
+        );
+    }
+
+    #[test]
+    fn guardian_halts_open_emergency_mode() {
+        let mut bridge = bridge([0; 32]);
+        let delay = bridge.emergency_delay;
+        assert!(!bridge.emergency_mode_active(i64::MAX));
+
+        // Locks alone do not trap deposits
+        bridge.pause_flags = PAUSE_LOCK;
+        bridge.track_halt(false, 100);
+        assert!(!bridge.halted());
+        assert!(!bridge.emergency_mode_active(i64::MAX));
+
+        bridge.pause_flags |= PAUSE_UNLOCK;
+        bridge.track_halt(false, 100);
+        assert!(!bridge.emergency_mode_active(100 + delay - 1));
+        assert!(bridge.emergency_mode_active(100 + delay));
+
+        // A later authority pause does not restart the clock
+        bridge.paused = true;
+        bridge.track_halt(true, 500);
+        assert_eq!(bridge.paused_at, 100);
+
+        bridge.unpause();
+        assert!(!bridge.emergency_mode_active(i64::MAX));
+    }
+
+    #[test]
+    fn reclaim_refunds_only_unrelayed_locks() {
+        let mut epoch = empty_epoch();
+        let mut tx = locked_tx();
+        assert_eq!(tx.refund(&mut epoch).unwrap(), 1_000);
+        assert!(tx.state == TransactionState::Refunded);
+        assert_eq!(tx.refund(&mut epoch).unwrap_err(), ErrorCode::InvalidState.into());
+
+        let mut relayed = locked_tx();
+        relayed.add_confirmation(1).unwrap();
+        assert_eq!(relayed.refund(&mut epoch).unwrap_err(), ErrorCode::InvalidState.into());
+
+        let mut other_epoch = locked_tx();
+        other_epoch.outbound_epoch = 1;
+        assert_eq!(other_epoch.refund(&mut epoch).unwrap_err(), ErrorCode::InvalidEpoch.into());
+    }
+
+    #[test]
+    fn reclaiming_a_lock_from_a_finalized_epoch_fails() {
+        let mut epoch = empty_epoch();
+        let mut tx = locked_tx();
+        tx.leaf_index = epoch.append(outbound_leaf(&tx)).unwrap();
+        epoch.finalize(10);
+        assert_eq!(epoch.root, epoch.compute_root());
+
+        assert_eq!(tx.refund(&mut epoch).unwrap_err(), ErrorCode::EpochFinalized.into());
+        assert!(tx.state == TransactionState::Locked);
+        assert!(!epoch.withheld);
+    }
+
+    #[test]
+    fn reclaiming_before_finalization_withholds_the_epoch_root() {
+        let mut epoch = empty_epoch();
+        let (mut reclaimed, mut stranded) = (locked_tx(), locked_tx());
+        stranded.id = [3; 32];
+        epoch.append(outbound_leaf(&reclaimed)).unwrap();
+        stranded.leaf_index = epoch.append(outbound_leaf(&stranded)).unwrap();
+        assert_eq!(stranded.leaf_index, 1);
+
+        reclaimed.refund(&mut epoch).unwrap();
+        assert!(epoch.withheld);
+        epoch.finalize(10);
+        assert!(epoch.finalized);
+        assert_eq!(epoch.root, [0; 32]);
+
+        // No root carries the other lock, so it stays reclaimable
+        assert_eq!(stranded.refund(&mut epoch).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn sweep_takes_only_the_surplus_over_liabilities() {
+        let mut token_config = TokenConfig {
+            mint: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            pause_flags: 0,
+            total_locked: 3_000,
+            total_unlocked: 1_000,
+        };
+        assert_eq!(token_config.surplus(2_500), 500);
+        assert_eq!(token_config.surplus(2_000), 0);
+        assert_eq!(token_config.surplus(1_500), 0);
+
+        // A reclaim settles its share of the liabilities
+        token_config.total_unlocked += 1_000;
+        assert_eq!(token_config.liabilities(), 1_000);
+        assert_eq!(token_config.surplus(1_500), 500);
+    }
+
+    #[test]
+    fn governance_delay_is_bounded() {
+        validate_governance_delay(MIN_GOVERNANCE_DELAY).unwrap();
+        validate_governance_delay(MAX_GOVERNANCE_DELAY).unwrap();
+        for delay in [0, MIN_GOVERNANCE_DELAY - 1, MAX_GOVERNANCE_DELAY + 1] {
+            assert_eq!(
+                validate_governance_delay(delay).unwrap_err(),
+                ErrorCode::InvalidGovernanceDelay.into()
+            );
+        }
+    }
+
+    #[test]
+    fn proposal_executes_once_within_its_window() {
+        let bridge = bridge([0; 32]);
+        let approved = |action| approved_proposal(&bridge, action);
+
+        let mut early = approved(GovernanceAction::UpdateFee { new_fee: 5 });
+        assert_eq!(early.execute(&bridge, 99).unwrap_err(), ErrorCode::TimelockNotElapsed.into());
+
+        let mut late = approved(GovernanceAction::UpdateFee { new_fee: 5 });
+        assert_eq!(
+            late.execute(&bridge, 101 + GOVERNANCE_GRACE_PERIOD).unwrap_err(),
+            ErrorCode::ProposalExpired.into()
+        );
+
+        let mut cancelled = approved(GovernanceAction::UpdateFee { new_fee: 5 });
+        cancelled.state = ProposalState::Cancelled;
+        assert_eq!(
+            cancelled.execute(&bridge, 100).unwrap_err(),
+            ErrorCode::ProposalNotQueued.into()
+        );
+
+        let mut proposal = approved(GovernanceAction::UpdateFee { new_fee: 5 });
+        proposal.execute(&bridge, 100).unwrap();
+        assert!(proposal.state == ProposalState::Executed);
+        assert_eq!(proposal.execute(&bridge, 100).unwrap_err(), ErrorCode::AlreadyExecuted.into());
+    }
+
+    #[test]
+    fn account_creating_actions_must_match_the_proposal() {
+        let bridge = bridge([0; 32]);
+        let guardian = Pubkey::new_unique();
+        let mut proposal =
+            approved_proposal(&bridge, GovernanceAction::SetGuardian { guardian, active: true });
+
+        for action in [
+            GovernanceAction::SetGuardian { guardian, active: false },
+            GovernanceAction::SetGuardian { guardian: Pubkey::new_unique(), active: true },
+            GovernanceAction::AddRelayer { relayer_authority: guardian },
+        ] {
+            assert_eq!(
+                proposal.execute_matching(&bridge, &action, 100).unwrap_err(),
+                ErrorCode::ProposalActionMismatch.into()
+            );
+        }
+        assert!(proposal.state == ProposalState::Queued);
+
+        proposal
+            .execute_matching(&bridge, &GovernanceAction::SetGuardian { guardian, active: true }, 100)
+            .unwrap();
+        assert!(proposal.state == ProposalState::Executed);
+    }
+
+    fn empty_epoch() -> OutboundEpoch {
+        OutboundEpoch {
+            epoch_id: 0,
+            frontier: [[0; 32]; OUTBOUND_TREE_DEPTH],
+            leaf_count: 0,
+            started_at: 1,
+            finalized: false,
+            finalized_at: 0,
+            root: [0; 32],
+            withheld: false,
+        }
+    }
+
+    fn epoch_leaf(index: u32) -> [u8; 32] {
+        let mut leaf = [0xff; 32];
+        leaf[..4].copy_from_slice(&index.to_le_bytes());
+        leaf
+    }
+
+    /// Root of the full-depth tree over `count` leaves, hashing every level
+    fn reference_root(count: u32) -> [u8; 32] {
+        let mut level: Vec<[u8; 32]> = (0..count).map(epoch_leaf).collect();
+        level.resize(1 << OUTBOUND_TREE_DEPTH, [0; 32]);
+        while level.len() > 1 {
+            level = level.chunks(2).map(|pair| outbound_node(&pair[0], &pair[1])).collect();
+        }
+        level[0]
+    }
+
+    #[test]
+    fn epoch_root_matches_the_full_tree() {
+        let mut epoch = empty_epoch();
+        assert_eq!(epoch.compute_root(), reference_root(0));
+
+        for index in 0..5 {
+            assert_eq!(epoch.append(epoch_leaf(index)).unwrap(), index);
+        }
+        assert_eq!(epoch.compute_root(), reference_root(5));
+    }
+
+    #[test]
+    fn epoch_fills_to_capacity() {
+        let mut epoch = empty_epoch();
+        for index in 0..MAX_EPOCH_LEAVES - 1 {
+            epoch.append(epoch_leaf(index)).unwrap();
+        }
+        assert!(!epoch.is_full());
+        assert_eq!(epoch.compute_root(), reference_root(MAX_EPOCH_LEAVES - 1));
+
+        epoch.append(epoch_leaf(MAX_EPOCH_LEAVES - 1)).unwrap();
+        assert!(epoch.is_full());
+        let root = reference_root(MAX_EPOCH_LEAVES);
+        assert_eq!(epoch.compute_root(), root);
+        assert_eq!(
+            epoch.append(epoch_leaf(MAX_EPOCH_LEAVES)).unwrap_err(),
+            ErrorCode::EpochFull.into()
+        );
+        assert_eq!(epoch.compute_root(), root);
+    }
+
+    fn unlock_item(nullifier: u8) -> BatchUnlockItem {
+        BatchUnlockItem {
+            proof: ZkProof { a: [1; 64], b: [2; 128], c: [3; 64] },
+            nullifier: [nullifier; 32],
+            recipient_blinding: [0; 32],
+        }
+    }
+
+    fn proof_inputs(nullifier: u8) -> ProofPublicInputs {
+        ProofPublicInputs {
+            commitment: [9; 32],
+            nullifier: [nullifier; 32],
+            amount: 1_000,
+            recipient: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn batch_size_is_bounded() {
+        assert_eq!(check_batch_size(0).unwrap_err(), ErrorCode::InvalidBatchSize.into());
+        check_batch_size(1).unwrap();
+        check_batch_size(MAX_BATCH_SIZE).unwrap();
+        assert_eq!(
+            check_batch_size(MAX_BATCH_SIZE + 1).unwrap_err(),
+            ErrorCode::InvalidBatchSize.into()
+        );
+    }
+
+    #[test]
+    fn batch_items_get_distinct_tx_ids() {
+        let base = generate_tx_id(&Pubkey::new_unique(), 2, &[4; 32], 100);
+        let ids: Vec<[u8; 32]> = (0..MAX_BATCH_SIZE as u8)
+            .map(|index| generate_batch_tx_id(&base, index))
+            .collect();
+        for (index, id) in ids.iter().enumerate() {
+            assert_ne!(*id, base);
+            assert!(ids[..index].iter().all(|other| other != id));
+        }
+    }
+
+    #[test]
+    fn batch_rejects_a_repeated_nullifier() {
+        check_unique_nullifiers(&[unlock_item(1), unlock_item(2), unlock_item(3)]).unwrap();
+        assert_eq!(
+            check_unique_nullifiers(&[unlock_item(1), unlock_item(2), unlock_item(1)]).unwrap_err(),
+            ErrorCode::NullifierUsed.into()
+        );
+    }
+
+    #[test]
+    fn proof_batch_needs_one_input_set_per_proof() {
+        let items = [unlock_item(1), unlock_item(2)];
+        let proofs: Vec<&ZkProof> = items.iter().map(|item| &item.proof).collect();
+
+        let key = [7; 32];
+        assert!(verify_proof_batch(&key, &proofs, &[proof_inputs(1), proof_inputs(2)]).unwrap());
+        assert!(!verify_proof_batch(&key, &proofs, &[proof_inputs(1)]).unwrap());
+        assert!(!verify_proof_batch(&key, &[], &[]).unwrap());
+    }
+
+    #[test]
+    fn proof_batch_weights_commit_to_every_proof_and_input() {
+        let items = [unlock_item(1), unlock_item(2)];
+        let proofs: Vec<&ZkProof> = items.iter().map(|item| &item.proof).collect();
+        let inputs = [proof_inputs(1), proof_inputs(2)];
+        let weights = batch_proof_weights(&proofs, &inputs);
+        assert_eq!(weights.len(), 2);
+        assert_ne!(weights[0], weights[1]);
+
+        // Changing the last input or proof changes the first proof's weight too
+        let mut changed_input = [proof_inputs(1), proof_inputs(2)];
+        changed_input[0].recipient = inputs[0].recipient;
+        changed_input[1].recipient = inputs[1].recipient;
+        changed_input[1].amount += 1;
+        assert_ne!(batch_proof_weights(&proofs, &changed_input)[0], weights[0]);
+
+        let mut changed_item = unlock_item(2);
+        changed_item.proof.c[0] ^= 1;
+        let changed_proofs = [proofs[0], &changed_item.proof];
+        assert_ne!(batch_proof_weights(&changed_proofs, &inputs)[0], weights[0]);
+    }
+}
@@ -0,0 +1,154 @@
+pragma circom 2.0.0;
+
+include "../node_modules/ed25519-circom/circuits/scalarmul.circom";
+include "../node_modules/ed25519-circom/circuits/point-addition.circom";
+include "../node_modules/ed25519-circom/circuits/pointcompress.circom";
+include "../node_modules/keccak256-circom/circuits/keccak.circom";
+
+/**
+ * Stealth Address Circuit (ed25519 / keccak)
+ *
+ * Proves ownership of a privacy_bridge stealth address, as derived by
+ * assets/contracts/rust/bridge_client/src/stealth_address.rs:
+ *
+ *   sharedSecret   = v * R
+ *   tweak          = keccak256("safemask-stealth-v1" || sharedSecret) mod l
+ *   stealthAddress = (s + tweak) * G
+ *
+ * Points use ed25519-circom's extended coordinates [X, Y, Z, T], each
+ * coordinate as three 85-bit limbs. Byte strings are little-endian bits,
+ * byte by byte, matching both the point encoding and keccak256-circom.
+ *
+ * Public inputs:
+ * - ephemeralPublicKey: compressed R from the lock's `StealthMeta`
+ *
+ * Private inputs:
+ * - spendSecret, viewSecret: recipient's secret scalars s and v
+ * - ephemeralPoint: R in extended coordinates, bound to ephemeralPublicKey
+ *
+ * Output:
+ * - stealthAddress: compressed stealth public key, the Solana recipient
+ */
+
+template StealthAddressEd25519() {
+    signal input ephemeralPublicKey[256];
+    signal input ephemeralPoint[4][3];
+    signal input spendSecret[255];
+    signal input viewSecret[255];
+
+    signal output stealthAddress[256];
+
+    var G[4][3] = [
+        [6836562328990639286768922, 21231440843933962135602345, 10097852978535018773096760],
+        [7737125245533626718119512, 23211375736600880154358579, 30948500982134506872478105],
+        [1, 0, 0],
+        [20943500354259764865654179, 24722277920680796426601402, 31289658119428895172835987]
+    ];
+    // 2^255 * G, for the top bit of the 256-bit tweak
+    var G255[4][3] = [
+        [1592543078678797173317484, 4058207221478190725249355, 5427475244123947770801016],
+        [17418717167928495186398062, 9743908896362119002030157, 19809748626801243874872090],
+        [1, 0, 0],
+        [32890220404141344091822033, 2994703213239254256691567, 551592228781850626422059]
+    ];
+    // "safemask-stealth-v1"
+    var DOMAIN[19] = [115, 97, 102, 101, 109, 97, 115, 107, 45, 115, 116, 101, 97, 108, 116, 104, 45, 118, 49];
+
+    var i;
+    var j;
+
+    // 1. Bind the ephemeral point to its public encoding
+    component ephemeral = PointCompress();
+    for (i = 0; i < 4; i++) {
+        for (j = 0; j < 3; j++) {
+            ephemeral.P[i][j] <== ephemeralPoint[i][j];
+        }
+    }
+    for (i = 0; i < 256; i++) {
+        ephemeral.out[i] === ephemeralPublicKey[i];
+    }
+
+    // 2. Shared secret: sharedSecret = v * R
+    component shared = ScalarMul();
+    for (i = 0; i < 255; i++) {
+        shared.s[i] <== viewSecret[i];
+    }
+    for (i = 0; i < 4; i++) {
+        for (j = 0; j < 3; j++) {
+            shared.P[i][j] <== ephemeralPoint[i][j];
+        }
+    }
+    component sharedBits = PointCompress();
+    for (i = 0; i < 4; i++) {
+        for (j = 0; j < 3; j++) {
+            sharedBits.P[i][j] <== shared.sP[i][j];
+        }
+    }
+
+    // 3. Tweak: keccak256(domain || sharedSecret)
+    component hasher = Keccak((19 + 32) * 8, 256);
+    for (i = 0; i < 19; i++) {
+        for (j = 0; j < 8; j++) {
+            hasher.in[i * 8 + j] <== (DOMAIN[i] >> j) & 1;
+        }
+    }
+    for (i = 0; i < 256; i++) {
+        hasher.in[19 * 8 + i] <== sharedBits.out[i];
+    }
+
+    // 4. tweak * G; G has order l, so the reduction mod l is implicit.
+    // The low 255 bits go through ScalarMul, the top bit adds 2^255 * G.
+    component tweakLow = ScalarMul();
+    for (i = 0; i < 255; i++) {
+        tweakLow.s[i] <== hasher.out[i];
+    }
+    for (i = 0; i < 4; i++) {
+        for (j = 0; j < 3; j++) {
+            tweakLow.P[i][j] <== G[i][j];
+        }
+    }
+    component tweakHigh = PointAdd();
+    for (i = 0; i < 4; i++) {
+        for (j = 0; j < 3; j++) {
+            tweakHigh.P[i][j] <== tweakLow.sP[i][j];
+            tweakHigh.Q[i][j] <== G255[i][j];
+        }
+    }
+    signal tweakPoint[4][3];
+    for (i = 0; i < 4; i++) {
+        for (j = 0; j < 3; j++) {
+            tweakPoint[i][j] <== tweakLow.sP[i][j]
+                + hasher.out[255] * (tweakHigh.R[i][j] - tweakLow.sP[i][j]);
+        }
+    }
+
+    // 5. Stealth public key: s * G + tweak * G
+    component spend = ScalarMul();
+    for (i = 0; i < 255; i++) {
+        spend.s[i] <== spendSecret[i];
+    }
+    for (i = 0; i < 4; i++) {
+        for (j = 0; j < 3; j++) {
+            spend.P[i][j] <== G[i][j];
+        }
+    }
+    component stealth = PointAdd();
+    for (i = 0; i < 4; i++) {
+        for (j = 0; j < 3; j++) {
+            stealth.P[i][j] <== spend.sP[i][j];
+            stealth.Q[i][j] <== tweakPoint[i][j];
+        }
+    }
+
+    component address = PointCompress();
+    for (i = 0; i < 4; i++) {
+        for (j = 0; j < 3; j++) {
+            address.P[i][j] <== stealth.R[i][j];
+        }
+    }
+    for (i = 0; i < 256; i++) {
+        stealthAddress[i] <== address.out[i];
+    }
+}
+
+component main {public [ephemeralPublicKey]} = StealthAddressEd25519();